@@ -0,0 +1,709 @@
+//! Text files and ANSI art rendered into in-memory frames.
+//!
+//! ANSI art colors runs of characters with SGR escape sequences such as
+//! `ESC[31m` (red foreground) or `ESC[0m` (reset). This module splits a text
+//! file into lines of colored `Span`s, picks the largest BDF font that fits
+//! them on the panel, and draws the result into an `RgbImage`.
+//!
+//! Only foreground colors are honored. Other escape sequences (cursor
+//! movement, background colors, blinking) are skipped.
+//!
+//! The same spans back scrolling text, so this module also word-wraps them
+//! for `TextMode::Auto`, which shows a message statically when it fits.
+//!
+//! ## Rust concepts
+//! - `Peekable` iterators for hand-written tokenizers
+//! - `&mut` state threaded through a parser
+//! - `Iterator::max_by_key` / `min_by_key` to choose between candidates
+
+use crate::Color;
+use crate::bdf::BdfFont;
+use image::{Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A run of characters drawn in the same color.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub text: String,
+    pub color: Color,
+}
+
+/// How scrolling-text requests are laid out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TextMode {
+    /// Always scroll the text as one line
+    #[default]
+    Scroll,
+    /// Word-wrap and center the text if it fits the panel, otherwise scroll
+    Auto,
+}
+
+/// Where a single line of text sits vertically on the panel.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum VerticalPosition {
+    /// Centered using the font's height
+    #[default]
+    Center,
+    /// Top of the line this many pixels from the top of the panel
+    Pixels(i32),
+    /// 0.0 puts the line at the top, 1.0 at the bottom, 0.5 centers it
+    Fraction(f32),
+}
+
+impl VerticalPosition {
+    /// Top y for a line of `font` text on a panel `height` pixels tall.
+    pub fn top(self, font: &BdfFont, height: u32) -> i32 {
+        let free = height as i32 - font.height();
+        match self {
+            VerticalPosition::Center => free / 2,
+            VerticalPosition::Pixels(y) => y,
+            VerticalPosition::Fraction(f) => (free as f32 * f).round() as i32,
+        }
+    }
+
+    /// Baseline y for a line of `font` text on a panel `height` pixels tall.
+    pub fn baseline(self, font: &BdfFont, height: u32) -> i32 {
+        self.top(font, height) + font.ascent()
+    }
+}
+
+/// The 16 standard ANSI colors: 0-7 normal, 8-15 bright (VGA palette).
+const ANSI_PALETTE: [Color; 16] = [
+    Color::new(0, 0, 0),
+    Color::new(170, 0, 0),
+    Color::new(0, 170, 0),
+    Color::new(170, 85, 0),
+    Color::new(0, 0, 170),
+    Color::new(170, 0, 170),
+    Color::new(0, 170, 170),
+    Color::new(170, 170, 170),
+    Color::new(85, 85, 85),
+    Color::new(255, 85, 85),
+    Color::new(85, 255, 85),
+    Color::new(255, 255, 85),
+    Color::new(85, 85, 255),
+    Color::new(255, 85, 255),
+    Color::new(85, 255, 255),
+    Color::new(255, 255, 255),
+];
+
+// ── Parsing ──────────────────────────────────────────────────────────
+
+/// Foreground color state carried across SGR sequences and lines.
+struct Pen {
+    /// Explicit color, or `None` for the default
+    color: Option<Color>,
+    /// Index into the normal palette, remembered so bold can brighten it
+    palette_index: Option<usize>,
+    bold: bool,
+}
+
+impl Pen {
+    fn current(&self, default: Color) -> Color {
+        match self.palette_index {
+            Some(i) if self.bold && i < 8 => ANSI_PALETTE[i + 8],
+            Some(i) => ANSI_PALETTE[i],
+            None => self.color.unwrap_or(default),
+        }
+    }
+
+    fn set_palette(&mut self, index: usize) {
+        self.palette_index = Some(index);
+        self.color = None;
+    }
+
+    fn set_rgb(&mut self, color: Color) {
+        self.palette_index = None;
+        self.color = Some(color);
+    }
+
+    fn reset(&mut self) {
+        self.palette_index = None;
+        self.color = None;
+        self.bold = false;
+    }
+
+    /// Apply the parameters of one SGR sequence (`ESC[...m`).
+    fn apply_sgr(&mut self, params: &[u16]) {
+        // `ESC[m` with no parameters means reset
+        if params.is_empty() {
+            self.reset();
+            return;
+        }
+
+        let mut params = params.iter().copied();
+        while let Some(code) = params.next() {
+            match code {
+                0 => self.reset(),
+                1 => self.bold = true,
+                22 => self.bold = false,
+                30..=37 => self.set_palette((code - 30) as usize),
+                90..=97 => self.set_palette((code - 90 + 8) as usize),
+                39 => {
+                    self.palette_index = None;
+                    self.color = None;
+                }
+                38 => match params.next() {
+                    // 256-color: ESC[38;5;Nm
+                    Some(5) => {
+                        if let Some(n) = params.next() {
+                            self.set_rgb(xterm_256(n.min(255) as u8));
+                        }
+                    }
+                    // Truecolor: ESC[38;2;R;G;Bm
+                    Some(2) => {
+                        let mut channel = || params.next().unwrap_or(0).min(255) as u8;
+                        let (r, g, b) = (channel(), channel(), channel());
+                        self.set_rgb(Color::new(r, g, b));
+                    }
+                    _ => {}
+                },
+                // Background colors carry their own arguments; skip them too
+                48 => match params.next() {
+                    Some(5) => {
+                        params.next();
+                    }
+                    Some(2) => {
+                        params.nth(2);
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Map an xterm 256-color index to RGB.
+fn xterm_256(n: u8) -> Color {
+    match n {
+        0..=15 => ANSI_PALETTE[n as usize],
+        16..=231 => {
+            // 6x6x6 color cube
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let i = n - 16;
+            Color::new(level(i / 36), level((i / 6) % 6), level(i % 6))
+        }
+        232..=255 => {
+            let grey = 8 + (n - 232) * 10;
+            Color::new(grey, grey, grey)
+        }
+    }
+}
+
+/// Read the parameters of a CSI sequence after `ESC[`, returning them with
+/// the final byte (e.g. `'m'` for SGR).
+fn read_csi(chars: &mut Peekable<Chars>) -> (Vec<u16>, Option<char>) {
+    let mut params = Vec::new();
+    let mut current: Option<u16> = None;
+
+    for c in chars.by_ref() {
+        match c {
+            '0'..='9' => {
+                let digit = c as u16 - '0' as u16;
+                current = Some(
+                    current
+                        .unwrap_or(0)
+                        .saturating_mul(10)
+                        .saturating_add(digit),
+                );
+            }
+            ';' => params.push(current.take().unwrap_or(0)),
+            // Final bytes of a CSI sequence are in the range '@'..='~'
+            '@'..='~' => {
+                if let Some(value) = current {
+                    params.push(value);
+                }
+                return (params, Some(c));
+            }
+            _ => {}
+        }
+    }
+
+    (params, None)
+}
+
+/// Split text into lines of colored spans.
+///
+/// With `honor_colors` false, escape sequences are still removed but every
+/// span uses `default`. Trailing whitespace is dropped from each line.
+pub fn parse(text: &str, default: Color, honor_colors: bool) -> Vec<Vec<Span>> {
+    let mut pen = Pen {
+        color: None,
+        palette_index: None,
+        bold: false,
+    };
+    let mut lines = Vec::new();
+
+    for raw_line in text.lines() {
+        let mut spans: Vec<Span> = Vec::new();
+        let mut chars = raw_line.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                if chars.peek() == Some(&'[') {
+                    chars.next();
+                    let (params, final_byte) = read_csi(&mut chars);
+                    if final_byte == Some('m') && honor_colors {
+                        pen.apply_sgr(&params);
+                    }
+                }
+                continue;
+            }
+            if c.is_control() && c != '\t' {
+                continue;
+            }
+
+            let color = pen.current(default);
+            let text = if c == '\t' {
+                "    ".to_string()
+            } else {
+                c.to_string()
+            };
+            match spans.last_mut() {
+                Some(span) if span.color == color => span.text.push_str(&text),
+                _ => spans.push(Span { text, color }),
+            }
+        }
+
+        trim_trailing_whitespace(&mut spans);
+        lines.push(spans);
+    }
+
+    // Blank lines at the end of a file would only waste vertical space
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+
+    lines
+}
+
+fn trim_trailing_whitespace(spans: &mut Vec<Span>) {
+    while let Some(span) = spans.last_mut() {
+        let trimmed_len = span.text.trim_end().len();
+        if trimmed_len > 0 {
+            span.text.truncate(trimmed_len);
+            return;
+        }
+        spans.pop();
+    }
+}
+
+// ── Layout and drawing ───────────────────────────────────────────────
+
+/// Size in pixels of `lines` drawn with `font`, one font height per line.
+pub fn text_size(font: &BdfFont, lines: &[Vec<Span>]) -> (i32, i32) {
+    let width = lines
+        .iter()
+        .map(|spans| spans.iter().map(|s| font.text_width(&s.text)).sum())
+        .max()
+        .unwrap_or(0);
+    (width, font.height() * lines.len() as i32)
+}
+
+/// Pick the tallest font in which `lines` fit a `width`x`height` panel.
+///
+/// If nothing fits, the smallest font is returned so at least the top-left
+/// of the text is visible. Returns `None` only when `fonts` is empty.
+pub fn choose_font<'a>(
+    fonts: &'a [BdfFont],
+    lines: &[Vec<Span>],
+    width: u32,
+    height: u32,
+) -> Option<&'a BdfFont> {
+    fonts
+        .iter()
+        .filter(|font| {
+            let (w, h) = text_size(font, lines);
+            w <= width as i32 && h <= height as i32
+        })
+        .max_by_key(|font| (font.height(), text_size(font, lines).0))
+        .or_else(|| fonts.iter().min_by_key(|font| font.height()))
+}
+
+/// Draw `lines` top-left aligned onto a black `width`x`height` frame.
+pub fn render(font: &BdfFont, lines: &[Vec<Span>], width: u32, height: u32) -> RgbImage {
+    let mut img = RgbImage::new(width, height);
+
+    for (row, spans) in lines.iter().enumerate() {
+        let baseline = font.ascent() + font.height() * row as i32;
+        if baseline - font.ascent() >= height as i32 {
+            break;
+        }
+
+        let mut x = 0;
+        for span in spans {
+            x = font.draw_text(&mut img, &span.text, x, baseline, Rgb::from(span.color));
+        }
+    }
+
+    img
+}
+
+/// Draw `lines` onto a black `width`x`height` frame, each line centered
+/// horizontally and the block centered vertically.
+pub fn render_centered(font: &BdfFont, lines: &[Vec<Span>], width: u32, height: u32) -> RgbImage {
+    render_centered_partial(font, lines, usize::MAX, width, height)
+}
+
+/// Like `render_centered`, but only the first `visible` characters are
+/// drawn. Every character stays where it would be in the full layout, so
+/// revealing one more at a time looks like typing.
+pub fn render_centered_partial(
+    font: &BdfFont,
+    lines: &[Vec<Span>],
+    visible: usize,
+    width: u32,
+    height: u32,
+) -> RgbImage {
+    let mut img = RgbImage::new(width, height);
+    let (_, text_height) = text_size(font, lines);
+    let top = (height as i32 - text_height) / 2;
+    let mut remaining = visible;
+
+    for (row, spans) in lines.iter().enumerate() {
+        let line_width: i32 = spans.iter().map(|s| font.text_width(&s.text)).sum();
+        let baseline = top + font.ascent() + font.height() * row as i32;
+
+        let mut x = (width as i32 - line_width) / 2;
+        for span in spans {
+            if remaining == 0 {
+                return img;
+            }
+            let shown: String = span.text.chars().take(remaining).collect();
+            remaining -= shown.chars().count();
+            x = font.draw_text(&mut img, &shown, x, baseline, Rgb::from(span.color));
+        }
+    }
+
+    img
+}
+
+/// One line of a dashboard-style frame, with its own font and color.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TextLine {
+    pub text: String,
+    /// BDF font name
+    pub font: String,
+    pub color: Color,
+    /// Top of the line in pixels from the top of the panel; `None` stacks
+    /// it directly below the previous line (the first one at the top)
+    pub y: Option<i32>,
+}
+
+/// Draw independently styled lines, left-aligned, onto a black
+/// `width`x`height` frame. Each line is paired with its loaded font.
+pub fn render_stacked(lines: &[(&BdfFont, &TextLine)], width: u32, height: u32) -> RgbImage {
+    let mut img = RgbImage::new(width, height);
+    let mut next_top = 0;
+
+    for &(font, line) in lines {
+        let top = line.y.unwrap_or(next_top);
+        font.draw_text(
+            &mut img,
+            &line.text,
+            0,
+            top + font.ascent(),
+            Rgb::from(line.color),
+        );
+        next_top = top + font.height();
+    }
+
+    img
+}
+
+/// Number of characters in `lines`, as counted by `render_centered_partial`.
+pub fn char_count(lines: &[Vec<Span>]) -> usize {
+    lines
+        .iter()
+        .flatten()
+        .map(|span| span.text.chars().count())
+        .sum()
+}
+
+// ── Word wrapping ────────────────────────────────────────────────────
+
+/// Break `spans` into lines no wider than `width` pixels.
+///
+/// Lines break at whitespace, and at `\n` unconditionally. Words wider
+/// than the panel are split between characters. Colors are kept, with the
+/// space between two words taking the color of the word before it.
+pub fn wrap(font: &BdfFont, spans: &[Span], width: u32) -> Vec<Vec<Span>> {
+    let chars: Vec<(char, Color)> = spans
+        .iter()
+        .flat_map(|span| span.text.chars().map(move |c| (c, span.color)))
+        .collect();
+    let fits = |line: &[(char, Color)]| {
+        let text: String = line.iter().map(|&(c, _)| c).collect();
+        font.text_width(&text) <= width as i32
+    };
+
+    let mut lines = Vec::new();
+    for paragraph in chars.split(|&(c, _)| c == '\n') {
+        let mut line: Vec<(char, Color)> = Vec::new();
+
+        for word in paragraph.split(|&(c, _)| c.is_whitespace()) {
+            if word.is_empty() {
+                continue;
+            }
+
+            let mut candidate = line.clone();
+            if let Some(&(_, color)) = candidate.last() {
+                candidate.push((' ', color));
+            }
+            candidate.extend_from_slice(word);
+
+            if line.is_empty() || fits(&candidate) {
+                line = candidate;
+            } else {
+                lines.push(line);
+                line = word.to_vec();
+            }
+
+            // A single word wider than the panel: break it wherever it must
+            while line.len() > 1 && !fits(&line) {
+                let keep = (1..line.len())
+                    .rev()
+                    .find(|&n| fits(&line[..n]))
+                    .unwrap_or(1);
+                let rest = line.split_off(keep);
+                lines.push(line);
+                line = rest;
+            }
+        }
+
+        lines.push(line);
+    }
+
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines.into_iter().map(|line| merge_spans(&line)).collect()
+}
+
+/// Join consecutive same-colored characters back into spans.
+fn merge_spans(chars: &[(char, Color)]) -> Vec<Span> {
+    let mut spans: Vec<Span> = Vec::new();
+    for &(c, color) in chars {
+        match spans.last_mut() {
+            Some(span) if span.color == color => span.text.push(c),
+            _ => spans.push(Span {
+                text: c.to_string(),
+                color,
+            }),
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    const WHITE: Color = Color::new(255, 255, 255);
+
+    fn span(text: &str, color: Color) -> Span {
+        Span {
+            text: text.to_string(),
+            color,
+        }
+    }
+
+    #[test]
+    fn plain_text_uses_default_color() {
+        let lines = parse("hi\nthere  \n\n", WHITE, true);
+        assert_eq!(
+            lines,
+            vec![vec![span("hi", WHITE)], vec![span("there", WHITE)]]
+        );
+    }
+
+    #[test]
+    fn sgr_colors_split_spans() {
+        let lines = parse("a\x1b[31mb\x1b[0mc", WHITE, true);
+        assert_eq!(
+            lines,
+            vec![vec![
+                span("a", WHITE),
+                span("b", ANSI_PALETTE[1]),
+                span("c", WHITE),
+            ]]
+        );
+    }
+
+    #[test]
+    fn color_carries_across_lines() {
+        let lines = parse("\x1b[32mone\ntwo", WHITE, true);
+        assert_eq!(lines[1], vec![span("two", ANSI_PALETTE[2])]);
+    }
+
+    #[test]
+    fn escapes_are_stripped_when_colors_are_ignored() {
+        let lines = parse("\x1b[2J\x1b[1;34mart", WHITE, false);
+        assert_eq!(lines, vec![vec![span("art", WHITE)]]);
+    }
+
+    #[rstest]
+    #[case(&[1, 31], ANSI_PALETTE[9])]
+    #[case(&[94], ANSI_PALETTE[12])]
+    #[case(&[38, 5, 196], Color::new(255, 0, 0))]
+    #[case(&[38, 5, 244], Color::new(128, 128, 128))]
+    #[case(&[38, 2, 1, 2, 3], Color::new(1, 2, 3))]
+    #[case(&[48, 5, 200, 33], ANSI_PALETTE[3])]
+    #[case(&[31, 39], WHITE)]
+    fn test_apply_sgr(#[case] params: &[u16], #[case] expected: Color) {
+        let mut pen = Pen {
+            color: None,
+            palette_index: None,
+            bold: false,
+        };
+        pen.apply_sgr(params);
+        assert_eq!(pen.current(WHITE), expected);
+    }
+
+    fn font(size: u32) -> BdfFont {
+        // Every printable ASCII glyph is a solid `size`x`size` block
+        let row = format!("{:02X}", 0xFFu8 << (8 - size));
+        let mut source = format!("FONT_ASCENT {size}\nFONT_DESCENT 0\n");
+        for code in 32..127 {
+            source.push_str(&format!(
+                "STARTCHAR c\nENCODING {code}\nDWIDTH {size} 0\nBBX {size} {size} 0 0\nBITMAP\n"
+            ));
+            for _ in 0..size {
+                source.push_str(&row);
+                source.push('\n');
+            }
+            source.push_str("ENDCHAR\n");
+        }
+        BdfFont::parse(&source).unwrap()
+    }
+
+    #[test]
+    fn chooses_tallest_font_that_fits() {
+        let fonts = [font(2), font(4), font(8)];
+        let lines = parse("abcd\nefgh", WHITE, true);
+
+        // 4 chars x 4px = 16 wide, 2 lines x 4px = 8 tall
+        let chosen = choose_font(&fonts, &lines, 16, 16).unwrap();
+        assert_eq!(chosen.height(), 4);
+    }
+
+    #[test]
+    fn falls_back_to_smallest_font() {
+        let fonts = [font(4), font(2)];
+        let lines = parse("way too long for this panel", WHITE, true);
+        assert_eq!(choose_font(&fonts, &lines, 8, 8).unwrap().height(), 2);
+        assert!(choose_font(&[], &lines, 8, 8).is_none());
+    }
+
+    #[test]
+    fn render_draws_spans_in_their_colors() {
+        let red = ANSI_PALETTE[9];
+        let lines = parse("a\x1b[91mb\nc", WHITE, true);
+        let img = render(&font(2), &lines, 4, 4);
+
+        assert_eq!(*img.get_pixel(0, 0), Rgb::from(WHITE));
+        assert_eq!(*img.get_pixel(2, 0), Rgb::from(red));
+        // Color carries to the second line
+        assert_eq!(*img.get_pixel(0, 2), Rgb::from(red));
+        assert_eq!(*img.get_pixel(3, 3), Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn wrap_breaks_at_spaces_and_keeps_colors() {
+        let red = ANSI_PALETTE[1];
+        let spans = [span("ab cd ", WHITE), span("ef", red)];
+
+        // 2px glyphs on a 10px panel: at most 5 characters per line
+        let lines = wrap(&font(2), &spans, 10);
+        assert_eq!(
+            lines,
+            vec![vec![span("ab cd", WHITE)], vec![span("ef", red)],]
+        );
+    }
+
+    #[rstest]
+    #[case("abcdefgh", vec!["abcd", "efgh"])]
+    #[case("a\n\nb\n", vec!["a", "", "b"])]
+    #[case("  a   b  ", vec!["a b"])]
+    #[case("", vec![])]
+    fn test_wrap(#[case] text: &str, #[case] expected: Vec<&str>) {
+        let lines = wrap(&font(2), &[span(text, WHITE)], 8);
+        let texts: Vec<String> = lines
+            .iter()
+            .map(|spans| spans.iter().map(|s| s.text.as_str()).collect())
+            .collect();
+        assert_eq!(texts, expected);
+    }
+
+    #[test]
+    fn partial_render_keeps_full_layout_positions() {
+        let lines = vec![vec![span("ab", WHITE)], vec![span("c", WHITE)]];
+        assert_eq!(char_count(&lines), 3);
+
+        // "ab" is centered as a whole, so "a" alone stays at x=1
+        let img = render_centered_partial(&font(2), &lines, 1, 6, 4);
+        assert_eq!(*img.get_pixel(1, 0), Rgb::from(WHITE));
+        assert_eq!(*img.get_pixel(3, 0), Rgb([0, 0, 0]));
+        assert_eq!(*img.get_pixel(2, 2), Rgb([0, 0, 0]));
+
+        let full = render_centered_partial(&font(2), &lines, 3, 6, 4);
+        assert_eq!(full, render_centered(&font(2), &lines, 6, 4));
+    }
+
+    #[rstest]
+    #[case(VerticalPosition::Center, 6)]
+    #[case(VerticalPosition::Pixels(0), 4)]
+    #[case(VerticalPosition::Fraction(0.0), 4)]
+    #[case(VerticalPosition::Fraction(1.0), 8)]
+    #[case(VerticalPosition::Fraction(0.5), 6)]
+    fn test_baseline(#[case] position: VerticalPosition, #[case] expected: i32) {
+        // 4px font on an 8px panel: 4px of free space
+        assert_eq!(position.baseline(&font(4), 8), expected);
+    }
+
+    #[test]
+    fn render_stacked_places_each_line() {
+        let red = ANSI_PALETTE[9];
+        let line = |text: &str, color, y| TextLine {
+            text: text.to_string(),
+            font: String::new(),
+            color,
+            y,
+        };
+        let (small, big) = (font(2), font(4));
+        let (first, second, third) = (
+            line("a", WHITE, None),
+            line("b", red, None),
+            line("c", WHITE, Some(10)),
+        );
+        let img = render_stacked(
+            &[(&big, &first), (&small, &second), (&small, &third)],
+            4,
+            12,
+        );
+
+        // "a" fills rows 0-3, "b" stacks right below it, "c" sits at y=10
+        assert_eq!(*img.get_pixel(3, 3), Rgb::from(WHITE));
+        assert_eq!(*img.get_pixel(0, 4), Rgb::from(red));
+        assert_eq!(*img.get_pixel(1, 5), Rgb::from(red));
+        assert_eq!(*img.get_pixel(0, 6), Rgb([0, 0, 0]));
+        assert_eq!(*img.get_pixel(1, 11), Rgb::from(WHITE));
+    }
+
+    #[test]
+    fn render_centered_centers_the_block() {
+        let lines = vec![vec![span("a", WHITE)]];
+        let img = render_centered(&font(2), &lines, 6, 6);
+
+        assert_eq!(*img.get_pixel(2, 2), Rgb::from(WHITE));
+        assert_eq!(*img.get_pixel(3, 3), Rgb::from(WHITE));
+        assert_eq!(*img.get_pixel(1, 2), Rgb([0, 0, 0]));
+        assert_eq!(*img.get_pixel(2, 4), Rgb([0, 0, 0]));
+    }
+}
@@ -0,0 +1,554 @@
+//! Request and response types shared by the HTTP server and its clients.
+//!
+//! The server deserializes requests into these and serializes status out
+//! of them; the `client` feature's `MatrixClient` does the opposite, so
+//! both sides always agree on field names and defaults. Nothing here needs
+//! the matrix hardware.
+//!
+//! ## Rust concepts
+//! - `pub` fields plus a `new` for the required ones, so callers can use
+//!   struct update syntax (`..TextRequest::new("hi")`) for the rest
+//! - `#[serde(default = "...")]` defaults kept in one place for both sides
+
+use crate::Color;
+use crate::ansi::TextMode;
+use crate::layout::FitMode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+// ── Display requests ─────────────────────────────────────────────────
+
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct ImageRequest {
+    /// Path to image file relative to media directory
+    #[schema(example = "images/test.png")]
+    pub path: String,
+    /// How to scale the image when its aspect ratio differs from the panel: stretch, contain, cover, or center
+    #[serde(default)]
+    #[schema(example = "contain", default = "stretch")]
+    pub fit: FitMode,
+    /// Color of the bars left by fit=contain or center, and of the corners
+    /// uncovered by `rotate_deg`
+    #[serde(default)]
+    #[schema(example = "[10, 0, 20]", default = "[0, 0, 0]")]
+    pub background: Color,
+    /// Rotate the fitted image clockwise by this many degrees (-360 to
+    /// 360) about its center, sampled bilinearly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 15.0)]
+    pub rotate_deg: Option<f32>,
+    /// Keep watching the file and redraw it whenever it changes, until the
+    /// next command. Lets another program update the panel by rewriting it
+    #[serde(default)]
+    #[schema(example = false, default = false)]
+    pub watch: bool,
+}
+
+impl ImageRequest {
+    /// Show the image at `path` (relative to the media directory) with
+    /// every other field at its default.
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            fit: FitMode::default(),
+            background: Color::default(),
+            rotate_deg: None,
+            watch: false,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct VideoRequest {
+    /// Path to video directory relative to media directory. Use GET /api/videos to list available videos.
+    #[schema(example = "videos/eyes_25")]
+    pub path: String,
+    /// Frames per second. Typical range: 15-60. Higher fps = smoother but more CPU intensive.
+    #[serde(default = "default_fps")]
+    #[schema(example = 25, default = 30)]
+    pub fps: u32,
+    /// Loop playback indefinitely. Set to true to repeat video, false to play once and then apply `on_finish`.
+    #[serde(default, rename = "loop")]
+    #[schema(example = true, default = false)]
+    pub loop_playback: bool,
+    /// Play the video this many times, then apply `on_finish`; 0 loops
+    /// forever. Overrides `loop` when given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 3)]
+    pub loop_count: Option<u32>,
+    /// How to scale frames whose aspect ratio differs from the panel: stretch, contain, or cover
+    #[serde(default)]
+    #[schema(example = "cover", default = "stretch")]
+    pub fit: FitMode,
+    /// Color of the bars left by fit=contain or center
+    #[serde(default)]
+    #[schema(example = "[10, 0, 20]", default = "[0, 0, 0]")]
+    pub background: Color,
+    /// Show the video's name over the first seconds of playback, then fade it out
+    #[serde(default)]
+    #[schema(example = true, default = false)]
+    pub show_title: bool,
+    /// What to show when a non-looping video ends: clear (blank), hold
+    /// (keep the last frame), or dark (panel off until the next command).
+    /// Defaults to the server's --video-finish-action
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "hold")]
+    pub on_finish: Option<VideoFinish>,
+}
+
+impl VideoRequest {
+    /// Play the video directory at `path` once, at the default fps.
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            fps: default_fps(),
+            loop_playback: false,
+            loop_count: None,
+            fit: FitMode::default(),
+            background: Color::default(),
+            show_title: false,
+            on_finish: None,
+        }
+    }
+}
+
+pub(crate) fn default_fps() -> u32 {
+    30
+}
+
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct TextRequest {
+    /// Text to display in `color`. Ignored when `segments` is given
+    #[serde(default)]
+    #[schema(example = "Hello!")]
+    pub text: String,
+    /// Differently-colored runs scrolled as one line, e.g. a scoreboard.
+    /// When non-empty, replaces `text` and `color`
+    #[serde(default)]
+    pub segments: Vec<TextSegment>,
+    /// BDF font name, defaulting to the server's --default-font. Available fonts: 4x6, 5x7, 5x8, 6x9, 6x10, 6x12, 6x13, 6x13B, 6x13O, 7x13, 7x13B, 7x13O, 7x14, 7x14B, 8x13, 8x13B, 8x13O, 9x15, 9x15B, 9x18, 9x18B, 10x20, and more in fonts/bdf/
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "6x13")]
+    pub font: Option<String>,
+    /// Text color, defaulting to the server's --default-text-color. Either [red, green, blue] with each value 0-255 or a string, e.g. [255, 0, 0], "#ff0000", "rgb(255,0,0)", and "red" are all red
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "[255, 255, 255]")]
+    pub color: Option<Color>,
+    /// Scroll speed in pixels per second. Typical range: 10-100
+    #[serde(default = "default_speed")]
+    #[schema(example = 30, default = 30)]
+    pub speed: u32,
+    /// scroll: always scroll as one line. auto: show the text wrapped and
+    /// centered if it fits the panel, and only scroll if it's too tall
+    #[serde(default)]
+    #[schema(example = "auto", default = "scroll")]
+    pub mode: TextMode,
+    /// Left edge of the text when it starts scrolling, in pixels from the
+    /// left of the panel; negative starts partly off the left edge. Later
+    /// passes start off the right edge, as they do by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 0)]
+    pub x: Option<i32>,
+    /// Top of the scrolling line in pixels from the top of the panel;
+    /// negative starts partly off the top. Defaults to centering the line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 2)]
+    pub y: Option<i32>,
+    /// Line position as a fraction of the free space: 0.0 top, 0.5 center,
+    /// 1.0 bottom. Works the same on any panel height; use instead of `y`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 1.0)]
+    pub y_percent: Option<f32>,
+    /// Scroll this many times (at least 1), then clear the panel and go
+    /// idle, e.g. for a notification. Scrolls until replaced if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 3)]
+    pub repeat: Option<u32>,
+}
+
+impl TextRequest {
+    /// Scroll `text` in the server's default font and color.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            segments: Vec::new(),
+            font: None,
+            color: None,
+            speed: default_speed(),
+            mode: TextMode::default(),
+            x: None,
+            y: None,
+            y_percent: None,
+            repeat: None,
+        }
+    }
+}
+
+/// One colored run of a multi-color text line.
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct TextSegment {
+    #[schema(example = "HOME 3 ")]
+    pub text: String,
+    /// Color, defaulting to the server's --default-text-color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "[255, 255, 0]")]
+    pub color: Option<Color>,
+}
+
+pub(crate) fn default_speed() -> u32 {
+    30
+}
+
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct BrightnessRequest {
+    /// Brightness level (0-100)
+    #[schema(example = 75, minimum = 0, maximum = 100)]
+    pub value: u8,
+}
+
+#[derive(Clone, Default, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct StopRequest {
+    /// Also blank the panel. Defaults to the server's `--stop-clears` setting
+    #[schema(example = true)]
+    pub clear: Option<bool>,
+}
+
+impl StopRequest {
+    /// Whether stopping blanks the panel, given the server's `--stop-clears`.
+    pub fn clears(&self, stop_clears: bool) -> bool {
+        self.clear.unwrap_or(stop_clears)
+    }
+}
+
+/// What the panel shows after a video played without `loop` ends.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    utoipa::ToSchema,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum VideoFinish {
+    /// Blank the panel
+    #[default]
+    Clear,
+    /// Keep the last frame on the panel. The status goes idle but still
+    /// names the video and its last frame
+    Hold,
+    /// Turn the panel off until the next command, as after `--power-off-after`
+    Dark,
+}
+
+// ── WebSocket control ────────────────────────────────────────────────
+
+/// Longest a control message may hold the channel before the next one.
+pub const MAX_CONTROL_DWELL_MS: u32 = 3_600_000;
+
+/// One message on the control WebSocket. The server runs `command` as a
+/// preset command; it's generic so the envelope doesn't need the hardware.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ControlMessage<C> {
+    /// Command to run, in the same format as a preset's `command`
+    pub command: C,
+    /// Wait this long after the command before running the next message
+    #[serde(default)]
+    pub dwell_ms: u32,
+}
+
+impl<C: DeserializeOwned> ControlMessage<C> {
+    /// Read a message from its JSON, which is `Null` if the text wasn't
+    /// JSON at all. The error says what's wrong with it.
+    pub fn parse(message: serde_json::Value) -> Result<Self, String> {
+        if message.is_null() {
+            return Err("Invalid JSON".to_string());
+        }
+        let message: Self =
+            serde_json::from_value(message).map_err(|e| format!("Invalid message: {e}"))?;
+        if message.dwell_ms > MAX_CONTROL_DWELL_MS {
+            return Err(format!("dwell_ms must be at most {MAX_CONTROL_DWELL_MS}"));
+        }
+        Ok(message)
+    }
+}
+
+// ── Status ───────────────────────────────────────────────────────────
+
+/// The display command the render thread last started, with everything it
+/// was given, so a reconnecting client can rebuild its view of the panel.
+#[derive(Clone, Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct ActiveCommand {
+    /// Command name, e.g. `play_video`
+    pub name: String,
+    /// Its parameters, e.g. `{"path": ..., "fps": 25, ...}` for a video
+    #[schema(value_type = Object)]
+    pub params: serde_json::Value,
+}
+
+/// What the display is currently doing.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayState {
+    Idle,
+    ShowingImage,
+    PlayingVideo,
+    ScrollingText,
+    /// Showing a text file or ANSI art
+    ShowingText,
+    Streaming,
+    /// Showing a generated graphic such as a gauge
+    Drawing,
+    /// Running a procedural animation such as the color wheel
+    Animating,
+    /// Matrix shut down after `--power-off-after` without commands; the
+    /// next command turns it back on
+    PoweredOff,
+}
+
+/// Shared status that the HTTP server can read to report current state.
+///
+/// Rust concept: Arc<Mutex<T>>
+/// `Arc` = atomic reference counting (shared ownership across threads)
+/// `Mutex` = mutual exclusion (only one thread can access at a time)
+/// Together they allow the render thread to update status while the
+/// HTTP server reads it.
+#[derive(Clone, Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct DisplayStatus {
+    /// Current display state
+    pub state: DisplayState,
+    /// Currently displayed media (if any)
+    pub current_media: Option<String>,
+    /// Current frame number (for videos), or image number in a slideshow
+    pub frame: Option<usize>,
+    /// Total frame count (for videos), or image count in a slideshow
+    pub total_frames: Option<usize>,
+    /// Current software brightness (0-100), applied to each pixel
+    pub brightness: u8,
+    /// Whether colors are inverted at draw time
+    pub inverted: bool,
+    /// Whether the playing video loops once its current pass ends
+    /// (`POST /api/v1/display/loop` changes it mid-playback)
+    pub looping: bool,
+    /// Current hardware (PWM) brightness (1-100) of the matrix library
+    pub hardware_brightness: u8,
+    /// Cap on both brightnesses (`--max-brightness`); requests above it
+    /// are clamped
+    pub max_brightness: u8,
+    /// Server version
+    pub version: String,
+    /// Seconds since the server started
+    pub uptime_secs: u64,
+    /// Frames received over WebSocket streams since startup
+    pub total_stream_frames: u64,
+    /// Bytes of frame data received over WebSocket streams since startup
+    pub total_stream_bytes: u64,
+    /// Number of currently connected WebSocket stream clients
+    pub active_stream_clients: u32,
+    /// Raw frames (HTTP or WebSocket) dropped for a bad checksum since startup
+    pub checksum_failures: u64,
+    /// Raw frames identical to the one shown, skipped without a redraw
+    /// since startup (`--skip-duplicate-frames`)
+    pub duplicate_frames_skipped: u64,
+    /// Video frame files that failed to load since startup; each one was
+    /// replaced by the frame before it
+    pub failed_video_frames: u64,
+    /// Commands sent to the render thread since startup
+    pub commands_sent: u64,
+    /// Commands the render thread has taken off its queue since startup.
+    /// `commands_sent - commands_processed` is the current backlog
+    pub commands_processed: u64,
+    /// The last display command started and its parameters. It stays set
+    /// once the command ends (e.g. a video held on its last frame); `state`
+    /// tells whether it's still running. A command that fails clears it
+    pub command: Option<ActiveCommand>,
+}
+
+impl DisplayStatus {
+    pub fn new() -> Self {
+        Self {
+            state: DisplayState::Idle,
+            current_media: None,
+            frame: None,
+            total_frames: None,
+            brightness: 75,
+            inverted: false,
+            looping: false,
+            hardware_brightness: 100,
+            max_brightness: 100,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_secs: 0,
+            total_stream_frames: 0,
+            total_stream_bytes: 0,
+            active_stream_clients: 0,
+            checksum_failures: 0,
+            duplicate_frames_skipped: 0,
+            failed_video_frames: 0,
+            commands_sent: 0,
+            commands_processed: 0,
+            command: None,
+        }
+    }
+
+    pub fn set_idle(&mut self) {
+        self.state = DisplayState::Idle;
+        self.current_media = None;
+        self.frame = None;
+        self.total_frames = None;
+    }
+
+    /// Go idle after a video finished with `on_finish`. A held video is
+    /// still on the panel, so its name and frame stay in the status.
+    pub fn finish_video(&mut self, on_finish: VideoFinish) {
+        match on_finish {
+            VideoFinish::Hold => self.state = DisplayState::Idle,
+            VideoFinish::Clear | VideoFinish::Dark => self.set_idle(),
+        }
+    }
+}
+
+impl Default for DisplayStatus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ── Configuration ────────────────────────────────────────────────────
+
+/// The settings the server is running with, after defaults and the
+/// settings file are applied (`GET /api/v1/config`).
+///
+/// Options given as a choice on the command line are reported by the name
+/// the flag takes (e.g. `double-buffered`). File paths are reported, their
+/// contents (the TLS key among them) are not.
+#[derive(Clone, Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct EffectiveConfig {
+    /// Drawable rows, excluding the safe inset
+    pub rows: u32,
+    /// Drawable columns, excluding the safe inset
+    pub cols: u32,
+    /// Border left dark around the drawable area
+    pub safe_inset: u32,
+    /// GPIO mapping of each panel, in panel id order
+    pub hardware_mappings: Vec<String>,
+    /// Bits of PWM color depth the matrix library runs with
+    pub pwm_bits: u8,
+    /// GPIO slowdown the matrix library runs with
+    pub gpio_slowdown: u32,
+    pub canvas: String,
+    pub color_order: String,
+    pub symmetry: String,
+    /// Pixel map file, if any
+    pub pixel_map: Option<String>,
+    /// Hardware (PWM) brightness the matrix started with
+    pub hardware_brightness: u8,
+    pub brightness_mode: String,
+    /// Current cap on both brightnesses; a reloaded settings file can change it
+    pub max_brightness: u8,
+    /// Current refresh cap; a reloaded settings file can change it
+    pub max_refresh_fps: Option<u32>,
+    /// Number of brightness schedule points
+    pub brightness_schedule_points: usize,
+    pub light_sensor: bool,
+    pub media_dir: String,
+    pub fonts_dir: String,
+    pub presets_file: String,
+    pub settings_file: Option<String>,
+    pub default_font: String,
+    pub default_text_color: Color,
+    pub power_off_after_secs: Option<u64>,
+    pub min_display_ms: u64,
+    pub skip_duplicate_frames: bool,
+    pub stop_clears: bool,
+    pub video_finish_action: VideoFinish,
+    pub max_stream_clients: u32,
+    pub max_body_bytes: usize,
+    /// Whether the server was started with `--appliance`
+    pub appliance: bool,
+    /// Whether the server speaks HTTPS
+    pub tls: bool,
+    /// Port from `--port`; 0 means the OS picked one
+    pub port: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn playing() -> DisplayStatus {
+        DisplayStatus {
+            state: DisplayState::PlayingVideo,
+            current_media: Some("videos/intro".to_string()),
+            frame: Some(119),
+            total_frames: Some(120),
+            ..DisplayStatus::new()
+        }
+    }
+
+    #[test]
+    fn held_video_stays_in_the_status() {
+        let mut status = playing();
+        status.finish_video(VideoFinish::Hold);
+        assert_eq!(status.state, DisplayState::Idle);
+        assert_eq!(status.current_media.as_deref(), Some("videos/intro"));
+        assert_eq!((status.frame, status.total_frames), (Some(119), Some(120)));
+    }
+
+    #[rstest]
+    #[case(VideoFinish::Clear)]
+    #[case(VideoFinish::Dark)]
+    fn cleared_video_leaves_the_status(#[case] on_finish: VideoFinish) {
+        let mut status = playing();
+        status.finish_video(on_finish);
+        assert_eq!(status.state, DisplayState::Idle);
+        assert_eq!(status.current_media, None);
+        assert_eq!((status.frame, status.total_frames), (None, None));
+    }
+
+    #[rstest]
+    #[case(false, None, false)]
+    #[case(true, None, true)]
+    #[case(true, Some(false), false)]
+    #[case(false, Some(true), true)]
+    fn stop_clears_unless_the_request_says(
+        #[case] stop_clears: bool,
+        #[case] clear: Option<bool>,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(StopRequest { clear }.clears(stop_clears), expected);
+    }
+
+    #[test]
+    fn stop_request_fields_are_optional() {
+        let req: StopRequest = serde_json::from_str("{}").unwrap();
+        assert_eq!(req.clear, None);
+    }
+
+    #[test]
+    fn control_messages_dwell_only_when_asked() {
+        let message = serde_json::json!({ "id": 7, "command": { "type": "clear" } });
+        let message = ControlMessage::<serde_json::Value>::parse(message).unwrap();
+        assert_eq!(message.command, serde_json::json!({ "type": "clear" }));
+        assert_eq!(message.dwell_ms, 0);
+    }
+
+    #[rstest]
+    #[case::not_json(serde_json::Value::Null, "Invalid JSON")]
+    #[case::no_command(serde_json::json!({ "dwell_ms": 10 }), "Invalid message")]
+    #[case::dwell_too_long(
+        serde_json::json!({ "command": {}, "dwell_ms": MAX_CONTROL_DWELL_MS + 1 }),
+        "dwell_ms must be at most"
+    )]
+    fn bad_control_messages_say_why(#[case] message: serde_json::Value, #[case] error: &str) {
+        let Err(message) = ControlMessage::<serde_json::Value>::parse(message) else {
+            panic!("accepted a bad control message");
+        };
+        assert!(message.starts_with(error), "{message}");
+    }
+}
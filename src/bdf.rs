@@ -0,0 +1,512 @@
+//! Minimal BDF font parser for rendering text into in-memory frames.
+//!
+//! The `rpi-led-matrix` font API (`LedFont`) can only draw onto a live
+//! `LedCanvas`. This module reads the same `.bdf` files directly, so text
+//! can be measured and rendered into an `RgbImage` — for previews, and
+//! anywhere else text needs to be composed off the panel.
+//!
+//! Only the parts of the format needed for drawing are read: the font
+//! ascent/descent and, per glyph, its encoding, advance (`DWIDTH`),
+//! bounding box (`BBX`), and bitmap rows.
+//!
+//! The render thread keeps parsed fonts in a `FontCache`, so text commands
+//! don't re-read the same file from the SD card every time.
+//!
+//! A couple of small fonts are compiled into the binary and used whenever
+//! the fonts directory doesn't have a file of that name, so text still
+//! works on an install whose fonts are missing.
+//!
+//! ## Rust concepts
+//! - `HashMap` for glyph lookup by code point
+//! - `Rc` to hand out shared references to cached fonts
+//! - `str::lines()` and `split_whitespace()` for line-oriented parsing
+//! - `u8::from_str_radix` for hex decoding
+//! - `include_str!` to embed a file in the binary at compile time
+
+use image::{Rgb, RgbImage};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
+
+/// Fonts built into the binary, by name. A file of the same name in the
+/// fonts directory takes precedence.
+const EMBEDDED_FONTS: &[(&str, &str)] = &[
+    ("5x8", include_str!("../fonts/bdf/5x8.bdf")),
+    ("6x10", include_str!("../fonts/bdf/6x10.bdf")),
+];
+
+/// Built-in font used in place of a `--default-font` that can't be found.
+pub const FALLBACK_FONT: &str = "6x10";
+
+/// Names of the built-in fonts.
+pub fn embedded_fonts() -> impl Iterator<Item = &'static str> {
+    EMBEDDED_FONTS.iter().map(|&(name, _)| name)
+}
+
+fn embedded_source(name: &str) -> Option<&'static str> {
+    EMBEDDED_FONTS
+        .iter()
+        .find(|(embedded, _)| *embedded == name)
+        .map(|&(_, source)| source)
+}
+
+/// A single glyph: its bitmap plus placement relative to the pen position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Glyph {
+    /// Horizontal pen advance after drawing this glyph
+    advance: i32,
+    width: u32,
+    height: u32,
+    /// Offset of the bitmap's left edge from the pen position
+    x_off: i32,
+    /// Offset of the bitmap's bottom edge from the baseline (negative = below)
+    y_off: i32,
+    /// One entry per bitmap row, each a big-endian bit string padded to bytes
+    rows: Vec<Vec<u8>>,
+}
+
+impl Glyph {
+    fn is_set(&self, x: u32, y: u32) -> bool {
+        self.rows
+            .get(y as usize)
+            .and_then(|row| row.get((x / 8) as usize))
+            .is_some_and(|byte| byte & (0x80 >> (x % 8)) != 0)
+    }
+}
+
+/// A parsed BDF font.
+#[derive(Clone, Debug)]
+pub struct BdfFont {
+    glyphs: HashMap<u32, Glyph>,
+    /// Pixels above the baseline
+    ascent: i32,
+    /// Pixels below the baseline
+    descent: i32,
+    /// Glyph drawn for characters the font doesn't cover
+    default_char: Option<u32>,
+}
+
+impl BdfFont {
+    /// Read and parse a `.bdf` file.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let source = std::fs::read_to_string(path)?;
+        Self::parse(&source)
+    }
+
+    /// The font `name` from `dir`, or the built-in font of that name if
+    /// there's no such file.
+    pub fn load_named(dir: &Path, name: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = dir.join(format!("{name}.bdf"));
+        match embedded_source(name) {
+            Some(source) if !path.exists() => Self::parse(source),
+            _ => Self::load(&path).map_err(|e| format!("{}: {e}", path.display()).into()),
+        }
+    }
+
+    /// Parse BDF source text.
+    pub fn parse(source: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut glyphs = HashMap::new();
+        let mut ascent = None;
+        let mut descent = None;
+        let mut bbox_height = 0;
+        let mut default_char = None;
+
+        // Per-glyph state, reset at each STARTCHAR
+        let mut encoding: Option<u32> = None;
+        let mut advance = 0;
+        let mut bbx = (0u32, 0u32, 0i32, 0i32);
+        let mut rows: Option<Vec<Vec<u8>>> = None;
+
+        for line in source.lines() {
+            let mut parts = line.split_whitespace();
+            let Some(keyword) = parts.next() else {
+                continue;
+            };
+
+            // Inside a BITMAP block every line is a hex row until ENDCHAR
+            if let Some(bitmap) = rows.as_mut()
+                && keyword != "ENDCHAR"
+            {
+                bitmap.push(parse_hex_row(keyword)?);
+                continue;
+            }
+
+            let mut next_int = || -> Result<i32, Box<dyn std::error::Error>> {
+                Ok(parts
+                    .next()
+                    .ok_or_else(|| format!("Missing value in line: {line}"))?
+                    .parse()?)
+            };
+
+            match keyword {
+                "FONTBOUNDINGBOX" => {
+                    next_int()?;
+                    bbox_height = next_int()?;
+                }
+                "FONT_ASCENT" => ascent = Some(next_int()?),
+                "FONT_DESCENT" => descent = Some(next_int()?),
+                "DEFAULT_CHAR" => default_char = u32::try_from(next_int()?).ok(),
+                "STARTCHAR" => {
+                    encoding = None;
+                    advance = 0;
+                    bbx = (0, 0, 0, 0);
+                }
+                // Negative encodings mark glyphs without a standard code point
+                "ENCODING" => encoding = u32::try_from(next_int()?).ok(),
+                "DWIDTH" => advance = next_int()?,
+                "BBX" => {
+                    bbx = (
+                        next_int()?.max(0) as u32,
+                        next_int()?.max(0) as u32,
+                        next_int()?,
+                        next_int()?,
+                    );
+                }
+                "BITMAP" => rows = Some(Vec::new()),
+                "ENDCHAR" => {
+                    let bitmap = rows.take().unwrap_or_default();
+                    if let Some(code) = encoding {
+                        let (width, height, x_off, y_off) = bbx;
+                        glyphs.insert(
+                            code,
+                            Glyph {
+                                advance,
+                                width,
+                                height,
+                                x_off,
+                                y_off,
+                                rows: bitmap,
+                            },
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if glyphs.is_empty() {
+            return Err("No glyphs found in BDF font".into());
+        }
+
+        // Fall back to the bounding box if the font omits ascent/descent
+        let descent = descent.unwrap_or(0);
+        let ascent = ascent.unwrap_or(bbox_height - descent);
+
+        Ok(Self {
+            glyphs,
+            ascent,
+            descent,
+            default_char,
+        })
+    }
+
+    /// Pixels above the baseline.
+    pub fn ascent(&self) -> i32 {
+        self.ascent
+    }
+
+    /// Total line height (ascent + descent).
+    pub fn height(&self) -> i32 {
+        self.ascent + self.descent
+    }
+
+    fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs
+            .get(&(c as u32))
+            .or_else(|| self.default_char.and_then(|d| self.glyphs.get(&d)))
+    }
+
+    /// Width in pixels of `text` when drawn with this font.
+    pub fn text_width(&self, text: &str) -> i32 {
+        text.chars()
+            .filter_map(|c| self.glyph(c))
+            .map(|g| g.advance)
+            .sum()
+    }
+
+    /// Draw `text` with its baseline at `baseline_y`, starting at pen
+    /// position `x`. Pixels outside the image are clipped, so `x` and
+    /// `baseline_y` may be negative or past the edge.
+    ///
+    /// Returns the pen position after the last character.
+    pub fn draw_text(
+        &self,
+        img: &mut RgbImage,
+        text: &str,
+        x: i32,
+        baseline_y: i32,
+        color: Rgb<u8>,
+    ) -> i32 {
+        let (img_w, img_h) = (img.width() as i32, img.height() as i32);
+        let mut pen_x = x;
+
+        for c in text.chars() {
+            let Some(glyph) = self.glyph(c) else {
+                continue;
+            };
+
+            // Top row of the bitmap sits `y_off + height` above the baseline
+            let top = baseline_y - glyph.y_off - glyph.height as i32;
+            for gy in 0..glyph.height {
+                let py = top + gy as i32;
+                if py < 0 || py >= img_h {
+                    continue;
+                }
+                for gx in 0..glyph.width {
+                    let px = pen_x + glyph.x_off + gx as i32;
+                    if px >= 0 && px < img_w && glyph.is_set(gx, gy) {
+                        img.put_pixel(px as u32, py as u32, color);
+                    }
+                }
+            }
+
+            pen_x += glyph.advance;
+        }
+
+        pen_x
+    }
+
+    /// Render `text` onto a new image just large enough to hold it, with
+    /// `padding` pixels on every side.
+    pub fn render(
+        &self,
+        text: &str,
+        color: Rgb<u8>,
+        background: Rgb<u8>,
+        padding: u32,
+    ) -> RgbImage {
+        let width = self.text_width(text).max(1) as u32 + padding * 2;
+        let height = self.height().max(1) as u32 + padding * 2;
+        let mut img = RgbImage::from_pixel(width, height, background);
+        self.draw_text(
+            &mut img,
+            text,
+            padding as i32,
+            padding as i32 + self.ascent,
+            color,
+        );
+        img
+    }
+}
+
+/// Decode one hex bitmap row (e.g. `"F0"` or `"1FC0"`) into bytes.
+fn parse_hex_row(hex: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    // Slicing by two bytes would split a multi-byte character
+    if !hex.is_ascii() {
+        return Err(format!("Non-hex bitmap row: {hex}").into());
+    }
+    if !hex.len().is_multiple_of(2) {
+        return Err(format!("Odd-length bitmap row: {hex}").into());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| Ok(u8::from_str_radix(&hex[i..i + 2], 16)?))
+        .collect()
+}
+
+// ── Font cache ───────────────────────────────────────────────────────
+
+/// Fonts from one directory, each parsed at most once.
+///
+/// A font is re-read only when its file's modification time changes, so
+/// editing or replacing a `.bdf` file takes effect on the next command. The
+/// cache isn't `Send` (it hands out `Rc`s); it belongs to one thread.
+#[derive(Debug)]
+pub struct FontCache {
+    dir: PathBuf,
+    fonts: HashMap<String, (Option<SystemTime>, Rc<BdfFont>)>,
+}
+
+impl FontCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            fonts: HashMap::new(),
+        }
+    }
+
+    /// The font `name` (`<dir>/<name>.bdf`, or the built-in font of that
+    /// name if there's no file), parsing it on first use or after the file
+    /// changed.
+    pub fn get(&mut self, name: &str) -> Result<Rc<BdfFont>, Box<dyn std::error::Error>> {
+        let path = self.dir.join(format!("{name}.bdf"));
+        let modified = match std::fs::metadata(&path) {
+            Ok(meta) => meta.modified().ok(),
+            Err(e) => {
+                let Some(source) = embedded_source(name) else {
+                    self.fonts.remove(name);
+                    return Err(format!("{}: {e}", path.display()).into());
+                };
+                // Built-in fonts are cached with no modification time
+                if let Some((None, font)) = self.fonts.get(name) {
+                    return Ok(font.clone());
+                }
+                let font = Rc::new(BdfFont::parse(source)?);
+                self.fonts.insert(name.to_string(), (None, font.clone()));
+                return Ok(font);
+            }
+        };
+
+        if let Some((cached_modified, font)) = self.fonts.get(name)
+            && *cached_modified == modified
+        {
+            return Ok(font.clone());
+        }
+
+        let font = Rc::new(BdfFont::load(&path).map_err(|e| format!("{}: {e}", path.display()))?);
+        self.fonts
+            .insert(name.to_string(), (modified, font.clone()));
+        Ok(font)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+    const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+
+    /// Two 3x3 glyphs: "L" (encoding 76) and a blank space.
+    const TINY_FONT: &str = "\
+STARTFONT 2.1
+FONTBOUNDINGBOX 4 4 0 -1
+STARTPROPERTIES 2
+FONT_ASCENT 3
+FONT_DESCENT 1
+ENDPROPERTIES
+CHARS 2
+STARTCHAR L
+ENCODING 76
+DWIDTH 4 0
+BBX 3 3 0 0
+BITMAP
+80
+80
+E0
+ENDCHAR
+STARTCHAR space
+ENCODING 32
+DWIDTH 2 0
+BBX 0 0 0 0
+BITMAP
+ENDCHAR
+ENDFONT
+";
+
+    #[test]
+    fn parses_metrics_and_glyphs() {
+        let font = BdfFont::parse(TINY_FONT).unwrap();
+        assert_eq!(font.ascent(), 3);
+        assert_eq!(font.height(), 4);
+        assert_eq!(font.text_width("L L"), 10);
+    }
+
+    #[test]
+    fn unknown_chars_are_skipped_without_default() {
+        let font = BdfFont::parse(TINY_FONT).unwrap();
+        assert_eq!(font.text_width("LZ"), 4);
+    }
+
+    #[test]
+    fn draws_glyph_above_baseline() {
+        let font = BdfFont::parse(TINY_FONT).unwrap();
+        let mut img = RgbImage::new(4, 4);
+        let end = font.draw_text(&mut img, "L", 0, 3, WHITE);
+
+        assert_eq!(end, 4);
+        assert_eq!(*img.get_pixel(0, 0), WHITE);
+        assert_eq!(*img.get_pixel(1, 0), BLACK);
+        assert_eq!(*img.get_pixel(2, 2), WHITE);
+        assert_eq!(*img.get_pixel(0, 3), BLACK); // descent row stays empty
+    }
+
+    #[test]
+    fn drawing_clips_negative_positions() {
+        let font = BdfFont::parse(TINY_FONT).unwrap();
+        let mut img = RgbImage::new(4, 4);
+        font.draw_text(&mut img, "L", -2, 2, WHITE);
+
+        // Only the rightmost column of the foot is visible, one row up
+        assert_eq!(*img.get_pixel(0, 1), WHITE);
+        assert_eq!(img.pixels().filter(|p| **p == WHITE).count(), 1);
+    }
+
+    #[test]
+    fn render_sizes_image_to_text() {
+        let font = BdfFont::parse(TINY_FONT).unwrap();
+        let img = font.render("LL", WHITE, BLACK, 1);
+        assert_eq!(img.dimensions(), (10, 6));
+    }
+
+    #[test]
+    fn rejects_source_without_glyphs() {
+        assert!(BdfFont::parse("STARTFONT 2.1\nENDFONT\n").is_err());
+    }
+
+    #[test]
+    fn rejects_bad_hex() {
+        let bad = TINY_FONT.replace("E0", "ZZ");
+        assert!(BdfFont::parse(&bad).is_err());
+        // Non-ASCII rows are an error, not a panic
+        let bad = TINY_FONT.replace("E0", "0é0");
+        assert!(BdfFont::parse(&bad).is_err());
+    }
+
+    #[test]
+    fn font_cache_reuses_fonts_until_the_file_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("tiny.bdf");
+        std::fs::write(&path, TINY_FONT).unwrap();
+        let mut cache = FontCache::new(tmp.path().to_path_buf());
+
+        let first = cache.get("tiny").unwrap();
+        assert!(Rc::ptr_eq(&first, &cache.get("tiny").unwrap()));
+
+        // Set the time explicitly; a rewrite can land in the same mtime tick
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+        assert!(!Rc::ptr_eq(&first, &cache.get("tiny").unwrap()));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(cache.get("tiny").is_err());
+        assert!(cache.get("missing").is_err());
+    }
+
+    #[test]
+    fn parses_bundled_font() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fonts/bdf/5x7.bdf");
+        let font = BdfFont::load(&path).unwrap();
+        assert_eq!(font.height(), 7);
+        assert_eq!(font.text_width("Hello"), 25);
+    }
+
+    #[test]
+    fn embedded_fonts_parse() {
+        for name in embedded_fonts() {
+            assert!(
+                BdfFont::parse(embedded_source(name).unwrap()).is_ok(),
+                "{name}"
+            );
+        }
+        assert!(embedded_fonts().any(|name| name == FALLBACK_FONT));
+    }
+
+    #[test]
+    fn missing_fonts_fall_back_to_embedded_ones() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut cache = FontCache::new(tmp.path().join("no-such-dir"));
+
+        let font = cache.get(FALLBACK_FONT).unwrap();
+        assert_eq!(font.height(), 10);
+        assert!(Rc::ptr_eq(&font, &cache.get(FALLBACK_FONT).unwrap()));
+        assert!(BdfFont::load_named(tmp.path(), FALLBACK_FONT).is_ok());
+
+        // A file of the same name wins over the built-in font
+        std::fs::write(tmp.path().join("6x10.bdf"), TINY_FONT).unwrap();
+        let mut cache = FontCache::new(tmp.path().to_path_buf());
+        assert_eq!(cache.get(FALLBACK_FONT).unwrap().height(), 4);
+    }
+}
@@ -0,0 +1,300 @@
+//! Typed async client for the HTTP API, for driving a panel from another
+//! Rust program (`client` feature).
+//!
+//! Requests and responses are the same `api` types the server uses, so a
+//! field added on one side can't be forgotten on the other:
+//!
+//! ```no_run
+//! # async fn demo() -> Result<(), led_matrix_rs::client::ClientError> {
+//! use led_matrix_rs::api::TextRequest;
+//! use led_matrix_rs::client::MatrixClient;
+//!
+//! let client = MatrixClient::new("http://pi:8080");
+//! client.set_brightness(40).await?;
+//! client
+//!     .scroll_text(&TextRequest { speed: 50, ..TextRequest::new("Hello!") })
+//!     .await?;
+//! println!("{:?}", client.status().await?.state);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Rust concepts
+//! - `async fn` in an `impl` block, awaited by the caller's runtime
+//! - Generic helpers bounded by `Serialize`/`DeserializeOwned`
+//! - An error enum wrapping the underlying library's error as its `source`
+
+use crate::api::{BrightnessRequest, DisplayStatus, ImageRequest, TextRequest, VideoRequest};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt;
+
+/// Why a request to the server failed.
+#[derive(Debug)]
+pub enum ClientError {
+    /// The request couldn't be sent or the response couldn't be read
+    Http(reqwest::Error),
+    /// The server answered with an error status and this message
+    Status { status: u16, message: String },
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Http(e) => write!(f, "request failed: {e}"),
+            Self::Status { status, message } => write!(f, "server returned {status}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Http(e) => Some(e),
+            Self::Status { .. } => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Http(e)
+    }
+}
+
+/// Client for one server, and optionally one of its panels.
+#[derive(Clone, Debug)]
+pub struct MatrixClient {
+    http: reqwest::Client,
+    /// e.g. `http://pi:8080`, without a trailing slash
+    base_url: String,
+    /// Panel id sent as `?panel=`; `None` leaves it to the server (panel 0)
+    panel: Option<u32>,
+}
+
+impl MatrixClient {
+    /// A client for the server at `base_url`, e.g. `http://pi:8080`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        while base_url.ends_with('/') {
+            base_url.pop();
+        }
+        Self {
+            http: reqwest::Client::new(),
+            base_url,
+            panel: None,
+        }
+    }
+
+    /// The same client, sending every request to panel `panel`.
+    pub fn for_panel(mut self, panel: u32) -> Self {
+        self.panel = Some(panel);
+        self
+    }
+
+    /// `POST /api/v1/display/image`
+    pub async fn show_image(&self, req: &ImageRequest) -> Result<(), ClientError> {
+        self.post_json("/api/v1/display/image", req).await
+    }
+
+    /// `POST /api/v1/display/video`
+    pub async fn play_video(&self, req: &VideoRequest) -> Result<(), ClientError> {
+        self.post_json("/api/v1/display/video", req).await
+    }
+
+    /// `POST /api/v1/display/text`
+    pub async fn scroll_text(&self, req: &TextRequest) -> Result<(), ClientError> {
+        self.post_json("/api/v1/display/text", req).await
+    }
+
+    /// `POST /api/v1/brightness` with a level from 0 to 100.
+    pub async fn set_brightness(&self, value: u8) -> Result<(), ClientError> {
+        self.post_json("/api/v1/brightness", &BrightnessRequest { value })
+            .await
+    }
+
+    /// `POST /api/v1/display/frame` with raw RGB bytes, `rows * cols * 3`
+    /// of them.
+    pub async fn push_frame(&self, frame: &[u8]) -> Result<(), ClientError> {
+        let request = self
+            .http
+            .post(self.url("/api/v1/display/frame"))
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .body(frame.to_vec());
+        Self::check(request.send().await?).await?;
+        Ok(())
+    }
+
+    /// `GET /api/v1/status`
+    pub async fn status(&self) -> Result<DisplayStatus, ClientError> {
+        self.get_json("/api/v1/status").await
+    }
+
+    fn url(&self, path: &str) -> String {
+        match self.panel {
+            Some(panel) => format!("{}{path}?panel={panel}", self.base_url),
+            None => format!("{}{path}", self.base_url),
+        }
+    }
+
+    async fn post_json<T: Serialize>(&self, path: &str, body: &T) -> Result<(), ClientError> {
+        let response = self.http.post(self.url(path)).json(body).send().await?;
+        Self::check(response).await?;
+        Ok(())
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        let response = self.http.get(self.url(path)).send().await?;
+        Ok(Self::check(response).await?.json().await?)
+    }
+
+    /// Turn an error status into `ClientError::Status`, with the server's
+    /// plain-text explanation as the message.
+    async fn check(response: reqwest::Response) -> Result<reqwest::Response, ClientError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        Err(ClientError::Status {
+            status: status.as_u16(),
+            message: response.text().await.unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::body::Bytes;
+    use axum::extract::State;
+    use axum::http::{HeaderMap, Method, StatusCode, Uri, header};
+    use axum::response::{IntoResponse, Json, Response};
+    use pretty_assertions::assert_eq;
+    use std::sync::{Arc, Mutex};
+
+    /// A request the mock server received.
+    #[derive(Debug, PartialEq)]
+    struct Received {
+        method: Method,
+        uri: String,
+        content_type: Option<String>,
+        body: Vec<u8>,
+    }
+
+    type Log = Arc<Mutex<Vec<Received>>>;
+
+    /// Records every request. Status is answered with a fresh status and
+    /// panel 3 doesn't exist; everything else succeeds.
+    async fn mock(
+        State(log): State<Log>,
+        method: Method,
+        uri: Uri,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Response {
+        log.lock().unwrap().push(Received {
+            method,
+            uri: uri.to_string(),
+            content_type: headers
+                .get(header::CONTENT_TYPE)
+                .map(|value| value.to_str().unwrap().to_string()),
+            body: body.to_vec(),
+        });
+        if uri.query() == Some("panel=3") {
+            (StatusCode::NOT_FOUND, "Unknown panel 3").into_response()
+        } else if uri.path() == "/api/v1/status" {
+            Json(DisplayStatus::new()).into_response()
+        } else {
+            StatusCode::OK.into_response()
+        }
+    }
+
+    /// A client for a mock server on a free port, and what it receives.
+    async fn mock_server() -> (MatrixClient, Log) {
+        let log = Log::default();
+        let app = Router::new().fallback(mock).with_state(log.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (MatrixClient::new(format!("http://{addr}/")), log)
+    }
+
+    fn json_body(received: &Received) -> serde_json::Value {
+        serde_json::from_slice(&received.body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn posts_display_requests_as_json() {
+        let (client, log) = mock_server().await;
+        let image = ImageRequest::new("images/a.png");
+        let video = VideoRequest {
+            loop_playback: true,
+            ..VideoRequest::new("videos/eyes")
+        };
+        let text = TextRequest::new("Hello!");
+
+        client.show_image(&image).await.unwrap();
+        client.play_video(&video).await.unwrap();
+        client.scroll_text(&text).await.unwrap();
+
+        let log = log.lock().unwrap();
+        let uris: Vec<_> = log.iter().map(|r| (&r.method, r.uri.as_str())).collect();
+        assert_eq!(
+            uris,
+            [
+                (&Method::POST, "/api/v1/display/image"),
+                (&Method::POST, "/api/v1/display/video"),
+                (&Method::POST, "/api/v1/display/text"),
+            ]
+        );
+        assert_eq!(log[0].content_type.as_deref(), Some("application/json"));
+        assert_eq!(json_body(&log[0]), serde_json::to_value(&image).unwrap());
+        assert_eq!(json_body(&log[1])["loop"], true);
+        assert_eq!(json_body(&log[2])["text"], "Hello!");
+    }
+
+    #[tokio::test]
+    async fn targets_the_chosen_panel() {
+        let (client, log) = mock_server().await;
+        client.for_panel(1).set_brightness(40).await.unwrap();
+
+        let log = log.lock().unwrap();
+        assert_eq!(log[0].uri, "/api/v1/brightness?panel=1");
+        assert_eq!(json_body(&log[0]), serde_json::json!({ "value": 40 }));
+    }
+
+    #[tokio::test]
+    async fn pushes_raw_frames() {
+        let (client, log) = mock_server().await;
+        client.push_frame(&[255, 0, 0, 0, 255, 0]).await.unwrap();
+
+        assert_eq!(
+            log.lock().unwrap()[0],
+            Received {
+                method: Method::POST,
+                uri: "/api/v1/display/frame".to_string(),
+                content_type: Some("application/octet-stream".to_string()),
+                body: vec![255, 0, 0, 0, 255, 0],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn reads_the_status() {
+        let (client, _) = mock_server().await;
+        let status = client.status().await.unwrap();
+        assert_eq!(status.state, crate::api::DisplayState::Idle);
+        assert_eq!(status.version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[tokio::test]
+    async fn reports_the_servers_error_message() {
+        let (client, _) = mock_server().await;
+        let err = client.for_panel(3).status().await.unwrap_err();
+        assert!(
+            matches!(&err, ClientError::Status { status: 404, message } if message == "Unknown panel 3"),
+            "{err:?}"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Recent command history, for debugging a panel after the fact.
+//!
+//! Each render thread keeps the last `LOG_CAPACITY` commands it ran: when
+//! the server queued them, when the render thread started them, when they
+//! finished, and any error. "The display hitched at 3pm" can then be read
+//! off `GET /api/v1/diagnostics` without collecting logs: a long gap
+//! between queued and started means the render thread was busy, a long run
+//! means the command itself was slow.
+//!
+//! ## Rust concepts
+//! - `VecDeque` as a bounded ring buffer: push at the back, drop the front
+//! - `SystemTime` for wall-clock timestamps a client can line up with logs
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Commands kept per panel; older ones are dropped.
+pub const LOG_CAPACITY: usize = 200;
+
+/// One command the render thread ran.
+#[derive(Clone, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct CommandRecord {
+    /// Increases by one per command, so gaps show where entries were dropped
+    pub id: u64,
+    /// Command name, e.g. `play_video`
+    pub command: &'static str,
+    /// When the server queued it, in milliseconds since the Unix epoch
+    pub queued_at_ms: u64,
+    /// When the render thread started it
+    pub started_at_ms: u64,
+    /// When it finished or was replaced by the next command; `None` while
+    /// it's still running
+    pub finished_at_ms: Option<u64>,
+    /// Why it failed, if it did
+    pub error: Option<String>,
+}
+
+/// The last `capacity` commands, oldest first.
+#[derive(Debug)]
+pub struct CommandLog {
+    records: VecDeque<CommandRecord>,
+    capacity: usize,
+    next_id: u64,
+    /// The running command, which `finish` and `fail` apply to
+    current: Option<u64>,
+}
+
+impl Default for CommandLog {
+    fn default() -> Self {
+        Self::new(LOG_CAPACITY)
+    }
+}
+
+impl CommandLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(capacity),
+            capacity,
+            next_id: 0,
+            current: None,
+        }
+    }
+
+    /// Record `command` starting at `started_at` after waiting `queued_for`
+    /// in the channel. It's the current command until `finish`.
+    pub fn start(&mut self, command: &'static str, queued_for: Duration, started_at: SystemTime) {
+        self.current = Some(self.push(command, queued_for, started_at, None));
+    }
+
+    /// Record a command that ran in passing, such as a brightness change
+    /// during a video, without replacing the current command.
+    pub fn record(
+        &mut self,
+        command: &'static str,
+        queued_for: Duration,
+        started_at: SystemTime,
+        finished_at: SystemTime,
+    ) {
+        self.push(command, queued_for, started_at, Some(finished_at));
+    }
+
+    /// Mark the current command finished.
+    pub fn finish(&mut self, at: SystemTime) {
+        if let Some(record) = self.current.take().and_then(|id| self.get_mut(id)) {
+            record.finished_at_ms = Some(unix_ms(at));
+        }
+    }
+
+    /// Note why the current command failed. It still needs `finish`.
+    pub fn fail(&mut self, error: String) {
+        if let Some(record) = self.current.and_then(|id| self.get_mut(id)) {
+            record.error = Some(error);
+        }
+    }
+
+    /// All kept records, oldest first.
+    pub fn records(&self) -> impl Iterator<Item = &CommandRecord> {
+        self.records.iter()
+    }
+
+    fn push(
+        &mut self,
+        command: &'static str,
+        queued_for: Duration,
+        started_at: SystemTime,
+        finished_at: Option<SystemTime>,
+    ) -> u64 {
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.records.push_back(CommandRecord {
+            id,
+            command,
+            queued_at_ms: unix_ms(started_at.checked_sub(queued_for).unwrap_or(started_at)),
+            started_at_ms: unix_ms(started_at),
+            finished_at_ms: finished_at.map(unix_ms),
+            error: None,
+        });
+        id
+    }
+
+    fn get_mut(&mut self, id: u64) -> Option<&mut CommandRecord> {
+        // Ids are consecutive, so the position follows from the oldest one
+        let oldest = self.records.front()?.id;
+        let index = id.checked_sub(oldest)?;
+        self.records.get_mut(usize::try_from(index).ok()?)
+    }
+}
+
+fn unix_ms(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn at(ms: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn records_a_commands_timeline() {
+        let mut log = CommandLog::new(4);
+        log.start("play_video", Duration::from_millis(30), at(1_000));
+        log.fail("no frames".to_string());
+        log.finish(at(1_500));
+
+        let records: Vec<_> = log.records().cloned().collect();
+        assert_eq!(
+            records,
+            [CommandRecord {
+                id: 0,
+                command: "play_video",
+                queued_at_ms: 970,
+                started_at_ms: 1_000,
+                finished_at_ms: Some(1_500),
+                error: Some("no frames".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn passing_commands_leave_the_current_one_running() {
+        let mut log = CommandLog::new(4);
+        log.start("play_video", Duration::ZERO, at(0));
+        log.record("set_brightness", Duration::ZERO, at(10), at(11));
+        log.finish(at(20));
+
+        let finished: Vec<_> = log.records().map(|r| r.finished_at_ms).collect();
+        assert_eq!(finished, [Some(20), Some(11)]);
+    }
+
+    #[test]
+    fn keeps_only_the_newest_records() {
+        let mut log = CommandLog::new(2);
+        for ms in 0..3 {
+            log.start("clear", Duration::ZERO, at(ms));
+            log.finish(at(ms));
+        }
+        // The running command can still be finished after older ones drop
+        log.start("stop", Duration::ZERO, at(5));
+        log.finish(at(6));
+
+        let ids: Vec<_> = log.records().map(|r| (r.id, r.finished_at_ms)).collect();
+        assert_eq!(ids, [(2, Some(2)), (3, Some(6))]);
+    }
+}
@@ -0,0 +1,543 @@
+//! Drawing primitives rendered into in-memory frames.
+//!
+//! Like `effects`, these helpers build an `RgbImage` the size of the panel
+//! instead of touching the LED canvas directly, so they can be unit-tested
+//! without the `hardware` feature. The render thread draws the result.
+//!
+//! ## Rust concepts
+//! - `f32::clamp` and `atan2` for simple geometry
+//! - Closures passed to `RgbImage::from_fn` to compute each pixel
+//! - Indexing a foreign type with a tuple (`code[(x, y)]`) via `Index`
+//! - `chunks_exact` to walk a slice in pairs
+
+use crate::Color;
+use image::{Rgb, RgbImage};
+use qrcode::QrCode;
+use serde::{Deserialize, Serialize};
+
+// ── Gauges ───────────────────────────────────────────────────────────
+
+/// Visual style of a gauge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GaugeStyle {
+    /// Bar filling from left to right
+    BarHorizontal,
+    /// Bar filling from bottom to top
+    BarVertical,
+    /// Ring filling clockwise from 12 o'clock
+    Ring,
+}
+
+/// Where `value` falls in `[min, max]`, as a fraction clamped to 0.0-1.0.
+///
+/// An empty or inverted range yields 0.0 rather than dividing by zero.
+pub fn gauge_fraction(value: f32, min: f32, max: f32) -> f32 {
+    if max <= min || value.is_nan() {
+        return 0.0;
+    }
+    ((value - min) / (max - min)).clamp(0.0, 1.0)
+}
+
+/// Render a gauge filled to `fraction` (0.0-1.0).
+///
+/// Pixels past the fill are drawn in `track` if given, otherwise left black.
+pub fn gauge(
+    width: u32,
+    height: u32,
+    fraction: f32,
+    style: GaugeStyle,
+    color: Color,
+    track: Option<Color>,
+) -> RgbImage {
+    let fill = Rgb::from(color);
+    let track = track.map(Rgb::from);
+    let black = Rgb([0, 0, 0]);
+    let fraction = fraction.clamp(0.0, 1.0);
+
+    match style {
+        GaugeStyle::BarHorizontal => {
+            let filled = (fraction * width as f32).round() as u32;
+            RgbImage::from_fn(width, height, |x, _| {
+                if x < filled {
+                    fill
+                } else {
+                    track.unwrap_or(black)
+                }
+            })
+        }
+        GaugeStyle::BarVertical => {
+            let filled = (fraction * height as f32).round() as u32;
+            RgbImage::from_fn(width, height, |_, y| {
+                if y >= height - filled {
+                    fill
+                } else {
+                    track.unwrap_or(black)
+                }
+            })
+        }
+        GaugeStyle::Ring => {
+            let cx = (width as f32 - 1.0) / 2.0;
+            let cy = (height as f32 - 1.0) / 2.0;
+            let outer = width.min(height) as f32 / 2.0;
+            let inner = outer * 0.65;
+
+            RgbImage::from_fn(width, height, |x, y| {
+                let dx = x as f32 - cx;
+                let dy = y as f32 - cy;
+                let dist = (dx * dx + dy * dy).sqrt();
+                if dist > outer || dist < inner {
+                    return black;
+                }
+
+                // Angle measured clockwise from 12 o'clock, as 0.0-1.0
+                let angle = dx.atan2(-dy);
+                let turn = angle.rem_euclid(std::f32::consts::TAU) / std::f32::consts::TAU;
+                if turn < fraction {
+                    fill
+                } else {
+                    track.unwrap_or(black)
+                }
+            })
+        }
+    }
+}
+
+// ── Gradients ────────────────────────────────────────────────────────
+
+/// Direction a gradient runs across the panel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GradientDirection {
+    /// `from` on the left edge, `to` on the right
+    #[default]
+    Horizontal,
+    /// `from` on the top edge, `to` on the bottom
+    Vertical,
+    /// `from` in the top-left corner, `to` in the bottom-right
+    Diagonal,
+}
+
+/// Fill a `width`x`height` frame with a linear gradient between two colors.
+pub fn gradient(
+    width: u32,
+    height: u32,
+    from: Color,
+    to: Color,
+    direction: GradientDirection,
+) -> RgbImage {
+    // Position along the gradient, so the first and last pixels get the
+    // exact end colors
+    let fraction = |pos: u32, len: u32| {
+        if len == 0 {
+            0.0
+        } else {
+            pos as f32 / len as f32
+        }
+    };
+
+    RgbImage::from_fn(width, height, |x, y| {
+        let t = match direction {
+            GradientDirection::Horizontal => fraction(x, width.saturating_sub(1)),
+            GradientDirection::Vertical => fraction(y, height.saturating_sub(1)),
+            GradientDirection::Diagonal => fraction(x + y, (width + height).saturating_sub(2)),
+        };
+        from.lerp(to, t).into()
+    })
+}
+
+// ── Polygons ─────────────────────────────────────────────────────────
+
+/// Draw a closed polygon through `points` onto a black `width`x`height`
+/// frame, clipped to the frame.
+///
+/// The outline is always drawn, so thin or degenerate shapes (and two-point
+/// "polygons", which are lines) stay visible. With `fill`, the inside is
+/// filled by a scanline pass using the even-odd rule.
+pub fn polygon(
+    width: u32,
+    height: u32,
+    points: &[(i32, i32)],
+    color: Color,
+    fill: bool,
+) -> RgbImage {
+    let mut img = RgbImage::new(width, height);
+    if fill {
+        fill_polygon(&mut img, points, color);
+    }
+    for (i, &from) in points.iter().enumerate() {
+        let to = points[(i + 1) % points.len()];
+        draw_line(&mut img, from, to, color);
+    }
+    img
+}
+
+/// Fill the pixels whose centers lie inside the polygon.
+///
+/// For each row, find where the horizontal line through the pixel centers
+/// crosses each edge; sorted, the crossings pair up into spans to fill.
+fn fill_polygon(img: &mut RgbImage, points: &[(i32, i32)], color: Color) {
+    let (width, height) = (img.width() as i32, img.height() as i32);
+    let mut crossings: Vec<f32> = Vec::new();
+
+    for y in 0..height {
+        let center = y as f32 + 0.5;
+        crossings.clear();
+        for (i, &(x0, y0)) in points.iter().enumerate() {
+            let (x1, y1) = points[(i + 1) % points.len()];
+            let (y0, y1) = (y0 as f32, y1 as f32);
+            // Half-open, so a vertex shared by two edges counts once
+            if (y0 <= center) != (y1 <= center) {
+                let t = (center - y0) / (y1 - y0);
+                crossings.push(x0 as f32 + t * (x1 - x0) as f32);
+            }
+        }
+        crossings.sort_by(f32::total_cmp);
+
+        for span in crossings.chunks_exact(2) {
+            // Pixels whose centers fall in [span[0], span[1])
+            let start = ((span[0] - 0.5).ceil() as i32).max(0);
+            let end = ((span[1] - 0.5).ceil() as i32).min(width);
+            for x in start..end {
+                img.put_pixel(x as u32, y as u32, color.into());
+            }
+        }
+    }
+}
+
+/// Draw a one-pixel line with Bresenham's algorithm, skipping pixels
+/// outside the image.
+fn draw_line(img: &mut RgbImage, (x0, y0): (i32, i32), (x1, y1): (i32, i32), color: Color) {
+    let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+    let (step_x, step_y) = ((x1 - x0).signum(), (y1 - y0).signum());
+    let (mut x, mut y) = (x0, y0);
+    let mut err = dx + dy;
+
+    loop {
+        if x >= 0 && y >= 0 && (x as u32) < img.width() && (y as u32) < img.height() {
+            img.put_pixel(x as u32, y as u32, color.into());
+        }
+        if x == x1 && y == y1 {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += step_x;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += step_y;
+        }
+    }
+}
+
+// ── QR codes ─────────────────────────────────────────────────────────
+
+/// Quiet zone widths (in modules) to try, largest first. The QR spec asks
+/// for 4, but small panels often can't spare that much.
+const QR_QUIET_ZONES: [u32; 4] = [4, 2, 1, 0];
+
+/// Render `data` as a QR code centered on a `width`x`height` frame.
+///
+/// Each module is scaled up by the largest whole number that still fits,
+/// so modules stay crisp square blocks. The widest quiet zone that allows
+/// a scale of at least 1 is kept clear around the code.
+pub fn qr_code(
+    data: &str,
+    width: u32,
+    height: u32,
+    color: Color,
+    background: Color,
+) -> Result<RgbImage, String> {
+    let code = QrCode::new(data.as_bytes()).map_err(|e| format!("Can't encode QR code: {e}"))?;
+    let modules = code.width() as u32;
+    let side = width.min(height);
+
+    let scale = QR_QUIET_ZONES
+        .iter()
+        .map(|quiet| side / (modules + quiet * 2))
+        .find(|&scale| scale >= 1)
+        .ok_or_else(|| {
+            format!("QR code needs {modules}x{modules} pixels, panel is {width}x{height}")
+        })?;
+
+    let size = modules * scale;
+    let left = (width - size) / 2;
+    let top = (height - size) / 2;
+    let dark = Rgb::from(color);
+    let light = Rgb::from(background);
+
+    Ok(RgbImage::from_fn(width, height, |x, y| {
+        if x < left || y < top || x >= left + size || y >= top + size {
+            return light;
+        }
+        let module = ((x - left) / scale) as usize;
+        let row = ((y - top) / scale) as usize;
+        if code[(module, row)] == qrcode::Color::Dark {
+            dark
+        } else {
+            light
+        }
+    }))
+}
+
+// ── Client bitmaps ───────────────────────────────────────────────────
+
+/// Pixel layout of a client-supplied bitmap.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BitmapFormat {
+    /// One byte per pixel, row by row
+    #[default]
+    Gray8,
+    /// One bit per pixel, most significant bit first, each row padded to a whole byte
+    Mono1,
+}
+
+impl BitmapFormat {
+    /// Number of bytes a `width`x`height` bitmap takes in this format, or
+    /// `None` if that doesn't fit in a `usize` (32 bits on the Pi).
+    pub fn byte_len(self, width: u32, height: u32) -> Option<usize> {
+        let row = match self {
+            Self::Gray8 => width,
+            Self::Mono1 => width.div_ceil(8),
+        };
+        usize::try_from(row)
+            .ok()?
+            .checked_mul(usize::try_from(height).ok()?)
+    }
+}
+
+/// Colorize a bitmap: nonzero pixels become `color`, the rest black.
+pub fn bitmap_image(
+    width: u32,
+    height: u32,
+    data: &[u8],
+    format: BitmapFormat,
+    color: Color,
+) -> Result<RgbImage, String> {
+    let expected = format
+        .byte_len(width, height)
+        .ok_or_else(|| format!("Bitmap of {width}x{height} is too large"))?;
+    if data.len() != expected {
+        return Err(format!(
+            "Bitmap size mismatch: {width}x{height} {format:?} needs {expected} bytes, got {}",
+            data.len()
+        ));
+    }
+
+    let on = Rgb::from(color);
+    let off = Rgb([0, 0, 0]);
+    // Fits, since the whole bitmap did
+    let row_bytes = expected / height as usize;
+
+    Ok(RgbImage::from_fn(width, height, |x, y| {
+        let row = y as usize * row_bytes;
+        let is_on = match format {
+            BitmapFormat::Gray8 => data[row + x as usize] != 0,
+            BitmapFormat::Mono1 => data[row + (x / 8) as usize] & (0x80 >> (x % 8)) != 0,
+        };
+        if is_on { on } else { off }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    const RED: Color = Color { r: 255, g: 0, b: 0 };
+    const GREY: Color = Color {
+        r: 20,
+        g: 20,
+        b: 20,
+    };
+
+    #[rstest]
+    #[case(50.0, 0.0, 100.0, 0.5)]
+    #[case(-10.0, 0.0, 100.0, 0.0)]
+    #[case(150.0, 0.0, 100.0, 1.0)]
+    #[case(5.0, 5.0, 5.0, 0.0)] // empty range
+    #[case(5.0, 10.0, 0.0, 0.0)] // inverted range
+    fn test_gauge_fraction(
+        #[case] value: f32,
+        #[case] min: f32,
+        #[case] max: f32,
+        #[case] expected: f32,
+    ) {
+        assert_eq!(gauge_fraction(value, min, max), expected);
+    }
+
+    #[test]
+    fn horizontal_bar_fills_left_columns() {
+        let img = gauge(10, 2, 0.3, GaugeStyle::BarHorizontal, RED, Some(GREY));
+        assert_eq!(*img.get_pixel(2, 1), Rgb::from(RED));
+        assert_eq!(*img.get_pixel(3, 1), Rgb::from(GREY));
+    }
+
+    #[test]
+    fn vertical_bar_fills_from_bottom() {
+        let img = gauge(2, 10, 0.2, GaugeStyle::BarVertical, RED, None);
+        assert_eq!(*img.get_pixel(0, 9), Rgb::from(RED));
+        assert_eq!(*img.get_pixel(0, 8), Rgb::from(RED));
+        assert_eq!(*img.get_pixel(0, 7), Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn ring_fills_clockwise_from_top() {
+        let img = gauge(32, 32, 0.25, GaugeStyle::Ring, RED, Some(GREY));
+        // Right side of the ring (3 o'clock is the 25% mark, just inside it)
+        assert_eq!(*img.get_pixel(29, 14), Rgb::from(RED));
+        // Left side is still track
+        assert_eq!(*img.get_pixel(2, 16), Rgb::from(GREY));
+        // Center is outside the ring
+        assert_eq!(*img.get_pixel(16, 16), Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn qr_code_is_centered_with_quiet_zone() {
+        let black = Color::new(0, 0, 0);
+        let white = Color::new(255, 255, 255);
+        // Short data fits a version 1 code: 21 modules, scaled 2x with a
+        // 4-module quiet zone on a 64px panel
+        let img = qr_code("hi", 64, 64, black, white).unwrap();
+
+        // (64 - 42) / 2 = 11px margin, then the finder pattern's dark corner
+        assert_eq!(*img.get_pixel(10, 10), Rgb::from(white));
+        assert_eq!(*img.get_pixel(11, 11), Rgb::from(black));
+        assert_eq!(*img.get_pixel(12, 12), Rgb::from(black));
+        assert_eq!(*img.get_pixel(52, 52), Rgb::from(white));
+    }
+
+    #[rstest]
+    #[case(GradientDirection::Horizontal, (4, 0), (0, 2))]
+    #[case(GradientDirection::Vertical, (0, 2), (4, 0))]
+    #[case(GradientDirection::Diagonal, (4, 2), (4, 0))]
+    fn gradient_reaches_end_colors(
+        #[case] direction: GradientDirection,
+        #[case] end: (u32, u32),
+        #[case] other_corner: (u32, u32),
+    ) {
+        let img = gradient(5, 3, RED, GREY, direction);
+        assert_eq!(*img.get_pixel(0, 0), Rgb::from(RED));
+        assert_eq!(*img.get_pixel(end.0, end.1), Rgb::from(GREY));
+        assert_ne!(
+            *img.get_pixel(other_corner.0, other_corner.1),
+            Rgb::from(GREY)
+        );
+    }
+
+    #[test]
+    fn single_pixel_gradient_uses_from_color() {
+        let img = gradient(1, 1, RED, GREY, GradientDirection::Diagonal);
+        assert_eq!(*img.get_pixel(0, 0), Rgb::from(RED));
+    }
+
+    #[test]
+    fn qr_code_too_large_for_panel_is_an_error() {
+        let data = "x".repeat(200);
+        let white = Color::new(255, 255, 255);
+        assert!(qr_code(&data, 32, 32, RED, white).is_err());
+        assert!(qr_code(&data, 64, 64, RED, white).is_ok());
+    }
+
+    #[test]
+    fn full_bar_has_no_track() {
+        let img = gauge(4, 4, 1.0, GaugeStyle::BarHorizontal, RED, Some(GREY));
+        assert!(img.pixels().all(|p| *p == Rgb::from(RED)));
+    }
+
+    #[test]
+    fn gray8_bitmap_treats_nonzero_as_on() {
+        let img = bitmap_image(3, 1, &[0, 1, 255], BitmapFormat::Gray8, RED).unwrap();
+        assert_eq!(*img.get_pixel(0, 0), Rgb([0, 0, 0]));
+        assert_eq!(*img.get_pixel(1, 0), Rgb::from(RED));
+        assert_eq!(*img.get_pixel(2, 0), Rgb::from(RED));
+    }
+
+    #[test]
+    fn mono1_bitmap_reads_padded_rows() {
+        // 10 pixels wide = 2 bytes per row; second row has only pixel 9 set
+        let data = [0b1000_0000, 0, 0, 0b0100_0000];
+        let img = bitmap_image(10, 2, &data, BitmapFormat::Mono1, RED).unwrap();
+        assert_eq!(*img.get_pixel(0, 0), Rgb::from(RED));
+        assert_eq!(*img.get_pixel(1, 0), Rgb([0, 0, 0]));
+        assert_eq!(*img.get_pixel(9, 1), Rgb::from(RED));
+        assert_eq!(img.pixels().filter(|p| **p == Rgb::from(RED)).count(), 2);
+    }
+
+    #[rstest]
+    #[case(BitmapFormat::Gray8, 5)]
+    #[case(BitmapFormat::Mono1, 3)]
+    fn bitmap_rejects_wrong_length(#[case] format: BitmapFormat, #[case] len: usize) {
+        assert!(bitmap_image(4, 4, &vec![0; len], format, RED).is_err());
+    }
+
+    #[test]
+    fn bitmap_size_does_not_overflow() {
+        assert_eq!(BitmapFormat::Mono1.byte_len(10, 2), Some(4));
+        let huge = BitmapFormat::Gray8.byte_len(u32::MAX, u32::MAX);
+        assert_eq!(huge, (u32::MAX as usize).checked_mul(u32::MAX as usize));
+        assert!(bitmap_image(u32::MAX, u32::MAX, &[0; 4], BitmapFormat::Gray8, RED).is_err());
+    }
+
+    fn lit(img: &RgbImage) -> Vec<(u32, u32)> {
+        img.enumerate_pixels()
+            .filter(|(_, _, p)| p.0 != [0, 0, 0])
+            .map(|(x, y, _)| (x, y))
+            .collect()
+    }
+
+    #[test]
+    fn outline_draws_only_the_edges() {
+        let img = polygon(5, 5, &[(0, 0), (3, 0), (3, 2), (0, 2)], RED, false);
+        assert_eq!(*img.get_pixel(3, 1), Rgb::from(RED));
+        assert_eq!(*img.get_pixel(1, 1), Rgb([0, 0, 0]));
+        assert_eq!(lit(&img).len(), 10);
+    }
+
+    #[test]
+    fn filled_polygon_covers_the_inside() {
+        let img = polygon(5, 5, &[(0, 0), (3, 0), (3, 2), (0, 2)], RED, true);
+        let expected: Vec<(u32, u32)> = (0..3).flat_map(|y| (0..4).map(move |x| (x, y))).collect();
+        let mut actual = lit(&img);
+        actual.sort_by_key(|&(x, y)| (y, x));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn filled_triangle_leaves_the_far_corner_empty() {
+        let img = polygon(8, 8, &[(0, 0), (7, 0), (0, 7)], RED, true);
+        assert_eq!(*img.get_pixel(2, 2), Rgb::from(RED));
+        assert_eq!(*img.get_pixel(0, 7), Rgb::from(RED));
+        assert_eq!(*img.get_pixel(6, 6), Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn polygon_is_clipped_to_the_frame() {
+        let img = polygon(
+            4,
+            4,
+            &[(-10, -10), (20, -10), (20, 20), (-10, 20)],
+            RED,
+            true,
+        );
+        assert_eq!(lit(&img).len(), 16);
+    }
+
+    #[rstest]
+    #[case((0, 0), (3, 3), vec![(0, 0), (1, 1), (2, 2), (3, 3)])]
+    #[case((3, 1), (0, 1), vec![(0, 1), (1, 1), (2, 1), (3, 1)])]
+    #[case((2, 2), (2, 2), vec![(2, 2)])]
+    fn test_draw_line(
+        #[case] from: (i32, i32),
+        #[case] to: (i32, i32),
+        #[case] expected: Vec<(u32, u32)>,
+    ) {
+        let mut img = RgbImage::new(4, 4);
+        draw_line(&mut img, from, to, RED);
+        assert_eq!(lit(&img), expected);
+    }
+}
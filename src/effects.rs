@@ -0,0 +1,659 @@
+//! Transitions and procedural effects, computed on in-memory frames.
+//!
+//! Everything here works on `RgbImage` buffers rather than the LED canvas,
+//! so it builds and tests without the `hardware` feature. The render thread
+//! calls these helpers to produce each frame, then draws the result.
+//!
+//! ## Rust concepts
+//! - Pure functions: same inputs always give the same output
+//! - `Option` from `get_pixel_checked` instead of panicking on out-of-bounds
+//! - `serde` enums with `rename_all` for JSON-friendly variant names
+
+use crate::Color;
+use crate::bdf::BdfFont;
+use image::{Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// ── Wipe transition ──────────────────────────────────────────────────
+
+/// Direction a wipe edge travels across the panel.
+///
+/// `Right` starts at the left edge and reveals the new image column by
+/// column towards the right; `Down` starts at the top and moves down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl WipeDirection {
+    /// Number of steps (columns or rows) needed to fully reveal an image.
+    pub fn steps(self, width: u32, height: u32) -> u32 {
+        match self {
+            Self::Left | Self::Right => width,
+            Self::Up | Self::Down => height,
+        }
+    }
+}
+
+/// Compose one step of a wipe: the first `revealed` columns/rows (in the
+/// wipe direction) come from `next`, the rest from `previous`.
+///
+/// The output has the dimensions of `next`. Pixels missing from `previous`
+/// (e.g. it was a different size) are treated as black.
+pub fn wipe_frame(
+    previous: &RgbImage,
+    next: &RgbImage,
+    direction: WipeDirection,
+    revealed: u32,
+) -> RgbImage {
+    let (width, height) = next.dimensions();
+    let black = Rgb([0, 0, 0]);
+
+    RgbImage::from_fn(width, height, |x, y| {
+        let is_revealed = match direction {
+            WipeDirection::Right => x < revealed,
+            WipeDirection::Left => x >= width.saturating_sub(revealed),
+            WipeDirection::Down => y < revealed,
+            WipeDirection::Up => y >= height.saturating_sub(revealed),
+        };
+
+        if is_revealed {
+            *next.get_pixel(x, y)
+        } else {
+            previous.get_pixel_checked(x, y).copied().unwrap_or(black)
+        }
+    })
+}
+
+// ── Fade ─────────────────────────────────────────────────────────────
+
+/// One step of a fade from `from` to a solid `to`, `t` of the way there
+/// (0.0 is `from` unchanged, 1.0 is all `to`).
+pub fn fade_frame(from: &RgbImage, to: Rgb<u8>, t: f32) -> RgbImage {
+    let t = t.clamp(0.0, 1.0);
+    RgbImage::from_fn(from.width(), from.height(), |x, y| {
+        mix(*from.get_pixel(x, y), to, t)
+    })
+}
+
+/// One step of a crossfade from `from` to `to`, `t` of the way there.
+///
+/// The output has the dimensions of `to`; pixels missing from `from` are
+/// treated as black, as in `wipe_frame`.
+pub fn crossfade_frame(from: &RgbImage, to: &RgbImage, t: f32) -> RgbImage {
+    let t = t.clamp(0.0, 1.0);
+    RgbImage::from_fn(to.width(), to.height(), |x, y| {
+        let start = from
+            .get_pixel_checked(x, y)
+            .copied()
+            .unwrap_or(Rgb([0, 0, 0]));
+        mix(start, *to.get_pixel(x, y), t)
+    })
+}
+
+fn mix(a: Rgb<u8>, b: Rgb<u8>, t: f32) -> Rgb<u8> {
+    Rgb(std::array::from_fn(|i| {
+        let (a, b) = (a[i] as f32, b[i] as f32);
+        (a + (b - a) * t).round() as u8
+    }))
+}
+
+// ── Slideshow transitions ────────────────────────────────────────────
+
+/// How a slideshow moves from one image to the next.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SlideTransition {
+    /// Switch straight to the next image
+    Cut,
+    /// Crossfade from one image to the next
+    #[default]
+    Fade,
+    WipeLeft,
+    WipeRight,
+    WipeUp,
+    WipeDown,
+}
+
+impl SlideTransition {
+    /// Frames drawn by a crossfade, whatever the panel size.
+    const FADE_STEPS: u32 = 25;
+
+    /// Number of frames the transition takes on a `width` x `height` panel;
+    /// the last one is always the next image itself.
+    pub fn steps(self, width: u32, height: u32) -> u32 {
+        match self.wipe_direction() {
+            Some(direction) => direction.steps(width, height).max(1),
+            None if self == Self::Fade => Self::FADE_STEPS,
+            None => 1,
+        }
+    }
+
+    /// Frame `step` (1 to `steps`) of the transition from `previous` to `next`.
+    pub fn frame(self, previous: &RgbImage, next: &RgbImage, step: u32, steps: u32) -> RgbImage {
+        match self.wipe_direction() {
+            Some(direction) => wipe_frame(previous, next, direction, step),
+            None if self == Self::Fade => {
+                crossfade_frame(previous, next, step as f32 / steps as f32)
+            }
+            None => next.clone(),
+        }
+    }
+
+    fn wipe_direction(self) -> Option<WipeDirection> {
+        match self {
+            Self::WipeLeft => Some(WipeDirection::Left),
+            Self::WipeRight => Some(WipeDirection::Right),
+            Self::WipeUp => Some(WipeDirection::Up),
+            Self::WipeDown => Some(WipeDirection::Down),
+            Self::Cut | Self::Fade => None,
+        }
+    }
+}
+
+// ── Scrolling ────────────────────────────────────────────────────────
+
+/// Position of content scrolling right to left at `speed` pixels per
+/// second, from `start_x` until it's past `end_x`, then round again.
+///
+/// Movement comes from the time that actually passed, with the fraction
+/// of a pixel carried over to the next step, so the speed holds however
+/// long each frame takes: a late frame moves further instead of slowing
+/// the scroll down.
+#[derive(Clone, Debug)]
+pub struct Scroll {
+    start_x: i32,
+    end_x: i32,
+    x: i32,
+    speed: f64,
+    /// Movement towards the next whole pixel, in pixels (0.0 to 1.0)
+    fraction: f64,
+    /// Times the content has wrapped back to `start_x`
+    passes: u32,
+}
+
+impl Scroll {
+    pub fn new(start_x: i32, end_x: i32, speed: u32) -> Self {
+        Self {
+            start_x,
+            end_x,
+            x: start_x,
+            speed: speed.max(1) as f64,
+            fraction: 0.0,
+            passes: 0,
+        }
+    }
+
+    /// The same scroll with the content's left edge at `x` for the first
+    /// pass, e.g. already on the panel or partly off its left edge. Later
+    /// passes start from `start_x` as usual.
+    pub fn starting_at(mut self, x: i32) -> Self {
+        self.x = x;
+        self
+    }
+
+    /// Left edge of the content, in whole pixels.
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    /// Complete passes so far, each one ending when the content wraps.
+    pub fn passes(&self) -> u32 {
+        self.passes
+    }
+
+    /// Move on by `elapsed`, wrapping back to `start_x` after `end_x`.
+    pub fn advance(&mut self, elapsed: Duration) {
+        let moved = self.fraction + elapsed.as_secs_f64() * self.speed;
+        self.fraction = moved.fract();
+        let x = self.x as i64 - moved as i64;
+        if x >= self.end_x as i64 {
+            self.x = x as i32;
+            return;
+        }
+        // Every position from `start_x` down to `end_x` is shown in turn;
+        // `past` counts the positions after wrapping back to `start_x`
+        let span = (self.start_x - self.end_x + 1).max(1) as i64;
+        let past = self.end_x as i64 - 1 - x;
+        self.passes = self.passes.saturating_add((1 + past / span) as u32);
+        self.x = self.start_x - (past % span) as i32;
+    }
+
+    /// Time until the content is due to move by another whole pixel.
+    pub fn until_next_pixel(&self) -> Duration {
+        Duration::from_secs_f64((1.0 - self.fraction) / self.speed)
+    }
+}
+
+/// Whether a `width` x `height` block of text with its top-left corner at
+/// (`x`, `top`) shows on a panel `rows` tall at some point while it
+/// scrolls left from there.
+///
+/// Anything to the right scrolls into view eventually; text already past
+/// the left edge, or entirely above or below the panel, never does.
+pub fn scroll_start_visible(x: i32, top: i32, width: i32, height: i32, rows: u32) -> bool {
+    x + width > 0 && top + height > 0 && top < rows as i32
+}
+
+/// One frame of a horizontal scroll: `strip` placed with its left edge at
+/// `x` (may be negative or past the right edge), centered vertically.
+pub fn scroll_frame(strip: &RgbImage, x: i32, width: u32, height: u32) -> RgbImage {
+    let mut frame = RgbImage::new(width, height);
+    let y = (height as i64 - strip.height() as i64) / 2;
+    image::imageops::replace(&mut frame, strip, x as i64, y);
+    frame
+}
+
+/// One frame of an icon marquee: `icon` fixed at the left edge (centered
+/// vertically) and `strip` scrolling in the space to its right.
+///
+/// `x` is relative to the text area, so the strip is clipped at the icon's
+/// edge instead of scrolling underneath it.
+pub fn marquee_frame(
+    icon: &RgbImage,
+    strip: &RgbImage,
+    x: i32,
+    width: u32,
+    height: u32,
+) -> RgbImage {
+    let icon_width = icon.width().min(width);
+    let text_area = scroll_frame(strip, x, width - icon_width, height);
+
+    let mut frame = RgbImage::new(width, height);
+    let icon_y = (height as i64 - icon.height() as i64) / 2;
+    image::imageops::replace(&mut frame, icon, 0, icon_y);
+    image::imageops::replace(&mut frame, &text_area, icon_width as i64, 0);
+    frame
+}
+
+// ── Title overlay ────────────────────────────────────────────────────
+
+/// `frame` with `title` in a darkened band along the bottom edge, blended
+/// in at `opacity` (0.0 leaves the frame unchanged, 1.0 draws it fully).
+/// A title wider than the frame is clipped at the right edge.
+pub fn title_overlay(
+    frame: &RgbImage,
+    font: &BdfFont,
+    title: &str,
+    color: Rgb<u8>,
+    opacity: f32,
+) -> RgbImage {
+    let opacity = opacity.clamp(0.0, 1.0);
+    if opacity == 0.0 {
+        return frame.clone();
+    }
+
+    let band_height = (font.height() + 2).max(0) as u32;
+    let band_top = frame.height().saturating_sub(band_height);
+    let mut layer = frame.clone();
+    for (_, y, pixel) in layer.enumerate_pixels_mut() {
+        if y >= band_top {
+            pixel.0 = pixel.0.map(|c| c / 4);
+        }
+    }
+    font.draw_text(
+        &mut layer,
+        title,
+        1,
+        band_top as i32 + 1 + font.ascent(),
+        color,
+    );
+
+    RgbImage::from_fn(frame.width(), frame.height(), |x, y| {
+        let (under, over) = (frame.get_pixel(x, y), layer.get_pixel(x, y));
+        Rgb(std::array::from_fn(|i| {
+            let (a, b) = (under[i] as f32, over[i] as f32);
+            (a + (b - a) * opacity).round() as u8
+        }))
+    })
+}
+
+/// Opacity of an overlay shown fully for `hold`, then faded out linearly
+/// over `fade`, at `elapsed` since it appeared.
+pub fn fade_out_opacity(elapsed: Duration, hold: Duration, fade: Duration) -> f32 {
+    if elapsed <= hold {
+        return 1.0;
+    }
+    if fade.is_zero() {
+        return 0.0;
+    }
+    let fading = (elapsed - hold).as_secs_f32() / fade.as_secs_f32();
+    (1.0 - fading).max(0.0)
+}
+
+// ── Color wheel ──────────────────────────────────────────────────────
+
+/// A radial rainbow: hue varies with the angle around the panel center.
+///
+/// The angle of each pixel never changes, so it's computed once in `new`;
+/// each frame only adds the rotation phase and converts to a color.
+pub struct ColorWheel {
+    width: u32,
+    height: u32,
+    /// Hue (0.0-360.0) of each pixel at phase 0, indexed by `y * width + x`
+    base_hues: Vec<f32>,
+}
+
+impl ColorWheel {
+    pub fn new(width: u32, height: u32) -> Self {
+        let cx = (width as f32 - 1.0) / 2.0;
+        let cy = (height as f32 - 1.0) / 2.0;
+        let base_hues = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let angle = (y as f32 - cy).atan2(x as f32 - cx);
+                angle.to_degrees().rem_euclid(360.0)
+            })
+            .collect();
+
+        Self {
+            width,
+            height,
+            base_hues,
+        }
+    }
+
+    /// Render the wheel rotated by `phase` degrees.
+    pub fn frame(&self, phase: f32) -> RgbImage {
+        RgbImage::from_fn(self.width, self.height, |x, y| {
+            let base = self.base_hues[(y * self.width + x) as usize];
+            let hue = (base + phase).rem_euclid(360.0) as u16;
+            Rgb::from(Color::from_hue(hue))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    const OLD: Rgb<u8> = Rgb([10, 10, 10]);
+    const NEW: Rgb<u8> = Rgb([200, 0, 0]);
+
+    #[test]
+    fn scroll_keeps_to_its_speed_whatever_the_frame_timing() {
+        let mut scroll = Scroll::new(1000, -1000, 37);
+        // Uneven frames adding up to one second
+        let steps_ms = [3, 41, 16, 16, 120, 1, 7, 250, 33, 13, 500];
+        assert_eq!(steps_ms.iter().sum::<u64>(), 1000);
+        for ms in steps_ms {
+            scroll.advance(Duration::from_millis(ms));
+        }
+        let moved = 1000 - scroll.x();
+        assert!((36..=37).contains(&moved), "moved {moved} pixels");
+    }
+
+    #[test]
+    fn scroll_wraps_after_the_end() {
+        let mut scroll = Scroll::new(4, -2, 8);
+        // 6 pixels at 8 px/s: 4, 3, 2, 1, 0, -1, then -2
+        scroll.advance(Duration::from_millis(750));
+        assert_eq!(scroll.x(), -2);
+        scroll.advance(Duration::from_millis(125));
+        assert_eq!(scroll.x(), 4);
+        // 16 pixels is two laps of 7 and 2 more
+        scroll.advance(Duration::from_secs(2));
+        assert_eq!(scroll.x(), 2);
+    }
+
+    #[test]
+    fn scroll_counts_passes_until_a_repeat_target() {
+        let repeat = 3;
+        let mut scroll = Scroll::new(4, -2, 8);
+        let mut steps = 0;
+        // What the render thread does for `repeat`: step until it's reached
+        while scroll.passes() < repeat {
+            scroll.advance(Duration::from_millis(125));
+            steps += 1;
+        }
+        // 7 positions a pass, one pixel a step
+        assert_eq!(steps, 3 * 7);
+        assert_eq!(scroll.passes(), repeat);
+        assert_eq!(scroll.x(), 4);
+    }
+
+    #[test]
+    fn scroll_starts_from_a_given_position() {
+        // Text 6 wide scrolling on a panel 10 wide: start_x 10, end_x -6
+        let mut scroll = Scroll::new(10, -6, 8).starting_at(-3);
+        assert_eq!(scroll.x(), -3);
+        // 3 pixels on, the last visible column is about to leave
+        scroll.advance(Duration::from_millis(375));
+        assert_eq!(scroll.x(), -6);
+        assert_eq!(scroll.passes(), 0);
+        // The next pass comes in from the right edge as usual
+        scroll.advance(Duration::from_millis(125));
+        assert_eq!((scroll.x(), scroll.passes()), (10, 1));
+
+        // Starting further right than the right edge just takes longer
+        let mut scroll = Scroll::new(10, -6, 8).starting_at(20);
+        scroll.advance(Duration::from_secs(1));
+        assert_eq!((scroll.x(), scroll.passes()), (12, 0));
+    }
+
+    #[rstest]
+    #[case::on_the_panel(0, 0, true)]
+    #[case::partly_off_the_left(-5, 0, true)]
+    #[case::partly_off_the_top(0, -12, true)]
+    #[case::off_the_right(100, 0, true)]
+    #[case::past_the_left_edge(-6, 0, false)]
+    #[case::above_the_panel(0, -13, false)]
+    #[case::below_the_panel(0, 32, false)]
+    fn scroll_start_must_become_visible(#[case] x: i32, #[case] top: i32, #[case] visible: bool) {
+        // Text 6 wide and 13 tall on a 32-row panel
+        assert_eq!(scroll_start_visible(x, top, 6, 13, 32), visible);
+    }
+
+    #[test]
+    fn scroll_waits_for_the_rest_of_a_pixel() {
+        let mut scroll = Scroll::new(0, -10, 8);
+        assert_eq!(scroll.until_next_pixel(), Duration::from_millis(125));
+        scroll.advance(Duration::from_millis(100));
+        assert_eq!(scroll.x(), 0);
+        let left = scroll.until_next_pixel().as_secs_f64();
+        assert!((left - 0.025).abs() < 1e-9, "{left}");
+    }
+
+    fn solid(width: u32, height: u32, px: Rgb<u8>) -> RgbImage {
+        RgbImage::from_pixel(width, height, px)
+    }
+
+    #[rstest]
+    #[case(WipeDirection::Left, 8)]
+    #[case(WipeDirection::Right, 8)]
+    #[case(WipeDirection::Up, 4)]
+    #[case(WipeDirection::Down, 4)]
+    fn wipe_steps_follow_direction(#[case] direction: WipeDirection, #[case] expected: u32) {
+        assert_eq!(direction.steps(8, 4), expected);
+    }
+
+    #[test]
+    fn wipe_right_reveals_leading_columns() {
+        let frame = wipe_frame(
+            &solid(4, 2, OLD),
+            &solid(4, 2, NEW),
+            WipeDirection::Right,
+            1,
+        );
+        assert_eq!(*frame.get_pixel(0, 0), NEW);
+        assert_eq!(*frame.get_pixel(0, 1), NEW);
+        assert_eq!(*frame.get_pixel(1, 0), OLD);
+        assert_eq!(*frame.get_pixel(3, 1), OLD);
+    }
+
+    #[test]
+    fn wipe_left_reveals_trailing_columns() {
+        let frame = wipe_frame(&solid(4, 2, OLD), &solid(4, 2, NEW), WipeDirection::Left, 1);
+        assert_eq!(*frame.get_pixel(3, 0), NEW);
+        assert_eq!(*frame.get_pixel(2, 0), OLD);
+    }
+
+    #[test]
+    fn wipe_up_reveals_bottom_rows() {
+        let frame = wipe_frame(&solid(2, 4, OLD), &solid(2, 4, NEW), WipeDirection::Up, 2);
+        assert_eq!(*frame.get_pixel(0, 3), NEW);
+        assert_eq!(*frame.get_pixel(0, 2), NEW);
+        assert_eq!(*frame.get_pixel(0, 1), OLD);
+    }
+
+    #[rstest]
+    #[case(WipeDirection::Left)]
+    #[case(WipeDirection::Right)]
+    #[case(WipeDirection::Up)]
+    #[case(WipeDirection::Down)]
+    fn wipe_complete_matches_next(#[case] direction: WipeDirection) {
+        let next = solid(4, 4, NEW);
+        let frame = wipe_frame(&solid(4, 4, OLD), &next, direction, direction.steps(4, 4));
+        assert_eq!(frame, next);
+    }
+
+    #[test]
+    fn wipe_treats_missing_previous_pixels_as_black() {
+        let frame = wipe_frame(
+            &solid(1, 1, OLD),
+            &solid(3, 1, NEW),
+            WipeDirection::Right,
+            0,
+        );
+        assert_eq!(*frame.get_pixel(0, 0), OLD);
+        assert_eq!(*frame.get_pixel(2, 0), Rgb([0, 0, 0]));
+    }
+
+    #[rstest]
+    #[case(0.0, Rgb([200, 100, 0]))]
+    #[case(0.5, Rgb([100, 50, 0]))]
+    #[case(1.0, Rgb([0, 0, 0]))]
+    #[case(2.0, Rgb([0, 0, 0]))] // clamped
+    fn fade_frame_blends_towards_target(#[case] t: f32, #[case] expected: Rgb<u8>) {
+        let frame = fade_frame(&solid(2, 2, Rgb([200, 100, 0])), Rgb([0, 0, 0]), t);
+        assert_eq!(frame, solid(2, 2, expected));
+    }
+
+    #[rstest]
+    #[case(SlideTransition::Cut, 1)]
+    #[case(SlideTransition::Fade, 25)]
+    #[case(SlideTransition::WipeRight, 4)]
+    #[case(SlideTransition::WipeDown, 2)]
+    fn slide_transitions_end_on_the_next_image(
+        #[case] transition: SlideTransition,
+        #[case] expected_steps: u32,
+    ) {
+        let previous = solid(4, 2, Rgb([200, 0, 0]));
+        let next = solid(4, 2, Rgb([0, 0, 200]));
+
+        let steps = transition.steps(4, 2);
+        assert_eq!(steps, expected_steps);
+        assert_eq!(transition.frame(&previous, &next, steps, steps), next);
+    }
+
+    #[test]
+    fn crossfade_mixes_both_images() {
+        let frame = crossfade_frame(
+            &solid(2, 2, Rgb([200, 0, 0])),
+            &solid(2, 2, Rgb([0, 0, 100])),
+            0.5,
+        );
+        assert_eq!(frame, solid(2, 2, Rgb([100, 0, 50])));
+    }
+
+    #[test]
+    fn scroll_frame_clips_and_centers_strip() {
+        let strip = solid(4, 2, NEW);
+        let frame = scroll_frame(&strip, -2, 4, 4);
+
+        // Rows 1-2 hold the strip; only its right half is still on screen
+        assert_eq!(*frame.get_pixel(0, 1), NEW);
+        assert_eq!(*frame.get_pixel(1, 2), NEW);
+        assert_eq!(*frame.get_pixel(2, 1), Rgb([0, 0, 0]));
+        assert_eq!(*frame.get_pixel(0, 0), Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn marquee_frame_keeps_text_out_of_the_icon() {
+        let icon = solid(2, 2, OLD);
+        let strip = solid(8, 4, NEW);
+        // Scrolled far left: without clipping the strip would cover x=0-5
+        let frame = marquee_frame(&icon, &strip, -2, 6, 4);
+
+        assert_eq!(*frame.get_pixel(0, 1), OLD);
+        assert_eq!(*frame.get_pixel(1, 2), OLD);
+        assert_eq!(*frame.get_pixel(0, 0), Rgb([0, 0, 0]));
+        assert_eq!(*frame.get_pixel(2, 0), NEW);
+        assert_eq!(*frame.get_pixel(5, 3), NEW);
+    }
+
+    #[test]
+    fn color_wheel_hue_follows_angle() {
+        let wheel = ColorWheel::new(9, 9);
+        let frame = wheel.frame(0.0);
+        // Right of center is 0° (red), below center is 90° in screen coordinates
+        assert_eq!(*frame.get_pixel(8, 4), Rgb::from(Color::from_hue(0)));
+        assert_eq!(*frame.get_pixel(4, 8), Rgb::from(Color::from_hue(90)));
+        assert_eq!(*frame.get_pixel(0, 4), Rgb::from(Color::from_hue(180)));
+    }
+
+    /// Font with a single 2x2 solid glyph, "A".
+    fn block_font() -> BdfFont {
+        BdfFont::parse(
+            "FONT_ASCENT 2\nFONT_DESCENT 0\n\
+             STARTCHAR A\nENCODING 65\nDWIDTH 3 0\nBBX 2 2 0 0\nBITMAP\nC0\nC0\nENDCHAR\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn title_overlay_darkens_band_and_draws_text() {
+        let frame = solid(4, 6, Rgb([100, 100, 100]));
+        let titled = title_overlay(&frame, &block_font(), "A", Rgb([255, 255, 255]), 1.0);
+
+        // Band is the bottom 4 rows (glyph height + 2), text starts at x=1
+        assert_eq!(*titled.get_pixel(0, 1), Rgb([100, 100, 100]));
+        assert_eq!(*titled.get_pixel(0, 2), Rgb([25, 25, 25]));
+        assert_eq!(*titled.get_pixel(1, 3), Rgb([255, 255, 255]));
+        assert_eq!(*titled.get_pixel(2, 4), Rgb([255, 255, 255]));
+        assert_eq!(*titled.get_pixel(3, 4), Rgb([25, 25, 25]));
+    }
+
+    #[test]
+    fn title_overlay_blends_with_opacity() {
+        let frame = solid(4, 6, Rgb([100, 100, 100]));
+        let font = block_font();
+        let white = Rgb([255, 255, 255]);
+
+        assert_eq!(title_overlay(&frame, &font, "A", white, 0.0), frame);
+        let half = title_overlay(&frame, &font, "A", white, 0.5);
+        assert_eq!(*half.get_pixel(0, 2), Rgb([63, 63, 63]));
+        assert_eq!(*half.get_pixel(1, 3), Rgb([178, 178, 178]));
+    }
+
+    #[rstest]
+    #[case(0, 1.0)]
+    #[case(2000, 1.0)]
+    #[case(2500, 0.5)]
+    #[case(3000, 0.0)]
+    #[case(9000, 0.0)]
+    fn test_fade_out_opacity(#[case] elapsed_ms: u64, #[case] expected: f32) {
+        let opacity = fade_out_opacity(
+            Duration::from_millis(elapsed_ms),
+            Duration::from_secs(2),
+            Duration::from_secs(1),
+        );
+        assert_eq!(opacity, expected);
+    }
+
+    #[test]
+    fn color_wheel_rotates_with_phase() {
+        let wheel = ColorWheel::new(9, 9);
+        let rotated = wheel.frame(90.0);
+        assert_eq!(*rotated.get_pixel(8, 4), Rgb::from(Color::from_hue(90)));
+        // A full turn is back where it started
+        assert_eq!(wheel.frame(360.0), wheel.frame(0.0));
+    }
+}
@@ -0,0 +1,383 @@
+//! Raw frames pushed by clients: integrity checks and animation timing.
+//!
+//! A raw RGB frame only has its length checked, so a frame scrambled in
+//! transit but still the right size would be displayed as garbage. Clients
+//! can opt in to a checksum: they append a CRC32 of the frame data and the
+//! server drops frames that don't match.
+//!
+//! Pipelines that render with an alpha channel can send RGBA instead of
+//! RGB; the server composites each pixel over black, since that's what an
+//! unlit LED shows.
+//!
+//! Uploaded animations can give each frame its own delay, the way GIFs do,
+//! instead of a single frame rate.
+//!
+//! Streaming clients that send linear RGB can have the server gamma-correct
+//! and dim each frame through a `ColorLut`, instead of doing it per frame on
+//! the sender.
+//!
+//! A stream of a mostly static scene sends the same frame over and over;
+//! with `--skip-duplicate-frames` the render thread compares each one with
+//! what's shown and skips the draw and swap when nothing changed.
+//!
+//! A frame of the wrong size usually means `--rows`/`--cols` don't match the
+//! panel the client was written for, so size errors name the panel sizes the
+//! frame would have fit.
+//!
+//! ## Rust concepts
+//! - `split_at` to borrow two halves of one slice
+//! - `u32::from_be_bytes` with `try_into` to read a fixed-size integer
+//! - `std::array::from_fn` to build a fixed-size lookup table
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Checksum a client appends to each raw frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Checksum {
+    /// Frames are plain RGB data
+    #[default]
+    None,
+    /// Frames end with a 4-byte big-endian CRC32 (IEEE) of the RGB data
+    Crc32,
+}
+
+impl Checksum {
+    /// Bytes the checksum adds after the frame data.
+    pub fn trailer_len(self) -> usize {
+        match self {
+            Checksum::None => 0,
+            Checksum::Crc32 => 4,
+        }
+    }
+
+    /// Check and strip the trailing checksum, returning the frame data.
+    pub fn verify(self, data: &[u8]) -> Result<&[u8], String> {
+        if data.len() < self.trailer_len() {
+            return Err(format!(
+                "Frame is shorter than its {}-byte checksum",
+                self.trailer_len()
+            ));
+        }
+        let (frame, trailer) = data.split_at(data.len() - self.trailer_len());
+
+        match self {
+            Checksum::None => Ok(frame),
+            Checksum::Crc32 => {
+                let expected = u32::from_be_bytes(trailer.try_into().unwrap());
+                let actual = crc32fast::hash(frame);
+                if actual == expected {
+                    Ok(frame)
+                } else {
+                    Err(format!(
+                        "CRC32 mismatch: frame says {expected:08x}, data is {actual:08x}"
+                    ))
+                }
+            }
+        }
+    }
+}
+
+// ── Pixel formats ────────────────────────────────────────────────────
+
+/// Layout of each pixel in a raw frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PixelFormat {
+    /// 3 bytes: red, green, blue
+    #[default]
+    Rgb,
+    /// 4 bytes: red, green, blue, alpha (0 = transparent)
+    Rgba,
+}
+
+impl PixelFormat {
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Rgb => 3,
+            PixelFormat::Rgba => 4,
+        }
+    }
+
+    /// Name for error messages, e.g. `RGBA`.
+    pub fn label(self) -> &'static str {
+        match self {
+            PixelFormat::Rgb => "RGB",
+            PixelFormat::Rgba => "RGBA",
+        }
+    }
+
+    /// The frame as RGB, compositing RGBA pixels over black (each channel
+    /// scaled by alpha, rounded).
+    pub fn to_rgb(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            PixelFormat::Rgb => data.to_vec(),
+            PixelFormat::Rgba => data
+                .chunks_exact(4)
+                .flat_map(|pixel| {
+                    let alpha = pixel[3] as u16;
+                    [0, 1, 2].map(|i| ((pixel[i] as u16 * alpha + 127) / 255) as u8)
+                })
+                .collect(),
+        }
+    }
+
+    /// `size_hint` for a frame of `len` bytes in this format.
+    pub fn size_hint(self, len: usize) -> String {
+        if !len.is_multiple_of(self.bytes_per_pixel()) {
+            return String::new();
+        }
+        size_hint(len / self.bytes_per_pixel() * 3)
+    }
+}
+
+// ── Color correction ─────────────────────────────────────────────────
+
+/// Lookup table applying gamma and brightness to each color channel.
+///
+/// Building it costs 256 `powf` calls; applying it is one table lookup per
+/// byte, cheap enough to run on every streamed frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColorLut([u8; 256]);
+
+impl ColorLut {
+    /// `out = 255 * (in / 255)^gamma * brightness / 100`, so gamma 1.0 and
+    /// brightness 100 leave values unchanged.
+    pub fn new(gamma: f32, brightness: u8) -> Result<Self, String> {
+        if !(gamma.is_finite() && gamma > 0.0) {
+            return Err(format!("Gamma must be a positive number, got {gamma}"));
+        }
+        if brightness > 100 {
+            return Err(format!("Brightness must be 0-100, got {brightness}"));
+        }
+
+        let scale = brightness as f32 / 100.0;
+        Ok(Self(std::array::from_fn(|i| {
+            ((i as f32 / 255.0).powf(gamma) * scale * 255.0).round() as u8
+        })))
+    }
+
+    /// Correct every byte of an RGB buffer in place.
+    pub fn apply(&self, data: &mut [u8]) {
+        for value in data {
+            *value = self.0[*value as usize];
+        }
+    }
+}
+
+// ── Animation timing ─────────────────────────────────────────────────
+
+/// How long to show each of `count` frames.
+///
+/// With `delays_ms` empty, every frame gets `1000 / fps` ms. Otherwise
+/// there must be exactly one delay per frame.
+pub fn frame_delays(count: usize, delays_ms: &[u32], fps: u32) -> Result<Vec<Duration>, String> {
+    if delays_ms.is_empty() {
+        let delay = Duration::from_millis(1000 / fps.max(1) as u64);
+        return Ok(vec![delay; count]);
+    }
+    if delays_ms.len() != count {
+        return Err(format!(
+            "Expected one delay per frame ({count}), got {}",
+            delays_ms.len()
+        ));
+    }
+    Ok(delays_ms
+        .iter()
+        .map(|&ms| Duration::from_millis(ms as u64))
+        .collect())
+}
+
+// ── Size mismatches ──────────────────────────────────────────────────
+
+/// Most sizes `implied_sizes` returns.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Frame sizes, as `(width, height)`, that `len` bytes of RGB fill exactly.
+/// Only multiples of 8 pixels each way count, as LED panels come in those;
+/// the squarest sizes come first, wider before taller.
+pub fn implied_sizes(len: usize) -> Vec<(u32, u32)> {
+    if !len.is_multiple_of(3) {
+        return Vec::new();
+    }
+    let pixels = len / 3;
+    let mut sizes: Vec<(u32, u32)> = (8..=pixels / 8)
+        .step_by(8)
+        .filter(|&height| pixels.is_multiple_of(height) && (pixels / height).is_multiple_of(8))
+        .filter_map(|height| Some((u32::try_from(pixels / height).ok()?, height as u32)))
+        .collect();
+    sizes.sort_by_key(|&(width, height)| (width.abs_diff(height), height));
+    sizes.truncate(MAX_SUGGESTIONS);
+    sizes
+}
+
+/// Note for a frame size error, e.g. `; that's the size of a 64x64 or
+/// 128x32 frame, so check --cols and --rows`, or empty if `len` bytes
+/// don't fit any panel size.
+pub fn size_hint(len: usize) -> String {
+    let sizes: Vec<String> = implied_sizes(len)
+        .iter()
+        .map(|(width, height)| format!("{width}x{height}"))
+        .collect();
+    if sizes.is_empty() {
+        return String::new();
+    }
+    format!(
+        "; that's the size of a {} frame, so check --cols and --rows",
+        sizes.join(" or ")
+    )
+}
+
+// ── Duplicate frames ─────────────────────────────────────────────────
+
+/// Whether drawing raw frame `next` would leave the panel as it is, because
+/// `shown` (the last frame drawn, before brightness and inversion) has the
+/// same pixels.
+pub fn is_duplicate(shown: &[u8], next: &[u8]) -> bool {
+    shown == next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&[1, 2, 3, 4, 5, 6], true)]
+    #[case(&[1, 2, 3, 4, 5, 7], false)]
+    #[case(&[1, 2, 3], false)]
+    #[case(&[], false)]
+    fn duplicates_need_every_byte_to_match(#[case] next: &[u8], #[case] expected: bool) {
+        assert_eq!(is_duplicate(&[1, 2, 3, 4, 5, 6], next), expected);
+    }
+
+    fn with_crc(frame: &[u8]) -> Vec<u8> {
+        let mut data = frame.to_vec();
+        data.extend_from_slice(&crc32fast::hash(frame).to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn none_passes_data_through() {
+        assert_eq!(Checksum::None.verify(&[1, 2, 3]), Ok(&[1, 2, 3][..]));
+    }
+
+    #[test]
+    fn crc32_strips_a_valid_checksum() {
+        let data = with_crc(&[10, 20, 30]);
+        assert_eq!(Checksum::Crc32.verify(&data), Ok(&[10, 20, 30][..]));
+    }
+
+    #[test]
+    fn crc32_rejects_corrupted_data() {
+        let mut data = with_crc(&[10, 20, 30]);
+        data[1] ^= 0xFF;
+        assert!(Checksum::Crc32.verify(&data).is_err());
+    }
+
+    #[test]
+    fn crc32_rejects_frames_shorter_than_the_checksum() {
+        assert!(Checksum::Crc32.verify(&[1, 2]).is_err());
+    }
+
+    #[rstest]
+    #[case(64 * 64 * 3, vec![(64, 64), (128, 32), (32, 128)])]
+    #[case(64 * 32 * 3, vec![(64, 32), (32, 64), (128, 16)])]
+    #[case(16 * 16 * 3, vec![(16, 16), (32, 8), (8, 32)])]
+    #[case(8 * 8 * 3, vec![(8, 8)])]
+    #[case(10 * 10 * 3, vec![])] // not a multiple of 8 either way
+    #[case(64 * 64 * 3 + 1, vec![])] // not whole pixels
+    #[case(0, vec![])]
+    fn implied_sizes_suggests_panel_sizes(#[case] len: usize, #[case] expected: Vec<(u32, u32)>) {
+        assert_eq!(implied_sizes(len), expected);
+    }
+
+    #[test]
+    fn size_hint_names_the_flags() {
+        assert_eq!(
+            size_hint(16 * 8 * 3),
+            "; that's the size of a 16x8 or 8x16 frame, so check --cols and --rows"
+        );
+        assert_eq!(size_hint(7), "");
+    }
+
+    #[test]
+    fn rgba_is_composited_over_black() {
+        let rgba = [
+            255, 0, 0, 255, // opaque red
+            0, 255, 0, 128, // half-transparent green
+            0, 0, 255, 0, // fully transparent blue
+            200, 100, 50, 51, // 20% opaque orange
+        ];
+        assert_eq!(
+            PixelFormat::Rgba.to_rgb(&rgba),
+            [255, 0, 0, 0, 128, 0, 0, 0, 0, 40, 20, 10]
+        );
+    }
+
+    #[test]
+    fn rgb_is_passed_through() {
+        assert_eq!(PixelFormat::Rgb.to_rgb(&[1, 2, 3]), [1, 2, 3]);
+    }
+
+    #[test]
+    fn size_hint_counts_pixels_in_the_format() {
+        assert_eq!(
+            PixelFormat::Rgba.size_hint(16 * 8 * 4),
+            size_hint(16 * 8 * 3)
+        );
+        assert_eq!(PixelFormat::Rgba.size_hint(16 * 8 * 4 + 2), "");
+    }
+
+    #[test]
+    fn identity_lut_leaves_data_unchanged() {
+        let lut = ColorLut::new(1.0, 100).unwrap();
+        let mut data: Vec<u8> = (0..=255).collect();
+        lut.apply(&mut data);
+        assert_eq!(data, (0..=255).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn lut_applies_gamma_and_brightness() {
+        let mut data = vec![0, 128, 255];
+        ColorLut::new(2.2, 100).unwrap().apply(&mut data);
+        assert_eq!(data, vec![0, 56, 255]);
+
+        let mut data = vec![0, 255];
+        ColorLut::new(1.0, 50).unwrap().apply(&mut data);
+        assert_eq!(data, vec![0, 128]);
+    }
+
+    #[rstest]
+    #[case(0.0, 100)]
+    #[case(-1.0, 100)]
+    #[case(f32::NAN, 100)]
+    #[case(2.2, 101)]
+    fn lut_rejects_invalid_settings(#[case] gamma: f32, #[case] brightness: u8) {
+        assert!(ColorLut::new(gamma, brightness).is_err());
+    }
+
+    #[test]
+    fn frame_delays_default_to_fps() {
+        assert_eq!(
+            frame_delays(3, &[], 20),
+            Ok(vec![Duration::from_millis(50); 3])
+        );
+    }
+
+    #[test]
+    fn frame_delays_use_per_frame_values() {
+        assert_eq!(
+            frame_delays(2, &[1000, 40], 20),
+            Ok(vec![Duration::from_millis(1000), Duration::from_millis(40)])
+        );
+        assert!(frame_delays(3, &[1000, 40], 20).is_err());
+    }
+
+    #[test]
+    fn crc32_matches_the_standard_check_value() {
+        assert_eq!(crc32fast::hash(b"123456789"), 0xCBF4_3926);
+    }
+}
@@ -0,0 +1,310 @@
+//! Fitting images onto the panel.
+//!
+//! Source images rarely match the panel's aspect ratio. `FitMode` picks
+//! between stretching (the original behavior), letterboxing, and cropping,
+//! or centering small images (pixel art) at their native size.
+//! Streamed frames from clients with a different resolution are rescaled
+//! with `resize_raw_frame`, and `rotate_image` turns an image by any angle.
+//! These helpers are pure image transforms so they can be tested without the
+//! `hardware` feature.
+//!
+//! ## Rust concepts
+//! - `#[derive(Default)]` with `#[default]` on an enum variant
+//! - Integer/float conversions when computing scaled dimensions
+
+use image::imageops::{self, FilterType};
+use image::{Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+
+/// How an image is scaled to the panel when the aspect ratios differ.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FitMode {
+    /// Resize to exactly the panel size, distorting the aspect ratio
+    #[default]
+    Stretch,
+    /// Scale to fit inside the panel and center it, leaving bars in the
+    /// background color (black by default)
+    Contain,
+    /// Scale to fill the panel and center-crop whatever overflows
+    Cover,
+    /// Like `Contain`, but never enlarge: images smaller than the panel are
+    /// centered at their native size, keeping pixel art crisp
+    Center,
+}
+
+/// Resampling filter for rescaling streamed frames.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScaleFilter {
+    /// Blocky but cheapest; keeps pixel art crisp
+    #[default]
+    Nearest,
+    /// Smoother, for photos and video
+    Bilinear,
+}
+
+impl From<ScaleFilter> for FilterType {
+    fn from(filter: ScaleFilter) -> Self {
+        match filter {
+            ScaleFilter::Nearest => FilterType::Nearest,
+            ScaleFilter::Bilinear => FilterType::Triangle,
+        }
+    }
+}
+
+/// Scaled size of a `src_w`x`src_h` image fitted into `dst_w`x`dst_h`.
+///
+/// For `Contain` the result fits inside the destination; for `Cover` it
+/// covers it completely. `Center` fits inside too, but is never larger than
+/// the source. `Stretch` is always the destination size.
+pub fn fitted_size(src_w: u32, src_h: u32, dst_w: u32, dst_h: u32, mode: FitMode) -> (u32, u32) {
+    if src_w == 0 || src_h == 0 {
+        return (dst_w, dst_h);
+    }
+
+    let scale_x = dst_w as f32 / src_w as f32;
+    let scale_y = dst_h as f32 / src_h as f32;
+    let scale = match mode {
+        FitMode::Stretch => return (dst_w, dst_h),
+        FitMode::Contain => scale_x.min(scale_y),
+        FitMode::Cover => scale_x.max(scale_y),
+        FitMode::Center => scale_x.min(scale_y).min(1.0),
+    };
+
+    (
+        ((src_w as f32 * scale).round() as u32).max(1),
+        ((src_h as f32 * scale).round() as u32).max(1),
+    )
+}
+
+/// Fit `img` into a `width`x`height` frame using `mode`. `Contain` and
+/// `Center` fill the uncovered area with `background`.
+pub fn fit_image(
+    img: &RgbImage,
+    width: u32,
+    height: u32,
+    mode: FitMode,
+    background: Rgb<u8>,
+) -> RgbImage {
+    let (src_w, src_h) = img.dimensions();
+    if (src_w, src_h) == (width, height) {
+        return img.clone();
+    }
+
+    let (fit_w, fit_h) = fitted_size(src_w, src_h, width, height, mode);
+    let scaled = if (fit_w, fit_h) == (src_w, src_h) {
+        img.clone()
+    } else {
+        imageops::resize(img, fit_w, fit_h, FilterType::Lanczos3)
+    };
+
+    match mode {
+        FitMode::Stretch => scaled,
+        FitMode::Contain | FitMode::Center => {
+            let mut frame = RgbImage::from_pixel(width, height, background);
+            let x = (width - fit_w) / 2;
+            let y = (height - fit_h) / 2;
+            imageops::replace(&mut frame, &scaled, x as i64, y as i64);
+            frame
+        }
+        FitMode::Cover => {
+            let x = (fit_w - width) / 2;
+            let y = (fit_h - height) / 2;
+            imageops::crop_imm(&scaled, x, y, width, height).to_image()
+        }
+    }
+}
+
+/// Rotate `img` clockwise by `degrees` about its center, keeping its size.
+///
+/// Each output pixel is sampled bilinearly from the source, so edges stay
+/// smooth at any angle (and 0 or 90 degree steps are still pixel-exact).
+/// Corners the rotated image no longer covers are filled with `background`.
+pub fn rotate_image(img: &RgbImage, degrees: f32, background: Rgb<u8>) -> RgbImage {
+    let (width, height) = img.dimensions();
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let cx = (width as f32 - 1.0) / 2.0;
+    let cy = (height as f32 - 1.0) / 2.0;
+
+    // Source pixel, or the background outside the image
+    let source = |x: f32, y: f32| -> [f32; 3] {
+        let pixel = if x < 0.0 || y < 0.0 || x >= width as f32 || y >= height as f32 {
+            background
+        } else {
+            *img.get_pixel(x as u32, y as u32)
+        };
+        pixel.0.map(f32::from)
+    };
+
+    RgbImage::from_fn(width, height, |x, y| {
+        // Rotate back to find where this output pixel comes from
+        let dx = x as f32 - cx;
+        let dy = y as f32 - cy;
+        let sx = cx + dx * cos + dy * sin;
+        let sy = cy - dx * sin + dy * cos;
+
+        let (x0, y0) = (sx.floor(), sy.floor());
+        let (fx, fy) = (sx - x0, sy - y0);
+        let top_left = source(x0, y0);
+        let top_right = source(x0 + 1.0, y0);
+        let bottom_left = source(x0, y0 + 1.0);
+        let bottom_right = source(x0 + 1.0, y0 + 1.0);
+        Rgb(std::array::from_fn(|c| {
+            let top = top_left[c] + (top_right[c] - top_left[c]) * fx;
+            let bottom = bottom_left[c] + (bottom_right[c] - bottom_left[c]) * fx;
+            (top + (bottom - top) * fy).round() as u8
+        }))
+    })
+}
+
+/// Rescale a raw RGB24 frame of `src_w`x`src_h` to `dst_w`x`dst_h`.
+///
+/// Returns the bytes unchanged when the sizes already match, or an error
+/// if `data` isn't exactly `src_w * src_h * 3` bytes.
+pub fn resize_raw_frame(
+    data: Vec<u8>,
+    (src_w, src_h): (u32, u32),
+    (dst_w, dst_h): (u32, u32),
+    filter: ScaleFilter,
+) -> Result<Vec<u8>, String> {
+    let expected = src_w as usize * src_h as usize * 3;
+    if data.len() != expected {
+        return Err(format!("expected {expected} bytes, got {}", data.len()));
+    }
+    if (src_w, src_h) == (dst_w, dst_h) {
+        return Ok(data);
+    }
+
+    let img = RgbImage::from_raw(src_w, src_h, data)
+        .ok_or_else(|| "frame buffer too small".to_string())?;
+    Ok(imageops::resize(&img, dst_w, dst_h, filter.into()).into_raw())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(128, 64, FitMode::Stretch, (64, 64))]
+    #[case(128, 64, FitMode::Contain, (64, 32))]
+    #[case(128, 64, FitMode::Cover, (128, 64))]
+    #[case(32, 64, FitMode::Contain, (32, 64))]
+    #[case(32, 64, FitMode::Cover, (64, 128))]
+    #[case(16, 16, FitMode::Contain, (64, 64))]
+    #[case(16, 16, FitMode::Center, (16, 16))]
+    #[case(128, 64, FitMode::Center, (64, 32))]
+    fn test_fitted_size(
+        #[case] src_w: u32,
+        #[case] src_h: u32,
+        #[case] mode: FitMode,
+        #[case] expected: (u32, u32),
+    ) {
+        assert_eq!(fitted_size(src_w, src_h, 64, 64, mode), expected);
+    }
+
+    #[rstest]
+    #[case(FitMode::Stretch)]
+    #[case(FitMode::Contain)]
+    #[case(FitMode::Cover)]
+    #[case(FitMode::Center)]
+    fn fit_image_always_returns_panel_size(#[case] mode: FitMode) {
+        let img = RgbImage::new(100, 40);
+        assert_eq!(fit_image(&img, 64, 32, mode, BLACK).dimensions(), (64, 32));
+    }
+
+    const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+
+    #[test]
+    fn contain_letterboxes_with_black_bars() {
+        let img = RgbImage::from_pixel(8, 4, Rgb([255, 255, 255]));
+        let frame = fit_image(&img, 8, 8, FitMode::Contain, BLACK);
+        assert_eq!(*frame.get_pixel(4, 0), Rgb([0, 0, 0]));
+        assert_eq!(*frame.get_pixel(4, 4), Rgb([255, 255, 255]));
+        assert_eq!(*frame.get_pixel(4, 7), Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn contain_fills_bars_with_background() {
+        let img = RgbImage::from_pixel(4, 8, Rgb([255, 255, 255]));
+        let frame = fit_image(&img, 8, 8, FitMode::Contain, Rgb([10, 0, 20]));
+        assert_eq!(*frame.get_pixel(0, 4), Rgb([10, 0, 20]));
+        assert_eq!(*frame.get_pixel(4, 4), Rgb([255, 255, 255]));
+        assert_eq!(*frame.get_pixel(7, 4), Rgb([10, 0, 20]));
+    }
+
+    #[test]
+    fn center_keeps_small_images_pixel_exact() {
+        let img = RgbImage::from_fn(2, 2, |x, y| Rgb([x as u8 * 255, y as u8 * 255, 0]));
+        let frame = fit_image(&img, 8, 8, FitMode::Center, BLACK);
+        assert_eq!(*frame.get_pixel(2, 2), BLACK);
+        assert_eq!(*frame.get_pixel(3, 3), Rgb([0, 0, 0]));
+        assert_eq!(*frame.get_pixel(4, 3), Rgb([255, 0, 0]));
+        assert_eq!(*frame.get_pixel(3, 4), Rgb([0, 255, 0]));
+        assert_eq!(*frame.get_pixel(4, 4), Rgb([255, 255, 0]));
+        assert_eq!(*frame.get_pixel(5, 5), BLACK);
+    }
+
+    #[test]
+    fn cover_crops_to_center() {
+        // Left half red, right half blue; cropping a square keeps the middle
+        let img = RgbImage::from_fn(8, 2, |x, _| {
+            if x < 4 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        });
+        let frame = fit_image(&img, 2, 2, FitMode::Cover, BLACK);
+        assert_eq!(frame.dimensions(), (2, 2));
+        assert!(frame.get_pixel(0, 0)[0] > frame.get_pixel(0, 0)[2]);
+        assert!(frame.get_pixel(1, 0)[2] > frame.get_pixel(1, 0)[0]);
+    }
+
+    #[test]
+    fn rotate_by_zero_is_unchanged() {
+        let img = RgbImage::from_fn(4, 3, |x, y| Rgb([x as u8 * 60, y as u8 * 80, 7]));
+        assert_eq!(rotate_image(&img, 0.0, BLACK), img);
+    }
+
+    #[test]
+    fn rotate_quarter_turn_is_clockwise() {
+        let mut img = RgbImage::new(3, 3);
+        img.put_pixel(0, 0, Rgb([255, 0, 0]));
+        let rotated = rotate_image(&img, 90.0, BLACK);
+        assert_eq!(*rotated.get_pixel(2, 0), Rgb([255, 0, 0]));
+        assert_eq!(*rotated.get_pixel(0, 0), BLACK);
+    }
+
+    #[test]
+    fn rotate_fills_uncovered_corners_with_background() {
+        let img = RgbImage::from_pixel(9, 9, Rgb([255, 255, 255]));
+        let rotated = rotate_image(&img, 45.0, Rgb([0, 0, 255]));
+        assert_eq!(*rotated.get_pixel(0, 0), Rgb([0, 0, 255]));
+        assert_eq!(*rotated.get_pixel(4, 4), Rgb([255, 255, 255]));
+    }
+
+    #[test]
+    fn resize_raw_frame_scales_to_panel() {
+        // 2x1 red/blue frame doubled to 4x2 with nearest-neighbor
+        let data = vec![255, 0, 0, 0, 0, 255];
+        let out = resize_raw_frame(data, (2, 1), (4, 2), ScaleFilter::Nearest).unwrap();
+        let frame = RgbImage::from_raw(4, 2, out).unwrap();
+        assert_eq!(*frame.get_pixel(1, 1), Rgb([255, 0, 0]));
+        assert_eq!(*frame.get_pixel(2, 0), Rgb([0, 0, 255]));
+    }
+
+    #[test]
+    fn resize_raw_frame_passes_through_matching_size() {
+        let data = vec![1, 2, 3, 4, 5, 6];
+        let out = resize_raw_frame(data.clone(), (1, 2), (1, 2), ScaleFilter::Bilinear).unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn resize_raw_frame_rejects_wrong_length() {
+        assert!(resize_raw_frame(vec![0; 5], (1, 2), (4, 4), ScaleFilter::Nearest).is_err());
+    }
+}
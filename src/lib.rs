@@ -6,14 +6,39 @@
 //! - Color helper functions
 //! - Panel configuration
 //!
-//! It also re-exports the server, render, and media modules used by
-//! the main binary (HTTP API server).
-
+//! It also re-exports the api, client, server, render, media, effects, draw,
+//! layout, bdf, ansi, pixel_map, pacer, playback, presets, frame, remote,
+//! schedule, light, sysinfo, settings, sim, sprites, timer, diagnostics, and
+//! queue modules used by the main binary (HTTP API server).
+
+pub mod ansi;
+pub mod api;
+pub mod bdf;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod diagnostics;
+pub mod draw;
+pub mod effects;
+pub mod frame;
+pub mod layout;
+pub mod light;
 pub mod media;
+pub mod pacer;
+pub mod pixel_map;
+pub mod playback;
+pub mod presets;
+pub mod queue;
+pub mod remote;
 #[cfg(feature = "hardware")]
 pub mod render;
+pub mod schedule;
 #[cfg(feature = "hardware")]
 pub mod server;
+pub mod settings;
+pub mod sim;
+pub mod sprites;
+pub mod sysinfo;
+pub mod timer;
 
 #[cfg(feature = "hardware")]
 use rpi_led_matrix::{LedMatrix, LedMatrixOptions, LedRuntimeOptions};
@@ -49,6 +74,14 @@ impl PanelConfig {
     pub fn frame_byte_count(&self) -> usize {
         (self.rows * self.cols * 3) as usize
     }
+
+    /// The area left inside a border `pixels` wide on every side, or `None`
+    /// if the border covers the whole panel.
+    pub fn inset(self, pixels: u32) -> Option<Self> {
+        let rows = self.rows.checked_sub(pixels.checked_mul(2)?)?;
+        let cols = self.cols.checked_sub(pixels * 2)?;
+        (rows > 0 && cols > 0).then_some(Self { rows, cols })
+    }
 }
 
 impl Default for PanelConfig {
@@ -62,8 +95,9 @@ impl Default for PanelConfig {
 /// Our own color type, decoupled from the hardware crate.
 ///
 /// This lets us test color logic on macOS without needing `rpi-led-matrix`.
-/// At the hardware boundary, we convert via `Into<LedColor>`.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// At the hardware boundary, we convert via `Into<LedColor>`. The default
+/// is black.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -71,10 +105,34 @@ pub struct Color {
 }
 
 impl Color {
-    pub fn new(r: u8, g: u8, b: u8) -> Self {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
         Self { r, g, b }
     }
 
+    /// Parse a hex color: `#rrggbb`, `rrggbb`, or the short `#rgb` / `rgb`,
+    /// where each digit is doubled (`#f80` is `#ff8800`). Either case.
+    pub fn from_hex(s: &str) -> Result<Self, ColorParseError> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        if let Some(c) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(ColorParseError::InvalidDigit(c));
+        }
+        // All hex digits, so byte slicing and parsing can't fail
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).expect("checked hex digits");
+        match hex.len() {
+            6 => Ok(Self::new(
+                channel(&hex[0..2]),
+                channel(&hex[2..4]),
+                channel(&hex[4..6]),
+            )),
+            3 => Ok(Self::new(
+                channel(&hex[0..1]) * 17,
+                channel(&hex[1..2]) * 17,
+                channel(&hex[2..3]) * 17,
+            )),
+            len => Err(ColorParseError::InvalidLength(len)),
+        }
+    }
+
     /// Create a color from a hue value (0-360), with full saturation and brightness.
     /// Useful for rainbow effects.
     ///
@@ -98,6 +156,23 @@ impl Color {
         }
     }
 
+    /// Blend from this color towards `other`: `t` = 0.0 gives `self`, 1.0
+    /// gives `other`. `t` is clamped to that range.
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Self {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+        }
+    }
+
+    /// The opposite color: each channel becomes `255 - channel`.
+    pub fn invert(self) -> Self {
+        Self::new(255 - self.r, 255 - self.g, 255 - self.b)
+    }
+
     /// Apply brightness scaling (0-100) to this color.
     pub fn apply_brightness(self, brightness: u8) -> Self {
         if brightness >= 100 {
@@ -111,6 +186,192 @@ impl Color {
     }
 }
 
+/// Web color names accepted wherever a color is parsed from a string, with
+/// their CSS values (so `green` is the dimmer `0,128,0`; `lime` is full green).
+const NAMED_COLORS: &[(&str, Color)] = &[
+    ("black", Color::new(0, 0, 0)),
+    ("white", Color::new(255, 255, 255)),
+    ("gray", Color::new(128, 128, 128)),
+    ("grey", Color::new(128, 128, 128)),
+    ("silver", Color::new(192, 192, 192)),
+    ("red", Color::new(255, 0, 0)),
+    ("maroon", Color::new(128, 0, 0)),
+    ("lime", Color::new(0, 255, 0)),
+    ("green", Color::new(0, 128, 0)),
+    ("blue", Color::new(0, 0, 255)),
+    ("navy", Color::new(0, 0, 128)),
+    ("yellow", Color::new(255, 255, 0)),
+    ("olive", Color::new(128, 128, 0)),
+    ("cyan", Color::new(0, 255, 255)),
+    ("aqua", Color::new(0, 255, 255)),
+    ("teal", Color::new(0, 128, 128)),
+    ("magenta", Color::new(255, 0, 255)),
+    ("fuchsia", Color::new(255, 0, 255)),
+    ("purple", Color::new(128, 0, 128)),
+    ("orange", Color::new(255, 165, 0)),
+    ("gold", Color::new(255, 215, 0)),
+    ("pink", Color::new(255, 192, 203)),
+    ("hotpink", Color::new(255, 105, 180)),
+    ("brown", Color::new(165, 42, 42)),
+    ("coral", Color::new(255, 127, 80)),
+    ("salmon", Color::new(250, 128, 114)),
+    ("violet", Color::new(238, 130, 238)),
+    ("indigo", Color::new(75, 0, 130)),
+    ("turquoise", Color::new(64, 224, 208)),
+    ("skyblue", Color::new(135, 206, 235)),
+];
+
+/// Why `Color::from_hex` rejected a string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ColorParseError {
+    /// Not 3 or 6 digits long
+    InvalidLength(usize),
+    /// A character that isn't a hex digit
+    InvalidDigit(char),
+}
+
+impl std::fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::InvalidLength(len) => write!(f, "expected 3 or 6 hex digits, got {len}"),
+            Self::InvalidDigit(c) => write!(f, "{c:?} is not a hex digit"),
+        }
+    }
+}
+
+impl std::error::Error for ColorParseError {}
+
+/// Parse a color written as any of:
+/// - `R,G,B` (each 0-255), e.g. `255,128,0`, as used on the command line
+/// - `rgb(R,G,B)`, e.g. `rgb(255, 128, 0)`
+/// - hex, `#ff8000` or the short `#f80`
+/// - a web color name, e.g. `orange` (case-insensitive)
+impl std::str::FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if trimmed.starts_with('#') {
+            // `from_hex` takes off the one `#` itself
+            return Self::from_hex(trimmed).map_err(|e| format!("Invalid hex color {s:?}: {e}"));
+        }
+        if let Some((_, color)) = NAMED_COLORS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(trimmed))
+        {
+            return Ok(*color);
+        }
+
+        let channels = trimmed
+            .strip_prefix("rgb(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .unwrap_or(trimmed);
+        let channels: Vec<&str> = channels.split(',').collect();
+        let [r, g, b] = channels[..] else {
+            return Err(format!(
+                "Expected R,G,B, rgb(R,G,B), #RRGGBB, or a color name, got {s:?}"
+            ));
+        };
+        let channel = |c: &str| {
+            c.trim()
+                .parse::<u8>()
+                .map_err(|_| format!("Invalid color channel {c:?} in {s:?}"))
+        };
+        Ok(Self::new(channel(r)?, channel(g)?, channel(b)?))
+    }
+}
+
+/// In JSON a color is either a `[r, g, b]` array or any string `FromStr`
+/// accepts. It's always written back as an array.
+impl<'de> serde::Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ColorVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ColorVisitor {
+            type Value = Color;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("an [r, g, b] array, \"#rrggbb\", \"rgb(r, g, b)\", or a color name")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Color, E> {
+                s.parse().map_err(E::custom)
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, seq: A) -> Result<Color, A::Error> {
+                let (r, g, b) = <(u8, u8, u8) as serde::Deserialize>::deserialize(
+                    serde::de::value::SeqAccessDeserializer::new(seq),
+                )?;
+                Ok(Color::new(r, g, b))
+            }
+        }
+
+        deserializer.deserialize_any(ColorVisitor)
+    }
+}
+
+impl serde::Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&(self.r, self.g, self.b), serializer)
+    }
+}
+
+impl utoipa::PartialSchema for Color {
+    fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
+        use utoipa::openapi::schema::{ArrayBuilder, ObjectBuilder, OneOfBuilder, Type};
+
+        let channel = ObjectBuilder::new()
+            .schema_type(Type::Integer)
+            .minimum(Some(0))
+            .maximum(Some(255));
+        OneOfBuilder::new()
+            .item(
+                ArrayBuilder::new()
+                    .items(channel)
+                    .min_items(Some(3))
+                    .max_items(Some(3)),
+            )
+            .item(ObjectBuilder::new().schema_type(Type::String))
+            .description(Some(
+                "[r, g, b] (each 0-255), or a string: \"#ff8000\", \"#f80\", \"rgb(255,128,0)\", \"255,128,0\", or a web color name such as \"orange\"",
+            ))
+            .into()
+    }
+}
+
+impl utoipa::ToSchema for Color {}
+
+/// Order a panel's wiring takes color channels in. Most panels are RGB;
+/// on one wired BGR, red comes out blue until this is set to match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorOrder {
+    #[default]
+    Rgb,
+    Rbg,
+    Grb,
+    Gbr,
+    Brg,
+    Bgr,
+}
+
+impl Color {
+    /// The channels to send a panel wired in `order` so it shows this
+    /// color: the first channel sent is the one `order` names first, and
+    /// so on.
+    pub fn reorder(self, order: ColorOrder) -> Self {
+        let Self { r, g, b } = self;
+        let (first, second, third) = match order {
+            ColorOrder::Rgb => (r, g, b),
+            ColorOrder::Rbg => (r, b, g),
+            ColorOrder::Grb => (g, r, b),
+            ColorOrder::Gbr => (g, b, r),
+            ColorOrder::Brg => (b, r, g),
+            ColorOrder::Bgr => (b, g, r),
+        };
+        Self::new(first, second, third)
+    }
+}
+
 /// Convert our Color to the hardware crate's LedColor at the boundary.
 #[cfg(feature = "hardware")]
 impl From<Color> for rpi_led_matrix::LedColor {
@@ -123,6 +384,13 @@ impl From<Color> for rpi_led_matrix::LedColor {
     }
 }
 
+/// Convert our Color to an `image` pixel for in-memory frames.
+impl From<Color> for image::Rgb<u8> {
+    fn from(c: Color) -> Self {
+        image::Rgb([c.r, c.g, c.b])
+    }
+}
+
 // ── Backward-compatible color helpers ──────────────────────────────
 // These wrap the new Color type so existing code still compiles.
 
@@ -136,6 +404,13 @@ pub fn color_from_hue(hue: u16) -> Color {
     Color::from_hue(hue)
 }
 
+/// A requested brightness held to `--max-brightness` (`max`), and to 100.
+/// Applies to software and hardware brightness alike, so nothing the API
+/// asks for can draw more current than the power supply was sized for.
+pub fn limit_brightness(value: u8, max: u8) -> u8 {
+    value.min(max).min(100)
+}
+
 // ── Matrix initialization ──────────────────────────────────────────
 
 /// Create a matrix configured for our hardware:
@@ -147,17 +422,40 @@ pub fn color_from_hue(hue: u16) -> Color {
 /// The caller uses `?` to propagate errors upward.
 #[cfg(feature = "hardware")]
 pub fn create_matrix(panel: PanelConfig) -> Result<LedMatrix, Box<dyn std::error::Error>> {
+    create_matrix_with_options(panel, 100, DEFAULT_HARDWARE_MAPPING)
+}
+
+/// GPIO mapping of the Adafruit RGB Matrix Bonnet/HAT we use by default.
+pub const DEFAULT_HARDWARE_MAPPING: &str = "adafruit-hat";
+
+/// PWM bits of color depth the matrix library runs with. Matched to the
+/// standalone `video_player.rs`, which has stable output.
+pub const PWM_BITS: u8 = 8;
+
+/// GPIO slowdown the matrix library runs with; a Pi Zero 2 W needs 2.
+pub const GPIO_SLOWDOWN: u32 = 2;
+
+/// Like `create_matrix`, but with the library's hardware brightness (1-100)
+/// and GPIO mapping (e.g. `adafruit-hat`, `regular`) set. Brightness scales
+/// PWM duty for the whole panel rather than each pixel.
+#[cfg(feature = "hardware")]
+pub fn create_matrix_with_options(
+    panel: PanelConfig,
+    brightness: u8,
+    hardware_mapping: &str,
+) -> Result<LedMatrix, Box<dyn std::error::Error>> {
     let mut options = LedMatrixOptions::new();
     options.set_rows(panel.rows);
     options.set_cols(panel.cols);
-    options.set_hardware_mapping("adafruit-hat");
+    options.set_hardware_mapping(hardware_mapping);
+    options.set_brightness(brightness.clamp(1, 100))?;
 
     // PWM settings — matched to standalone video_player.rs which has stable output
-    options.set_pwm_bits(8)?; // Full 8-bit color depth
+    options.set_pwm_bits(PWM_BITS)?; // Full 8-bit color depth
     options.set_pwm_lsb_nanoseconds(130); // Stable timing (~143Hz refresh)
 
     let mut rt_options = LedRuntimeOptions::new();
-    rt_options.set_gpio_slowdown(2); // Pi Zero 2 W requires slowdown=2
+    rt_options.set_gpio_slowdown(GPIO_SLOWDOWN);
 
     // LedMatrix::new returns Result, so we can use ? directly
     // to propagate any errors upward.
@@ -201,6 +499,7 @@ pub fn is_running(running: &AtomicBool) -> bool {
 mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
     use rstest::rstest;
 
     // ── PanelConfig tests ──────────────────────────────────────────
@@ -229,6 +528,15 @@ mod tests {
         assert_eq!(PanelConfig::new(rows, cols).pixel_count(), expected);
     }
 
+    #[rstest]
+    #[case(0, Some(PanelConfig::new(32, 64)))]
+    #[case(2, Some(PanelConfig::new(28, 60)))]
+    #[case(16, None)] // nothing left of the 32 rows
+    #[case(u32::MAX, None)]
+    fn test_inset(#[case] pixels: u32, #[case] expected: Option<PanelConfig>) {
+        assert_eq!(PanelConfig::new(32, 64).inset(pixels), expected);
+    }
+
     // ── Color tests ────────────────────────────────────────────────
 
     #[test]
@@ -257,6 +565,30 @@ mod tests {
         assert_eq!(Color::from_hue(90), Color::from_hue(450));
     }
 
+    #[rstest]
+    #[case(90, 60, 60)]
+    #[case(40, 60, 40)]
+    #[case(255, 100, 100)]
+    #[case(90, 0, 0)]
+    fn limit_brightness_clamps_to_the_max(
+        #[case] value: u8,
+        #[case] max: u8,
+        #[case] expected: u8,
+    ) {
+        assert_eq!(limit_brightness(value, max), expected);
+    }
+
+    #[rstest]
+    #[case(ColorOrder::Rgb, Color::new(10, 20, 30))]
+    #[case(ColorOrder::Rbg, Color::new(10, 30, 20))]
+    #[case(ColorOrder::Grb, Color::new(20, 10, 30))]
+    #[case(ColorOrder::Gbr, Color::new(20, 30, 10))]
+    #[case(ColorOrder::Brg, Color::new(30, 10, 20))]
+    #[case(ColorOrder::Bgr, Color::new(30, 20, 10))]
+    fn reorder_sends_channels_in_wiring_order(#[case] order: ColorOrder, #[case] sent: Color) {
+        assert_eq!(Color::new(10, 20, 30).reorder(order), sent);
+    }
+
     #[test]
     fn apply_brightness_100_is_identity() {
         let c = Color::new(100, 200, 50);
@@ -275,6 +607,82 @@ mod tests {
         assert_eq!(c.apply_brightness(0), Color::new(0, 0, 0));
     }
 
+    #[rstest]
+    #[case("255,128,0", Ok(Color::new(255, 128, 0)))]
+    #[case(" 1, 2 ,3", Ok(Color::new(1, 2, 3)))]
+    #[case("rgb(255, 128, 0)", Ok(Color::new(255, 128, 0)))]
+    #[case("#FF8000", Ok(Color::new(255, 128, 0)))]
+    #[case("#f80", Ok(Color::new(255, 136, 0)))]
+    #[case("red", Ok(Color::new(255, 0, 0)))]
+    #[case("SkyBlue", Ok(Color::new(135, 206, 235)))]
+    fn test_color_from_str(#[case] s: &str, #[case] expected: Result<Color, String>) {
+        assert_eq!(s.parse::<Color>(), expected);
+    }
+
+    #[rstest]
+    #[case("255,128")]
+    #[case("255,128,0,0")]
+    #[case("256,0,0")]
+    #[case("redd")]
+    #[case("#ff80")]
+    #[case("#gg0000")]
+    #[case("##ff8800")]
+    #[case("#ü0")]
+    #[case("rgb(255,0)")]
+    fn test_color_from_str_rejects_invalid(#[case] s: &str) {
+        assert!(s.parse::<Color>().is_err());
+    }
+
+    #[rstest]
+    #[case("#ff8800", Color::new(255, 136, 0))]
+    #[case("FF8800", Color::new(255, 136, 0))]
+    #[case("#aBcDeF", Color::new(171, 205, 239))]
+    #[case("#f80", Color::new(255, 136, 0))]
+    #[case("FFF", Color::new(255, 255, 255))]
+    #[case("#000000", Color::new(0, 0, 0))]
+    fn color_from_hex(#[case] s: &str, #[case] expected: Color) {
+        assert_eq!(Color::from_hex(s), Ok(expected));
+    }
+
+    #[rstest]
+    #[case("", ColorParseError::InvalidLength(0))]
+    #[case("#", ColorParseError::InvalidLength(0))]
+    #[case("#ff80", ColorParseError::InvalidLength(4))]
+    #[case("#ff88000", ColorParseError::InvalidLength(7))]
+    #[case("#gg0000", ColorParseError::InvalidDigit('g'))]
+    #[case("##ff8800", ColorParseError::InvalidDigit('#'))]
+    #[case("#ü0", ColorParseError::InvalidDigit('ü'))]
+    fn color_from_hex_rejects_malformed_input(#[case] s: &str, #[case] expected: ColorParseError) {
+        assert_eq!(Color::from_hex(s), Err(expected));
+    }
+
+    #[rstest]
+    #[case("[255, 128, 0]")]
+    #[case("\"#ff8000\"")]
+    #[case("\"rgb(255,128,0)\"")]
+    fn color_deserializes_from_arrays_and_strings(#[case] json: &str) {
+        let color: Color = serde_json::from_str(json).unwrap();
+        assert_eq!(color, Color::new(255, 128, 0));
+        // Written back in the original array form
+        assert_eq!(serde_json::to_string(&color).unwrap(), "[255,128,0]");
+    }
+
+    #[rstest]
+    #[case("[255, 128]")]
+    #[case("[256, 0, 0]")]
+    #[case("[255, 128, 0, 0]")]
+    #[case("\"nope\"")]
+    #[case("42")]
+    fn color_rejects_invalid_json(#[case] json: &str) {
+        assert!(serde_json::from_str::<Color>(json).is_err());
+    }
+
+    #[test]
+    fn invert_flips_each_channel() {
+        assert_eq!(Color::new(255, 0, 55).invert(), Color::new(0, 255, 200));
+        assert_eq!(Color::new(1, 2, 3).invert().invert(), Color::new(1, 2, 3));
+    }
+
     #[test]
     fn apply_brightness_50_halves() {
         let c = Color::new(200, 100, 50);
@@ -282,6 +690,80 @@ mod tests {
         assert_eq!(dimmed, Color::new(100, 50, 25));
     }
 
+    #[rstest]
+    #[case(0.0, Color::new(0, 100, 200))]
+    #[case(0.5, Color::new(128, 150, 100))]
+    #[case(1.0, Color::new(255, 200, 0))]
+    #[case(-1.0, Color::new(0, 100, 200))]
+    #[case(2.0, Color::new(255, 200, 0))]
+    fn test_color_lerp(#[case] t: f32, #[case] expected: Color) {
+        let from = Color::new(0, 100, 200);
+        let to = Color::new(255, 200, 0);
+        assert_eq!(from.lerp(to, t), expected);
+    }
+
+    // ── Color property tests ───────────────────────────────────────
+
+    /// Largest change in any channel between adjacent whole-degree hues:
+    /// each 60° sector ramps one channel through 255 levels (~4.25 per
+    /// degree), and truncation can add one more at a sector boundary.
+    const MAX_HUE_STEP: u8 = 5;
+
+    fn any_color() -> impl Strategy<Value = Color> {
+        any::<(u8, u8, u8)>().prop_map(|(r, g, b)| Color::new(r, g, b))
+    }
+
+    fn channels(c: Color) -> [u8; 3] {
+        [c.r, c.g, c.b]
+    }
+
+    proptest! {
+        #[test]
+        fn apply_brightness_100_or_more_is_identity(c in any_color(), brightness in 100u8..) {
+            prop_assert_eq!(c.apply_brightness(brightness), c);
+        }
+
+        #[test]
+        fn apply_brightness_is_monotonic(c in any_color(), low in 0u8..=100, high in 0u8..=100) {
+            let (low, high) = (low.min(high), low.max(high));
+            let (dim, bright) = (c.apply_brightness(low), c.apply_brightness(high));
+            for (d, b) in channels(dim).into_iter().zip(channels(bright)) {
+                prop_assert!(d <= b, "{:?} at {} is brighter than at {}", c, low, high);
+            }
+        }
+
+        #[test]
+        fn apply_brightness_never_brightens(c in any_color(), brightness in any::<u8>()) {
+            let dimmed = c.apply_brightness(brightness);
+            for (d, original) in channels(dimmed).into_iter().zip(channels(c)) {
+                prop_assert!(d <= original);
+            }
+        }
+
+        #[test]
+        fn from_hue_is_continuous(hue in 0u16..360) {
+            let (here, next) = (Color::from_hue(hue), Color::from_hue((hue + 1) % 360));
+            for (a, b) in channels(here).into_iter().zip(channels(next)) {
+                prop_assert!(
+                    a.abs_diff(b) <= MAX_HUE_STEP,
+                    "hue {} -> {}: {:?} -> {:?}", hue, hue + 1, here, next
+                );
+            }
+        }
+
+        #[test]
+        fn from_hue_wraps_every_360_degrees(hue in any::<u16>()) {
+            prop_assert_eq!(Color::from_hue(hue), Color::from_hue(hue % 360));
+        }
+
+        #[test]
+        fn from_hue_is_fully_saturated(hue in any::<u16>()) {
+            // Every hue has one channel at full and one at zero
+            let c = channels(Color::from_hue(hue));
+            prop_assert!(c.contains(&255) && c.contains(&0), "{:?}", c);
+        }
+    }
+
     // ── Backward-compatible helper tests ───────────────────────────
 
     #[test]
@@ -0,0 +1,226 @@
+//! Automatic brightness from an ambient light sensor.
+//!
+//! A `LuxCurve` maps the measured light level to a panel brightness: points
+//! like `0=5, 400=100` are joined linearly, and readings outside the curve
+//! use the nearest end. Dark rooms get a dim panel, bright rooms a bright
+//! one.
+//!
+//! With the `light-sensor` feature, `Bh1750` reads a BH1750 sensor over the
+//! Linux I2C device interface (`/dev/i2c-N`). The server polls it from a
+//! background thread and sends the brightness commands.
+//!
+//! ## Rust concepts
+//! - `u16::from_str_radix` for hex arguments such as `0x23`
+//! - `ioctl` through `libc`, the one `unsafe` call needed to pick the I2C
+//!   device; reads and writes then go through `std::fs::File`
+
+/// Parse an I2C address written in hex (`0x23`) or decimal (`35`).
+pub fn parse_i2c_address(s: &str) -> Result<u16, String> {
+    let address = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u16::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+    .map_err(|_| format!("Invalid I2C address {s:?}"))?;
+
+    // 0x00-0x02 and 0x78-0x7F are reserved by the I2C spec
+    if !(0x03..=0x77).contains(&address) {
+        return Err(format!("I2C address {s} is not between 0x03 and 0x77"));
+    }
+    Ok(address)
+}
+
+/// Parse a curve point written as `lux=brightness`, e.g. `400=100`.
+pub fn parse_point(s: &str) -> Result<(f32, u8), String> {
+    let (lux, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Expected lux=brightness, got {s:?}"))?;
+
+    let lux: f32 = lux
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid lux value {lux:?}"))?;
+    if !(lux.is_finite() && lux >= 0.0) {
+        return Err(format!("Lux {lux} must be zero or more"));
+    }
+
+    let brightness: u8 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid brightness {value:?}"))?;
+    if brightness > 100 {
+        return Err(format!("Brightness {brightness} is not between 0 and 100"));
+    }
+
+    Ok((lux, brightness))
+}
+
+/// Brightness for each light level, sorted by lux.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LuxCurve {
+    /// `(lux, brightness)`, sorted and with unique lux values
+    points: Vec<(f32, u8)>,
+}
+
+impl Default for LuxCurve {
+    /// Dim at night (10% in the dark), full brightness in a bright room.
+    fn default() -> Self {
+        Self {
+            points: vec![(0.0, 10), (50.0, 40), (400.0, 100)],
+        }
+    }
+}
+
+impl LuxCurve {
+    /// Build a curve from points in any order. There must be at least one
+    /// point and no two at the same lux.
+    pub fn new(mut points: Vec<(f32, u8)>) -> Result<Self, String> {
+        if points.is_empty() {
+            return Err("A light curve needs at least one point".to_string());
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if let Some(pair) = points.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(format!("Two light curve points at {} lux", pair[0].0));
+        }
+        Ok(Self { points })
+    }
+
+    /// Brightness for a reading of `lux`, interpolated between the
+    /// surrounding points.
+    pub fn brightness_at(&self, lux: f32) -> u8 {
+        let next = self.points.partition_point(|&(point, _)| point <= lux);
+        if next == 0 {
+            return self.points[0].1;
+        }
+        let Some(&(to_lux, to)) = self.points.get(next) else {
+            return self.points[next - 1].1;
+        };
+        let (from_lux, from) = self.points[next - 1];
+
+        let t = (lux - from_lux) / (to_lux - from_lux);
+        (from as f32 + (to as f32 - from as f32) * t).round() as u8
+    }
+}
+
+/// Lux from a raw BH1750 measurement (big-endian count, 1.2 counts per lux
+/// in high-resolution mode).
+pub fn bh1750_lux(raw: [u8; 2]) -> f32 {
+    u16::from_be_bytes(raw) as f32 / 1.2
+}
+
+/// A BH1750 ambient light sensor on an I2C bus.
+#[cfg(feature = "light-sensor")]
+pub struct Bh1750 {
+    i2c: std::fs::File,
+}
+
+#[cfg(feature = "light-sensor")]
+impl Bh1750 {
+    /// `ioctl` request selecting the device address on an I2C bus
+    const I2C_SLAVE: libc::Ioctl = 0x0703;
+    const POWER_ON: u8 = 0x01;
+    /// Measure continuously at 1 lux resolution, every ~120 ms
+    const CONTINUOUS_HIGH_RES: u8 = 0x10;
+
+    /// Open the sensor at `address` on `bus` (e.g. `/dev/i2c-1`) and start
+    /// continuous measurement.
+    pub fn open(bus: &std::path::Path, address: u16) -> Result<Self, Box<dyn std::error::Error>> {
+        use std::io::Write;
+        use std::os::fd::AsRawFd;
+
+        let i2c = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(bus)?;
+        // SAFETY: `i2c` is an open file descriptor for the lifetime of the
+        // call, and I2C_SLAVE takes the address as a plain integer argument
+        let result = unsafe {
+            libc::ioctl(
+                i2c.as_raw_fd(),
+                Self::I2C_SLAVE,
+                libc::c_ulong::from(address),
+            )
+        };
+        if result < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        let mut sensor = Self { i2c };
+        sensor.i2c.write_all(&[Self::POWER_ON])?;
+        sensor.i2c.write_all(&[Self::CONTINUOUS_HIGH_RES])?;
+        Ok(sensor)
+    }
+
+    /// The latest light reading, in lux.
+    pub fn read_lux(&mut self) -> Result<f32, Box<dyn std::error::Error>> {
+        use std::io::Read;
+
+        let mut raw = [0u8; 2];
+        self.i2c.read_exact(&mut raw)?;
+        Ok(bh1750_lux(raw))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("0x23", Ok(0x23))]
+    #[case("0X5C", Ok(0x5C))]
+    #[case("35", Ok(35))]
+    fn parses_i2c_addresses(#[case] s: &str, #[case] expected: Result<u16, String>) {
+        assert_eq!(parse_i2c_address(s), expected);
+    }
+
+    #[rstest]
+    #[case("0x00")] // reserved
+    #[case("0x78")] // reserved
+    #[case("0xZZ")]
+    #[case("light")]
+    fn rejects_invalid_i2c_addresses(#[case] s: &str) {
+        assert!(parse_i2c_address(s).is_err());
+    }
+
+    #[rstest]
+    #[case("400=100", Ok((400.0, 100)))]
+    #[case("0.5=5", Ok((0.5, 5)))]
+    fn parses_valid_points(#[case] s: &str, #[case] expected: Result<(f32, u8), String>) {
+        assert_eq!(parse_point(s), expected);
+    }
+
+    #[rstest]
+    #[case("400")] // no brightness
+    #[case("-1=10")] // negative lux
+    #[case("400=101")] // brightness out of range
+    #[case("bright=10")] // not a number
+    fn rejects_invalid_points(#[case] s: &str) {
+        assert!(parse_point(s).is_err());
+    }
+
+    #[test]
+    fn new_rejects_empty_and_duplicate_points() {
+        assert!(LuxCurve::new(vec![]).is_err());
+        assert!(LuxCurve::new(vec![(10.0, 20), (10.0, 30)]).is_err());
+    }
+
+    #[rstest]
+    #[case(0.0, 20)] // below the curve: first point
+    #[case(10.0, 20)]
+    #[case(55.0, 60)] // halfway
+    #[case(100.0, 100)]
+    #[case(5000.0, 100)] // above the curve: last point
+    fn interpolates_and_clamps(#[case] lux: f32, #[case] expected: u8) {
+        // Points given out of order on purpose
+        let curve = LuxCurve::new(vec![(100.0, 100), (10.0, 20)]).unwrap();
+        assert_eq!(curve.brightness_at(lux), expected);
+    }
+
+    #[test]
+    fn bh1750_converts_counts_to_lux() {
+        assert_eq!(bh1750_lux([0x00, 0x00]), 0.0);
+        // 480 counts; 1.2 isn't exact in binary, so compare with a tolerance
+        assert!((bh1750_lux([0x01, 0xE0]) - 400.0).abs() < 0.01);
+    }
+}
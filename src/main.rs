@@ -5,8 +5,10 @@
 //! push raw frames via simple HTTP requests.
 //!
 //! ## Architecture
-//! - **Render thread** (std::thread): owns the LED matrix, processes commands
-//! - **HTTP server** (tokio/axum): accepts API requests, sends commands via channel
+//! - **Render threads** (std::thread): one per panel, each owns its LED
+//!   matrix and processes its own commands
+//! - **HTTP server** (tokio/axum): accepts API requests, sends commands via
+//!   the channel of the panel they target
 //!
 //! ## Rust concepts
 //! - `#[tokio::main]` async entry point
@@ -36,12 +38,29 @@ fn main() {
 #[tokio::main(flavor = "current_thread")]
 async fn hardware_main() {
     use clap::Parser;
-    use led_matrix_rs::PanelConfig;
-    use led_matrix_rs::render::{DisplayStatus, render_loop};
-    use led_matrix_rs::server::{self, AppState};
+    use led_matrix_rs::api::{DisplayStatus, EffectiveConfig, VideoFinish};
+    use led_matrix_rs::bdf;
+    use led_matrix_rs::diagnostics::CommandLog;
+    #[cfg(feature = "light-sensor")]
+    use led_matrix_rs::light::{self, Bh1750, LuxCurve};
+    use led_matrix_rs::media;
+    use led_matrix_rs::pixel_map::{PixelMap, Symmetry};
+    use led_matrix_rs::presets::PresetStore;
+    use led_matrix_rs::queue;
+    use led_matrix_rs::render::{
+        BrightnessMode, CanvasStrategy, RenderCommand, RenderConfig, render_loop,
+    };
+    use led_matrix_rs::schedule::{self, BrightnessSchedule};
+    use led_matrix_rs::server::{self, AppState, Display};
+    use led_matrix_rs::settings::{Limits, RuntimeSettings, SettingsFile};
+    use led_matrix_rs::{
+        Color, ColorOrder, DEFAULT_HARDWARE_MAPPING, GPIO_SLOWDOWN, PWM_BITS, PanelConfig,
+    };
+    use std::collections::BTreeMap;
     use std::path::PathBuf;
-    use std::sync::mpsc;
+    use std::sync::atomic::AtomicBool;
     use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
 
     /// LED Matrix HTTP API Server
     #[derive(Parser)]
@@ -53,14 +72,28 @@ async fn hardware_main() {
         #[arg(long, default_value = ".")]
         media_dir: PathBuf,
 
-        /// Port to listen on
+        /// Port to listen on; 0 lets the OS pick a free one
         #[arg(long, default_value = "8080")]
         port: u16,
 
+        /// Write the port actually listened on to this file once bound, e.g.
+        /// to find the server after starting it with --port 0
+        #[arg(long, value_name = "PATH")]
+        port_file: Option<PathBuf>,
+
         /// Path to BDF font directory
         #[arg(long, default_value = "fonts/bdf")]
         fonts_dir: PathBuf,
 
+        /// Font for text requests that don't name one
+        #[arg(long, default_value = "6x13")]
+        default_font: String,
+
+        /// Color for text requests that don't give one, as R,G,B, #RRGGBB, or a
+        /// web color name
+        #[arg(long, default_value = "255,255,255")]
+        default_text_color: Color,
+
         /// Number of rows on the LED panel
         #[arg(long, default_value = "64")]
         rows: u32,
@@ -68,6 +101,156 @@ async fn hardware_main() {
         /// Number of columns on the LED panel
         #[arg(long, default_value = "64")]
         cols: u32,
+
+        /// Canvas strategy: double-buffered (no tearing) or direct (lower latency and
+        /// one less frame buffer, may tear)
+        #[arg(long, value_enum, default_value_t = CanvasStrategy::DoubleBuffered)]
+        canvas: CanvasStrategy,
+
+        /// Initial hardware (PWM) brightness of the matrix library, 1-100
+        #[arg(long, default_value = "100", value_parser = clap::value_parser!(u8).range(1..=100))]
+        hardware_brightness: u8,
+
+        /// Highest brightness, 0-100, that any request can set (software and
+        /// hardware alike); higher values are clamped. Keeps a full-white frame
+        /// within the power supply's budget
+        #[arg(long, default_value = "100", value_parser = clap::value_parser!(u8).range(0..=100))]
+        max_brightness: u8,
+
+        /// Which brightness POST /api/v1/brightness controls: software (per-pixel) or hardware (PWM)
+        #[arg(long, value_enum, default_value_t = BrightnessMode::Software)]
+        brightness_mode: BrightnessMode,
+
+        /// Brightness at a local time of day, as HH:MM=brightness (e.g. 22:00=10).
+        /// Repeatable; brightness fades linearly between points
+        #[arg(long, value_parser = schedule::parse_point)]
+        brightness_schedule: Vec<(u32, u8)>,
+
+        /// Largest request body accepted, in bytes; larger requests get 413 Payload Too Large
+        #[arg(long, default_value = "16777216")]
+        max_body_bytes: usize,
+
+        /// Leave this many pixels dark on every edge, for enclosures that cover
+        /// them; content is laid out in the area inside
+        #[arg(long, default_value = "0")]
+        safe_inset: u32,
+
+        /// File mapping each logical (x, y) to a physical (x, y), for custom wiring
+        #[arg(long)]
+        pixel_map: Option<PathBuf>,
+
+        /// Order the panel's wiring takes color channels in; try bgr or grb if
+        /// red shows up as another color
+        #[arg(long, value_enum, default_value_t = ColorOrder::Rgb)]
+        color_order: ColorOrder,
+
+        /// Mirror part of the panel into the rest at draw time, so clients only
+        /// draw the left half, top half, or top-left quadrant
+        #[arg(long, value_enum, default_value_t = Symmetry::None)]
+        symmetry: Symmetry,
+
+        /// Make POST /api/v1/display/stop blank the panel instead of holding the last frame
+        #[arg(long)]
+        stop_clears: bool,
+
+        /// What a video played without `loop` leaves on the panel when it ends,
+        /// unless the request sets `on_finish`
+        #[arg(long, value_enum, default_value_t = VideoFinish::Clear)]
+        video_finish_action: VideoFinish,
+
+        /// Show the system stats dashboard (CPU temperature, load, memory, IP)
+        /// on every panel at startup, until another command replaces it
+        #[arg(long)]
+        show_sysinfo: bool,
+
+        /// Most WebSocket stream clients a panel accepts at once; more are rejected
+        #[arg(long, default_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
+        max_stream_clients: u32,
+
+        /// Shut the panel off after this many seconds without commands; the next
+        /// command turns it back on
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        power_off_after: Option<u64>,
+
+        /// Keep each display command up for at least this many milliseconds;
+        /// newer commands arriving meanwhile are coalesced to the latest
+        #[arg(long, default_value = "0")]
+        min_display_ms: u64,
+
+        /// Most frames per second swapped onto the panel in any mode; faster
+        /// content drops frames instead. Lower it if the panel flickers
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        max_refresh_fps: Option<u32>,
+
+        /// Skip drawing a raw frame identical to the one already shown
+        #[arg(long)]
+        skip_duplicate_frames: bool,
+
+        /// JSON file for saved presets [default: <media-dir>/presets.json]
+        #[arg(long)]
+        presets_file: Option<PathBuf>,
+
+        /// JSON file overriding --max-brightness and --max-refresh-fps, re-read
+        /// on SIGHUP so the caps change without a restart
+        #[arg(long)]
+        settings_file: Option<PathBuf>,
+
+        /// Saved preset to activate on every panel at startup
+        #[arg(long, value_name = "NAME")]
+        startup_preset: Option<String>,
+
+        /// Lock the display's content: every endpoint that would change it
+        /// answers 403, leaving status and media listings, unless allowed
+        /// with --appliance-allow
+        #[arg(long)]
+        appliance: bool,
+
+        /// Endpoint still served in appliance mode, as its exact path (e.g.
+        /// /api/v1/presets/night/activate). Repeatable
+        #[arg(long, value_name = "PATH", requires = "appliance")]
+        appliance_allow: Vec<String>,
+
+        /// GPIO mapping of a panel (e.g. adafruit-hat, regular). Repeat to drive
+        /// several independent panels; the Nth one is panel id N-1
+        #[arg(long, default_value = DEFAULT_HARDWARE_MAPPING)]
+        hardware_mapping: Vec<String>,
+
+        /// Host /api/v1/display/image/url may fetch from, even on the LAN. Repeatable;
+        /// when given, no other hosts are allowed
+        #[cfg(feature = "remote-images")]
+        #[arg(long)]
+        image_url_host: Vec<String>,
+
+        /// I2C address of a BH1750 ambient light sensor (e.g. 0x23) that sets
+        /// brightness automatically
+        #[cfg(feature = "light-sensor")]
+        #[arg(long, value_parser = light::parse_i2c_address)]
+        light_sensor: Option<u16>,
+
+        /// I2C bus device the light sensor is on
+        #[cfg(feature = "light-sensor")]
+        #[arg(long, default_value = "/dev/i2c-1")]
+        light_sensor_bus: PathBuf,
+
+        /// Brightness at a light level, as LUX=brightness (e.g. 400=100). Repeatable;
+        /// brightness scales linearly between points [default: 0=10 50=40 400=100]
+        #[cfg(feature = "light-sensor")]
+        #[arg(long, value_parser = light::parse_point)]
+        light_curve: Vec<(f32, u8)>,
+
+        /// PEM certificate chain to serve HTTPS with, instead of plain HTTP
+        #[cfg(feature = "tls")]
+        #[arg(long, value_name = "PATH", requires = "tls_key")]
+        tls_cert: Option<PathBuf>,
+
+        /// PEM private key for --tls-cert
+        #[cfg(feature = "tls")]
+        #[arg(long, value_name = "PATH", requires = "tls_cert")]
+        tls_key: Option<PathBuf>,
+
+        /// Write the OpenAPI spec (JSON) to this file and exit without starting the server
+        #[arg(long, value_name = "PATH")]
+        dump_openapi: Option<PathBuf>,
     }
 
     // Initialize tracing subscriber for request logging
@@ -77,8 +260,40 @@ async fn hardware_main() {
         .compact()
         .init();
 
+    let started_at = Instant::now();
     let args = Args::parse();
-    let panel = PanelConfig::new(args.rows, args.cols);
+
+    // Only needs the API definition: no directories, matrix, or port
+    if let Some(path) = &args.dump_openapi {
+        let json = server::openapi()
+            .to_pretty_json()
+            .expect("OpenAPI spec serializes to JSON");
+        if let Err(e) = std::fs::write(path, json + "\n") {
+            eprintln!("Error: failed to write {}: {e}", path.display());
+            std::process::exit(1);
+        }
+        println!("Wrote OpenAPI spec to {}", path.display());
+        return;
+    }
+
+    let matrix_panel = PanelConfig::new(args.rows, args.cols);
+    // Everything but the matrix itself only sees the area inside the inset
+    let panel = matrix_panel.inset(args.safe_inset).unwrap_or_else(|| {
+        eprintln!(
+            "Error: --safe-inset {} leaves nothing of a {}x{} panel",
+            args.safe_inset, args.cols, args.rows
+        );
+        std::process::exit(1);
+    });
+
+    // Fail fast on a missing directory instead of serving empty listings
+    let created = media::prepare_media_dir(&args.media_dir).unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        eprintln!("Create it or pass --media-dir pointing at your media directory.");
+        std::process::exit(1);
+    });
+    // Text still works from the built-in fonts, so this isn't fatal
+    let fonts_dir_error = media::require_dir(&args.fonts_dir, "Fonts directory").err();
 
     let media_dir = args.media_dir.canonicalize().unwrap_or_else(|_| {
         eprintln!("Warning: could not canonicalize media dir, using as-is");
@@ -86,50 +301,372 @@ async fn hardware_main() {
     });
 
     let fonts_dir = args.fonts_dir.canonicalize().unwrap_or_else(|_| {
-        eprintln!("Warning: could not canonicalize fonts dir, using as-is");
+        // A missing fonts dir gets its own warning below
+        if fonts_dir_error.is_none() {
+            eprintln!("Warning: could not canonicalize fonts dir, using as-is");
+        }
         args.fonts_dir.clone()
     });
 
+    let pixel_map = args.pixel_map.as_ref().map(|path| {
+        PixelMap::load(path, matrix_panel).unwrap_or_else(|e| {
+            eprintln!("Error: failed to load pixel map {}: {e}", path.display());
+            std::process::exit(1);
+        })
+    });
+    let pixel_map = PixelMap::for_display(matrix_panel, pixel_map, args.safe_inset, args.symmetry)
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        });
+
+    let presets_file = args
+        .presets_file
+        .clone()
+        .unwrap_or_else(|| media_dir.join("presets.json"));
+    let presets = PresetStore::load(presets_file.clone()).unwrap_or_else(|e| {
+        eprintln!("Error: failed to load presets: {e}");
+        std::process::exit(1);
+    });
+
+    let flag_settings = RuntimeSettings {
+        max_brightness: args.max_brightness,
+        max_refresh_fps: args.max_refresh_fps,
+    };
+    let mut restart_only = Vec::new();
+    let settings = match &args.settings_file {
+        Some(path) => {
+            let (file, ignored) = SettingsFile::load(path).unwrap_or_else(|e| {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            });
+            restart_only = ignored;
+            file.resolve(flag_settings)
+        }
+        None => flag_settings,
+    };
+    let limits = Arc::new(Limits::new(settings));
+
+    let brightness_schedule = if args.brightness_schedule.is_empty() {
+        None
+    } else {
+        let schedule =
+            BrightnessSchedule::new(args.brightness_schedule.clone()).unwrap_or_else(|e| {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            });
+        Some(Arc::new(schedule))
+    };
+
+    #[cfg(feature = "light-sensor")]
+    let light_sensor = args.light_sensor.map(|address| {
+        if brightness_schedule.is_some() {
+            eprintln!("Error: --light-sensor and --brightness-schedule can't be used together");
+            std::process::exit(1);
+        }
+        let curve = if args.light_curve.is_empty() {
+            LuxCurve::default()
+        } else {
+            LuxCurve::new(args.light_curve.clone()).unwrap_or_else(|e| {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            })
+        };
+        let sensor = Bh1750::open(&args.light_sensor_bus, address).unwrap_or_else(|e| {
+            eprintln!(
+                "Error: failed to open light sensor {address:#04x} on {}: {e}",
+                args.light_sensor_bus.display()
+            );
+            std::process::exit(1);
+        });
+        (sensor, curve)
+    });
+    #[cfg(feature = "light-sensor")]
+    let has_light_sensor = light_sensor.is_some();
+    #[cfg(not(feature = "light-sensor"))]
+    let has_light_sensor = false;
+
     tracing::info!("LED Matrix HTTP Server v{}", env!("CARGO_PKG_VERSION"));
-    tracing::info!("Panel: {}x{}", panel.cols, panel.rows);
+    tracing::info!("Panel: {}x{}", matrix_panel.cols, matrix_panel.rows);
+    if args.safe_inset > 0 {
+        tracing::info!(
+            "Safe inset: {}px, drawable area {}x{}",
+            args.safe_inset,
+            panel.cols,
+            panel.rows
+        );
+    }
+    for (id, mapping) in args.hardware_mapping.iter().enumerate() {
+        tracing::info!("Panel {}: hardware mapping {}", id, mapping);
+    }
     tracing::info!("Media dir: {}", media_dir.display());
     tracing::info!("Fonts dir: {}", fonts_dir.display());
+    tracing::info!("Presets: {}", presets_file.display());
+    tracing::info!("Canvas: {:?}", args.canvas);
+    if let Some(path) = &args.pixel_map {
+        tracing::info!("Pixel map: {}", path.display());
+    }
+    if args.symmetry != Symmetry::None {
+        tracing::info!("Symmetry: {:?}", args.symmetry);
+    }
+    if args.color_order != ColorOrder::Rgb {
+        tracing::info!("Color order: {:?}", args.color_order);
+    }
+    tracing::info!(
+        "Brightness: {:?} mode, hardware brightness {}, max {}",
+        args.brightness_mode,
+        args.hardware_brightness,
+        settings.max_brightness
+    );
+    if brightness_schedule.is_some() {
+        tracing::info!(
+            "Brightness schedule: {} points",
+            args.brightness_schedule.len()
+        );
+    }
+    if has_light_sensor {
+        tracing::info!("Light sensor: enabled");
+    }
+    if args.min_display_ms > 0 {
+        tracing::info!("Minimum display time: {} ms", args.min_display_ms);
+    }
+    if let Some(fps) = settings.max_refresh_fps {
+        tracing::info!("Refresh capped at {} fps", fps);
+    }
+    if let Some(path) = &args.settings_file {
+        tracing::info!("Settings file: {} (reloaded on SIGHUP)", path.display());
+        for key in &restart_only {
+            tracing::warn!("Settings file: {key} can't be set there; ignored");
+        }
+    }
+    if args.appliance {
+        tracing::info!(
+            "Appliance mode: {} changing endpoints allowed",
+            args.appliance_allow.len()
+        );
+    }
+    tracing::info!("Max body size: {} bytes", args.max_body_bytes);
     tracing::info!("Port: {}", args.port);
 
-    // Create the channel for sending commands to the render thread.
-    let (tx, rx) = mpsc::channel();
+    for dir in &created {
+        tracing::info!("Created missing directory: {}", dir.display());
+    }
+    if media::list_images(&media_dir).is_empty() && media::list_videos(&media_dir).is_empty() {
+        tracing::warn!(
+            "No images or videos found. Add files to {}/images/ or frame folders to {}/videos/",
+            media_dir.display(),
+            media_dir.display()
+        );
+    }
+    let builtin_fonts = bdf::embedded_fonts().collect::<Vec<_>>().join(", ");
+    if let Some(e) = fonts_dir_error {
+        tracing::warn!("{e}; only the built-in fonts ({builtin_fonts}) are available");
+        tracing::warn!("Pass --fonts-dir pointing at a directory of .bdf fonts.");
+    } else if media::list_bdf_fonts(&fonts_dir).is_empty() {
+        tracing::warn!(
+            "No .bdf fonts found in {}; only the built-in fonts ({builtin_fonts}) are available",
+            fonts_dir.display()
+        );
+    }
+    let default_font = if media::list_available_fonts(&fonts_dir).contains(&args.default_font) {
+        args.default_font.clone()
+    } else {
+        tracing::warn!(
+            "Default font {} not found in {}; using the built-in {} instead",
+            args.default_font,
+            fonts_dir.display(),
+            bdf::FALLBACK_FONT
+        );
+        bdf::FALLBACK_FONT.to_string()
+    };
 
-    // Shared display status — render thread writes, HTTP handlers read.
-    let status = Arc::new(Mutex::new(DisplayStatus::new()));
+    // One render thread per panel, each with its own command channel and
+    // status (render thread writes, HTTP handlers read).
+    let mut displays = BTreeMap::new();
+    let mut render_handles = Vec::new();
+    for (id, hardware_mapping) in args.hardware_mapping.iter().enumerate() {
+        let (tx, rx) = queue::counting_channel();
+        let status = Arc::new(Mutex::new(DisplayStatus::new()));
+        let diagnostics = Arc::new(Mutex::new(CommandLog::default()));
 
-    // Spawn the render thread.
-    let render_status = status.clone();
-    let render_handle = std::thread::spawn(move || {
-        render_loop(rx, render_status, fonts_dir, panel);
-    });
+        let render_status = status.clone();
+        let render_config = RenderConfig {
+            panel,
+            safe_inset: args.safe_inset,
+            fonts_dir: fonts_dir.clone(),
+            canvas: args.canvas,
+            hardware_brightness: args.hardware_brightness,
+            limits: limits.clone(),
+            pixel_map: pixel_map.clone(),
+            hardware_mapping: hardware_mapping.clone(),
+            power_off_after: args.power_off_after.map(Duration::from_secs),
+            min_display: Duration::from_millis(args.min_display_ms),
+            diagnostics: diagnostics.clone(),
+            skip_duplicate_frames: args.skip_duplicate_frames,
+            color_order: args.color_order,
+        };
+        render_handles.push(std::thread::spawn(move || {
+            render_loop(rx, render_status, render_config);
+        }));
+        if args.show_sysinfo {
+            tx.send(RenderCommand::ShowSysInfo {
+                font: default_font.clone(),
+                color: args.default_text_color,
+            })
+            .expect("render thread just started");
+        }
+
+        displays.insert(
+            id as u32,
+            Display {
+                command_tx: tx,
+                status,
+                schedule_override: Arc::new(AtomicBool::new(false)),
+                diagnostics,
+                started_at,
+            },
+        );
+    }
+
+    // What GET /api/v1/config reports; choices by the name their flag takes
+    fn flag_name(value: impl clap::ValueEnum) -> String {
+        value
+            .to_possible_value()
+            .map_or_else(String::new, |v| v.get_name().to_string())
+    }
+    let config = EffectiveConfig {
+        rows: panel.rows,
+        cols: panel.cols,
+        safe_inset: args.safe_inset,
+        hardware_mappings: args.hardware_mapping.clone(),
+        pwm_bits: PWM_BITS,
+        gpio_slowdown: GPIO_SLOWDOWN,
+        canvas: flag_name(args.canvas),
+        color_order: flag_name(args.color_order),
+        symmetry: flag_name(args.symmetry),
+        pixel_map: args.pixel_map.as_ref().map(|p| p.display().to_string()),
+        hardware_brightness: args.hardware_brightness,
+        brightness_mode: flag_name(args.brightness_mode),
+        max_brightness: settings.max_brightness,
+        max_refresh_fps: settings.max_refresh_fps,
+        brightness_schedule_points: args.brightness_schedule.len(),
+        light_sensor: has_light_sensor,
+        media_dir: media_dir.display().to_string(),
+        fonts_dir: fonts_dir.display().to_string(),
+        presets_file: presets_file.display().to_string(),
+        settings_file: args.settings_file.as_ref().map(|p| p.display().to_string()),
+        default_font: default_font.clone(),
+        default_text_color: args.default_text_color,
+        power_off_after_secs: args.power_off_after,
+        min_display_ms: args.min_display_ms,
+        skip_duplicate_frames: args.skip_duplicate_frames,
+        stop_clears: args.stop_clears,
+        video_finish_action: args.video_finish_action,
+        max_stream_clients: args.max_stream_clients,
+        max_body_bytes: args.max_body_bytes,
+        appliance: args.appliance,
+        #[cfg(feature = "tls")]
+        tls: args.tls_cert.is_some(),
+        #[cfg(not(feature = "tls"))]
+        tls: false,
+        port: args.port,
+    };
 
     // Build the HTTP server
     let app_state = AppState {
-        command_tx: tx,
-        status,
+        displays: Arc::new(displays),
         media_dir,
+        fonts_dir,
         panel,
+        brightness_mode: args.brightness_mode,
+        max_body_bytes: args.max_body_bytes,
+        stop_clears: args.stop_clears,
+        video_finish: args.video_finish_action,
+        max_stream_clients: args.max_stream_clients,
+        presets: Arc::new(Mutex::new(presets)),
+        brightness_schedule,
+        light_sensor: has_light_sensor,
+        default_font,
+        default_text_color: args.default_text_color,
+        #[cfg(feature = "remote-images")]
+        image_url_hosts: args.image_url_host.clone(),
+        appliance: args.appliance.then(|| args.appliance_allow.clone()),
+        limits,
+        config: Arc::new(config),
     };
 
-    let app = server::create_router(app_state);
+    if let Some(name) = &args.startup_preset
+        && let Err(e) = server::activate_startup_preset(&app_state, name)
+    {
+        eprintln!("Error: failed to activate startup preset {name}: {e}");
+        std::process::exit(1);
+    }
 
-    // Start listening
-    let addr = format!("0.0.0.0:{}", args.port);
-    tracing::info!("Listening on http://{}", addr);
-    tracing::info!("API Documentation: http://localhost:{}/docs", args.port);
-    tracing::info!("Try: curl http://localhost:{}/api/v1/status", args.port);
+    server::spawn_brightness_schedule(app_state.clone());
+    if let Some(path) = args.settings_file.clone() {
+        server::spawn_settings_reload(app_state.clone(), path, flag_settings);
+    }
+    #[cfg(feature = "light-sensor")]
+    if let Some((sensor, curve)) = light_sensor {
+        server::spawn_light_sensor(app_state.clone(), sensor, curve);
+    }
 
-    let listener = tokio::net::TcpListener::bind(&addr)
+    // Start listening
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", args.port))
         .await
         .expect("Failed to bind to address");
+    // Differs from --port when that is 0 and the OS picked the port
+    let port = listener
+        .local_addr()
+        .expect("Bound listener has an address")
+        .port();
+    if let Some(path) = &args.port_file
+        && let Err(e) = std::fs::write(path, format!("{port}\n"))
+    {
+        eprintln!("Error: failed to write {}: {e}", path.display());
+        std::process::exit(1);
+    }
+
+    #[cfg(feature = "tls")]
+    let tls = match (&args.tls_cert, &args.tls_key) {
+        (Some(cert), Some(key)) => {
+            match axum_server::tls_rustls::RustlsConfig::from_pem_file(cert, key).await {
+                Ok(config) => Some(config),
+                Err(e) => {
+                    eprintln!(
+                        "Error: failed to load TLS certificate {} and key {}: {e}",
+                        cert.display(),
+                        key.display()
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+        _ => None,
+    };
+    #[cfg(feature = "tls")]
+    let scheme = if tls.is_some() { "https" } else { "http" };
+    #[cfg(not(feature = "tls"))]
+    let scheme = "http";
+
+    let app = server::create_router(app_state, scheme, port);
+
+    tracing::info!("Listening on {scheme}://0.0.0.0:{}", port);
+    tracing::info!("API Documentation: {scheme}://localhost:{}/docs", port);
+    tracing::info!("Try: curl {scheme}://localhost:{}/api/v1/status", port);
 
     // Run the server — this blocks until the process is killed
+    #[cfg(feature = "tls")]
+    if let Some(config) = tls {
+        let listener = listener.into_std().expect("Bound listener converts to std");
+        axum_server::from_tcp_rustls(listener, config)
+            .serve(app.into_make_service())
+            .await
+            .expect("Server error");
+        drop(render_handles);
+        return;
+    }
     axum::serve(listener, app).await.expect("Server error");
 
-    drop(render_handle);
+    drop(render_handles);
 }
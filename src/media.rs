@@ -5,10 +5,31 @@
 //! - `Path` and `PathBuf` for cross-platform file paths
 //! - `serde::Serialize` for automatic JSON conversion
 //! - Collecting iterators into `Vec`
+//! - A small trait so one filter/sort/page function serves both listings
 
-use serde::Serialize;
+use crate::bdf;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Subdirectories the server expects under the media directory.
+pub const MEDIA_SUBDIRS: [&str; 3] = ["images", "videos", "fonts/bdf"];
+
+/// Image file extensions the server can decode. The `image` crate is built
+/// with only its PNG and JPEG decoders, so nothing else is listed.
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+
+/// Extensions of the frame images in a video directory.
+pub const VIDEO_FRAME_EXTENSIONS: &[&str] = IMAGE_EXTENSIONS;
+
+/// Whether `path` ends in one of `extensions` (without the dot).
+pub fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| extensions.contains(&e))
+}
 
 /// Information about a single media file.
 #[derive(Serialize, utoipa::ToSchema)]
@@ -19,6 +40,26 @@ pub struct MediaEntry {
     pub path: String,
     /// File size in bytes
     pub size: u64,
+    /// Last modification time, in seconds since the Unix epoch (0 if unknown)
+    pub modified: u64,
+}
+
+impl MediaEntry {
+    /// Entry for the file at `path`, listed as `rel_path`. Size and
+    /// modification time are 0 if the file can't be read.
+    pub fn for_file(path: &Path, rel_path: String) -> Self {
+        let metadata = fs::metadata(path).ok();
+        Self {
+            name: path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            path: rel_path,
+            size: metadata.as_ref().map_or(0, |m| m.len()),
+            modified: metadata.as_ref().map_or(0, modified_secs),
+        }
+    }
 }
 
 /// Information about a video directory (folder of frame images).
@@ -30,6 +71,97 @@ pub struct VideoEntry {
     pub path: String,
     /// Number of frame files in the directory
     pub frame_count: usize,
+    /// Last modification time of the directory, in seconds since the Unix
+    /// epoch (0 if unknown)
+    pub modified: u64,
+}
+
+/// Order of a media listing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    /// By name, A to Z
+    #[default]
+    Name,
+    /// Largest first (file size for images, frame count for videos)
+    Size,
+    /// Most recently modified first
+    Mtime,
+}
+
+/// A listing entry that `page` can filter and sort.
+pub trait Listed {
+    fn name(&self) -> &str;
+    /// What `SortKey::Size` sorts by
+    fn size_key(&self) -> u64;
+    fn modified(&self) -> u64;
+}
+
+impl Listed for MediaEntry {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn size_key(&self) -> u64 {
+        self.size
+    }
+    fn modified(&self) -> u64 {
+        self.modified
+    }
+}
+
+impl Listed for VideoEntry {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn size_key(&self) -> u64 {
+        self.frame_count as u64
+    }
+    fn modified(&self) -> u64 {
+        self.modified
+    }
+}
+
+/// Keep the entries whose name starts with `prefix`, sort them, and return
+/// up to `limit` of them starting at `offset`, along with how many matched
+/// in total. Ties in size or time are broken by name, so pages are stable.
+pub fn page<T: Listed>(
+    mut entries: Vec<T>,
+    prefix: &str,
+    sort: SortKey,
+    offset: usize,
+    limit: Option<usize>,
+) -> (Vec<T>, usize) {
+    entries.retain(|entry| entry.name().starts_with(prefix));
+    match sort {
+        SortKey::Name => entries.sort_by(|a, b| a.name().cmp(b.name())),
+        SortKey::Size => entries.sort_by(|a, b| {
+            b.size_key()
+                .cmp(&a.size_key())
+                .then_with(|| a.name().cmp(b.name()))
+        }),
+        SortKey::Mtime => entries.sort_by(|a, b| {
+            b.modified()
+                .cmp(&a.modified())
+                .then_with(|| a.name().cmp(b.name()))
+        }),
+    }
+
+    let total = entries.len();
+    let page = entries
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+    (page, total)
+}
+
+/// Modification time of `metadata` in whole seconds since the Unix epoch.
+fn modified_secs(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_secs())
 }
 
 /// Scan the images directory for PNG and JPEG files.
@@ -48,25 +180,10 @@ pub fn list_images(media_dir: &Path) -> Vec<MediaEntry> {
             continue;
         }
 
-        let is_image = path
-            .extension()
-            .and_then(|e| e.to_str())
-            .is_some_and(|e| matches!(e, "png" | "jpg" | "jpeg" | "gif" | "bmp"));
-
-        if is_image {
-            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
-            let name = path
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .into_owned();
+        if has_extension(&path, IMAGE_EXTENSIONS) {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
             let rel_path = format!("images/{name}");
-
-            entries.push(MediaEntry {
-                name,
-                path: rel_path,
-                size,
-            });
+            entries.push(MediaEntry::for_file(&path, rel_path));
         }
     }
 
@@ -93,20 +210,7 @@ pub fn list_videos(media_dir: &Path) -> Vec<VideoEntry> {
             continue;
         }
 
-        // Count image files in this subdirectory
-        let frame_count = fs::read_dir(&path)
-            .map(|rd| {
-                rd.flatten()
-                    .filter(|e| {
-                        e.path()
-                            .extension()
-                            .and_then(|ext| ext.to_str())
-                            .is_some_and(|ext| matches!(ext, "png" | "jpg" | "jpeg"))
-                    })
-                    .count()
-            })
-            .unwrap_or(0);
-
+        let frame_count = count_frames(&path);
         if frame_count > 0 {
             let name = path
                 .file_name()
@@ -119,6 +223,7 @@ pub fn list_videos(media_dir: &Path) -> Vec<VideoEntry> {
                 name,
                 path: rel_path,
                 frame_count,
+                modified: entry.metadata().map_or(0, |m| modified_secs(&m)),
             });
         }
     }
@@ -127,12 +232,37 @@ pub fn list_videos(media_dir: &Path) -> Vec<VideoEntry> {
     entries
 }
 
+/// Number of video frame images directly in `dir`; 0 if it can't be read.
+pub fn count_frames(dir: &Path) -> usize {
+    fs::read_dir(dir)
+        .map(|rd| {
+            rd.flatten()
+                .filter(|e| has_extension(&e.path(), VIDEO_FRAME_EXTENSIONS))
+                .count()
+        })
+        .unwrap_or(0)
+}
+
 /// Scan the fonts directory for available BDF fonts.
 pub fn list_fonts(media_dir: &Path) -> Vec<String> {
-    let fonts_dir = media_dir.join("fonts").join("bdf");
+    list_bdf_fonts(&media_dir.join("fonts").join("bdf"))
+}
+
+/// Every font text can use: the BDF fonts in `fonts_dir` plus the built-in
+/// ones, sorted and without duplicates.
+pub fn list_available_fonts(fonts_dir: &Path) -> Vec<String> {
+    let mut fonts = list_bdf_fonts(fonts_dir);
+    fonts.extend(bdf::embedded_fonts().map(String::from));
+    fonts.sort();
+    fonts.dedup();
+    fonts
+}
+
+/// Scan a directory for BDF fonts, returning their names without extension.
+pub fn list_bdf_fonts(fonts_dir: &Path) -> Vec<String> {
     let mut fonts = Vec::new();
 
-    let read_dir = match fs::read_dir(&fonts_dir) {
+    let read_dir = match fs::read_dir(fonts_dir) {
         Ok(rd) => rd,
         Err(_) => return fonts,
     };
@@ -160,6 +290,108 @@ pub fn list_fonts(media_dir: &Path) -> Vec<String> {
     fonts
 }
 
+// ── Slideshows ───────────────────────────────────────────────────────
+
+/// Compare names the way people number files: runs of digits compare by
+/// value, so `img2.png` sorts before `img10.png`. Everything else compares
+/// character by character.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(ca), Some(cb)) = (a.chars().next(), b.chars().next()) else {
+            return a.len().cmp(&b.len());
+        };
+
+        if ca.is_ascii_digit() && cb.is_ascii_digit() {
+            let a_end = a.find(|c: char| !c.is_ascii_digit()).unwrap_or(a.len());
+            let b_end = b.find(|c: char| !c.is_ascii_digit()).unwrap_or(b.len());
+            let (a_num, b_num) = (
+                a[..a_end].trim_start_matches('0'),
+                b[..b_end].trim_start_matches('0'),
+            );
+            // Longer after dropping leading zeros means larger; no overflow
+            // however many digits there are
+            let by_value = a_num.len().cmp(&b_num.len()).then_with(|| a_num.cmp(b_num));
+            if by_value != Ordering::Equal {
+                return by_value;
+            }
+            (a, b) = (&a[a_end..], &b[b_end..]);
+        } else {
+            if ca != cb {
+                return ca.cmp(&cb);
+            }
+            (a, b) = (&a[ca.len_utf8()..], &b[cb.len_utf8()..]);
+        }
+    }
+}
+
+/// The PNG and JPEG files in `dir`, in natural order by file name.
+pub fn slideshow_images(dir: &Path) -> Vec<PathBuf> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<PathBuf> = read_dir
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && has_extension(path, IMAGE_EXTENSIONS))
+        .collect();
+    paths.sort_by(|a, b| {
+        natural_cmp(
+            &a.file_name().unwrap_or_default().to_string_lossy(),
+            &b.file_name().unwrap_or_default().to_string_lossy(),
+        )
+    });
+    paths
+}
+
+/// Shuffle `items` in place (Fisher-Yates), driven by a xorshift generator
+/// started from `seed`. The same seed always gives the same order.
+pub fn shuffle<T>(items: &mut [T], seed: u64) {
+    // xorshift gets stuck at zero
+    let mut state = seed | 1;
+    for i in (1..items.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let j = (state % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+// ── Startup checks ───────────────────────────────────────────────────
+
+/// Check that `path` exists and is a directory.
+///
+/// `what` names the directory in the error, e.g. `"Media directory"`.
+pub fn require_dir(path: &Path, what: &str) -> Result<(), String> {
+    match fs::metadata(path) {
+        Ok(meta) if meta.is_dir() => Ok(()),
+        Ok(_) => Err(format!("{what} {} is not a directory", path.display())),
+        Err(e) => Err(format!("{what} {} can't be read: {e}", path.display())),
+    }
+}
+
+/// Check the media directory and create any missing standard subdirectories.
+///
+/// Returns the subdirectories that were created, so the caller can log them.
+pub fn prepare_media_dir(media_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    require_dir(media_dir, "Media directory")?;
+
+    let mut created = Vec::new();
+    for subdir in MEDIA_SUBDIRS {
+        let path = media_dir.join(subdir);
+        if path.is_dir() {
+            continue;
+        }
+        fs::create_dir_all(&path)
+            .map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+        created.push(path);
+    }
+
+    Ok(created)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,19 +410,18 @@ mod tests {
         create_file(&images_dir, "photo.png");
         create_file(&images_dir, "pic.jpg");
         create_file(&images_dir, "shot.jpeg");
+        create_file(&images_dir, "readme.txt"); // should be excluded
+        // No decoder for these, so they'd only fail to display
         create_file(&images_dir, "anim.gif");
         create_file(&images_dir, "raw.bmp");
-        create_file(&images_dir, "readme.txt"); // should be excluded
 
         let entries = list_images(tmp.path());
         let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
 
-        assert_eq!(entries.len(), 5);
+        assert_eq!(entries.len(), 3);
         assert!(names.contains(&"photo.png"));
         assert!(names.contains(&"pic.jpg"));
         assert!(names.contains(&"shot.jpeg"));
-        assert!(names.contains(&"anim.gif"));
-        assert!(names.contains(&"raw.bmp"));
     }
 
     #[test]
@@ -258,10 +489,175 @@ mod tests {
         assert_eq!(fonts, vec!["6x13", "9x18"]);
     }
 
+    #[test]
+    fn list_bdf_fonts_scans_given_dir() {
+        let tmp = TempDir::new().unwrap();
+        create_file(tmp.path(), "helvR12.bdf");
+        create_file(tmp.path(), "notes.md");
+
+        assert_eq!(list_bdf_fonts(tmp.path()), vec!["helvR12"]);
+    }
+
+    #[test]
+    fn available_fonts_always_include_the_fallback() {
+        let tmp = TempDir::new().unwrap();
+        create_file(tmp.path(), "6x10.bdf");
+        create_file(tmp.path(), "helvR12.bdf");
+
+        let fonts = list_available_fonts(tmp.path());
+        assert!(fonts.contains(&bdf::FALLBACK_FONT.to_string()));
+        assert!(fonts.contains(&"helvR12".to_string()));
+        assert_eq!(fonts.iter().filter(|f| *f == "6x10").count(), 1);
+
+        let missing = list_available_fonts(&tmp.path().join("missing"));
+        assert!(missing.contains(&bdf::FALLBACK_FONT.to_string()));
+    }
+
     #[test]
     fn list_fonts_returns_empty_when_no_dir() {
         let tmp = TempDir::new().unwrap();
         let fonts = list_fonts(tmp.path());
         assert!(fonts.is_empty());
     }
+
+    #[test]
+    fn prepare_media_dir_creates_missing_subdirs() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir(tmp.path().join("images")).unwrap();
+
+        let created = prepare_media_dir(tmp.path()).unwrap();
+
+        assert_eq!(
+            created,
+            vec![tmp.path().join("videos"), tmp.path().join("fonts/bdf")]
+        );
+        assert!(tmp.path().join("fonts/bdf").is_dir());
+        assert!(prepare_media_dir(tmp.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn prepare_media_dir_rejects_missing_or_file() {
+        let tmp = TempDir::new().unwrap();
+        create_file(tmp.path(), "not_a_dir");
+
+        assert!(prepare_media_dir(&tmp.path().join("missing")).is_err());
+        let err = prepare_media_dir(&tmp.path().join("not_a_dir")).unwrap_err();
+        assert!(err.contains("is not a directory"));
+    }
+
+    fn entry(name: &str, size: u64, modified: u64) -> MediaEntry {
+        MediaEntry {
+            name: name.to_string(),
+            path: format!("images/{name}"),
+            size,
+            modified,
+        }
+    }
+
+    fn names(entries: &[MediaEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    fn sample() -> Vec<MediaEntry> {
+        vec![
+            entry("cat.png", 300, 20),
+            entry("bird.png", 100, 30),
+            entry("cow.png", 300, 10),
+            entry("ant.png", 200, 40),
+        ]
+    }
+
+    #[test]
+    fn page_sorts_by_each_key() {
+        let (by_name, _) = page(sample(), "", SortKey::Name, 0, None);
+        assert_eq!(
+            names(&by_name),
+            ["ant.png", "bird.png", "cat.png", "cow.png"]
+        );
+
+        // Equal sizes fall back to name order
+        let (by_size, _) = page(sample(), "", SortKey::Size, 0, None);
+        assert_eq!(
+            names(&by_size),
+            ["cat.png", "cow.png", "ant.png", "bird.png"]
+        );
+
+        let (by_mtime, _) = page(sample(), "", SortKey::Mtime, 0, None);
+        assert_eq!(
+            names(&by_mtime),
+            ["ant.png", "bird.png", "cat.png", "cow.png"]
+        );
+    }
+
+    #[test]
+    fn page_filters_then_counts_then_pages() {
+        let (entries, total) = page(sample(), "c", SortKey::Name, 1, Some(5));
+        assert_eq!(total, 2);
+        assert_eq!(names(&entries), ["cow.png"]);
+
+        let (entries, total) = page(sample(), "", SortKey::Name, 1, Some(2));
+        assert_eq!(total, 4);
+        assert_eq!(names(&entries), ["bird.png", "cat.png"]);
+
+        let (entries, total) = page(sample(), "", SortKey::Name, 10, None);
+        assert_eq!(total, 4);
+        assert!(entries.is_empty());
+    }
+
+    #[test]
+    fn natural_cmp_compares_numbers_by_value() {
+        let mut names = vec![
+            "img10.png",
+            "img2.png",
+            "img1.png",
+            "b.png",
+            "img02.png",
+            "a.png",
+        ];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            names,
+            [
+                "a.png",
+                "b.png",
+                "img1.png",
+                "img2.png",
+                "img02.png",
+                "img10.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn slideshow_images_are_in_natural_order() {
+        let tmp = TempDir::new().unwrap();
+        create_file(tmp.path(), "slide10.jpg");
+        create_file(tmp.path(), "slide9.png");
+        create_file(tmp.path(), "notes.txt"); // should be excluded
+        std::fs::create_dir(tmp.path().join("more.png")).unwrap(); // not a file
+
+        let names: Vec<_> = slideshow_images(tmp.path())
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["slide9.png", "slide10.jpg"]);
+    }
+
+    #[test]
+    fn shuffle_is_a_permutation_that_depends_on_the_seed() {
+        let original: Vec<u32> = (0..20).collect();
+        let mut a = original.clone();
+        let mut b = original.clone();
+        shuffle(&mut a, 1);
+        shuffle(&mut b, 2);
+
+        assert_ne!(a, original);
+        assert_ne!(a, b);
+        a.sort_unstable();
+        assert_eq!(a, original);
+
+        let mut again = original.clone();
+        shuffle(&mut again, 2);
+        assert_eq!(again, b);
+    }
 }
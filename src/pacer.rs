@@ -0,0 +1,115 @@
+//! A hard ceiling on how often the render thread swaps frames.
+//!
+//! Some panels flicker when frames are swapped in faster than they can
+//! refresh, whatever the content asks for. `--max-refresh-fps` sets a
+//! minimum interval between swaps for every mode at once: videos, effects
+//! and streams alike. A swap that comes early waits out the rest of the
+//! interval, which slows the loop producing the frames, so extra frames are
+//! dropped (videos skip ahead, streams keep only the newest) rather than
+//! queued up behind the panel.
+//!
+//! ## Rust concepts
+//! - `Instant` arithmetic with `saturating_duration_since`, which can't
+//!   panic when the clock readings are out of order
+//! - Passing `now` in, rather than reading the clock, so tests can control time
+
+use std::time::{Duration, Instant};
+
+/// Spaces swaps at least `min_interval` apart.
+#[derive(Clone, Debug)]
+pub struct Pacer {
+    min_interval: Duration,
+    last_swap: Option<Instant>,
+}
+
+impl Pacer {
+    /// A pacer allowing at most `max_fps` swaps per second; `None` or zero
+    /// means no limit.
+    pub fn new(max_fps: Option<u32>) -> Self {
+        Self {
+            min_interval: min_interval(max_fps),
+            last_swap: None,
+        }
+    }
+
+    /// Change the limit, e.g. after the settings file is reloaded. The next
+    /// swap is spaced from the last one by the new interval.
+    pub fn set_max_fps(&mut self, max_fps: Option<u32>) {
+        self.min_interval = min_interval(max_fps);
+    }
+
+    /// How long after `now` the next swap is allowed.
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.last_swap.map_or(Duration::ZERO, |last| {
+            self.min_interval
+                .saturating_sub(now.saturating_duration_since(last))
+        })
+    }
+
+    /// How long to wait before swapping at `now`, recording the swap as
+    /// happening once that wait is over.
+    pub fn delay(&mut self, now: Instant) -> Duration {
+        let wait = self.remaining(now);
+        self.last_swap = Some(now + wait);
+        wait
+    }
+
+    /// Wait until the next swap is allowed.
+    pub fn wait(&mut self) {
+        let wait = self.delay(Instant::now());
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+fn min_interval(max_fps: Option<u32>) -> Duration {
+    max_fps
+        .filter(|&fps| fps > 0)
+        .map_or(Duration::ZERO, |fps| Duration::from_secs(1) / fps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn enforces_a_minimum_interval_between_swaps() {
+        let mut pacer = Pacer::new(Some(50));
+        let start = Instant::now();
+
+        assert_eq!(pacer.delay(start), Duration::ZERO);
+        assert_eq!(pacer.remaining(start + 5 * MS), 15 * MS);
+        // 5ms later is 15ms too early for 50 fps
+        assert_eq!(pacer.delay(start + 5 * MS), 15 * MS);
+        // That swap counts as happening at 20ms, so the next is due at 40ms
+        assert_eq!(pacer.delay(start + 30 * MS), 10 * MS);
+        // Late swaps go straight through
+        assert_eq!(pacer.delay(start + 100 * MS), Duration::ZERO);
+    }
+
+    #[test]
+    fn no_limit_never_waits() {
+        let start = Instant::now();
+        for max_fps in [None, Some(0)] {
+            let mut pacer = Pacer::new(max_fps);
+            assert_eq!(pacer.delay(start), Duration::ZERO);
+            assert_eq!(pacer.delay(start), Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn limit_can_change_between_swaps() {
+        let mut pacer = Pacer::new(Some(50));
+        let start = Instant::now();
+        pacer.delay(start);
+
+        pacer.set_max_fps(Some(10));
+        assert_eq!(pacer.remaining(start + 20 * MS), 80 * MS);
+        pacer.set_max_fps(None);
+        assert_eq!(pacer.remaining(start + 20 * MS), Duration::ZERO);
+    }
+}
@@ -0,0 +1,398 @@
+//! Custom pixel mapping loaded from a file.
+//!
+//! The matrix library's named pixel mappers (U-mapper, rotation, ...) cover
+//! common panel arrangements, but not arbitrary ones such as LED strips
+//! wired into a grid in a nonstandard order. A `PixelMap` is a lookup table
+//! from each logical `(x, y)` the renderer draws to the physical `(x, y)`
+//! on the canvas.
+//!
+//! ## File format
+//! One mapping per line: `logical_x logical_y physical_x physical_y`,
+//! separated by spaces or commas. Blank lines and lines starting with `#`
+//! are ignored. Logical positions that aren't listed map to themselves.
+//!
+//! ```text
+//! # Swap the first two pixels of row 0
+//! 0 0 1 0
+//! 1 0 0 0
+//! ```
+//!
+//! ## Symmetry
+//! A map can also mirror part of the panel into the rest (`--symmetry`):
+//! pixels in the source half or quadrant are drawn at their reflections
+//! too, and pixels outside it aren't drawn, so a client only has to draw
+//! the top-left of the panel.
+//!
+//! ## Coordinates
+//! `for_display` builds the one map every draw goes through, from the
+//! remap file, the `--safe-inset` border, and `--symmetry`.
+//! `logical_to_physical` and `physical_to_logical` convert through it, so
+//! code that needs to know where a pixel lands doesn't redo the math.
+//! Chaining is left to the matrix library: `--rows` and `--cols` already
+//! describe the whole chain as one canvas.
+//!
+//! ## Rust concepts
+//! - A flat `Vec` indexed by `y * width + x` as a 2D lookup table
+//! - `str::split` with a closure to accept several separators
+//! - Early returns with `?` and formatted error strings
+//! - Returning `impl Iterator` over a fixed-size array of `Option`s, so
+//!   per-pixel lookups don't allocate
+
+use crate::PanelConfig;
+use std::path::Path;
+
+/// How the panel is mirrored at draw time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Symmetry {
+    /// Draw every pixel as sent
+    #[default]
+    None,
+    /// Mirror the left half onto the right
+    Horizontal,
+    /// Mirror the top half onto the bottom
+    Vertical,
+    /// Mirror the top-left quadrant into the other three
+    Kaleidoscope,
+}
+
+impl Symmetry {
+    /// Logical positions that `(x, y)` is drawn at on a `width` x `height`
+    /// panel: itself and its reflections if it's in the source region,
+    /// nothing if it's in a mirrored one. On an odd-sized panel the middle
+    /// row or column belongs to the source region and is drawn once.
+    pub fn reflect(
+        self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> impl Iterator<Item = (u32, u32)> {
+        let (mirror_x, mirror_y) = match self {
+            Self::None => (false, false),
+            Self::Horizontal => (true, false),
+            Self::Vertical => (false, true),
+            Self::Kaleidoscope => (true, true),
+        };
+        let in_panel = x < width && y < height;
+        let in_source = !in_panel
+            || ((!mirror_x || x < width.div_ceil(2)) && (!mirror_y || y < height.div_ceil(2)));
+
+        let flipped_x = (mirror_x && in_panel && width - 1 - x != x).then(|| width - 1 - x);
+        let flipped_y = (mirror_y && in_panel && height - 1 - y != y).then(|| height - 1 - y);
+        let positions = [
+            Some((x, y)),
+            flipped_x.map(|fx| (fx, y)),
+            flipped_y.map(|fy| (x, fy)),
+            flipped_x.zip(flipped_y),
+        ];
+        positions.into_iter().flatten().filter(move |_| in_source)
+    }
+}
+
+/// Lookup table from logical panel positions to physical canvas positions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PixelMap {
+    width: u32,
+    height: u32,
+    /// Physical position for each logical one, indexed by `y * width + x`
+    targets: Vec<(i32, i32)>,
+    /// Mirroring applied before the lookup
+    symmetry: Symmetry,
+}
+
+impl PixelMap {
+    /// The identity map: every logical position maps to itself.
+    pub fn identity(panel: PanelConfig) -> Self {
+        let targets = (0..panel.rows)
+            .flat_map(|y| (0..panel.cols).map(move |x| (x as i32, y as i32)))
+            .collect();
+        Self {
+            width: panel.cols,
+            height: panel.rows,
+            targets,
+            symmetry: Symmetry::None,
+        }
+    }
+
+    /// The map to draw through on a `matrix`-sized canvas: `remap` (or the
+    /// identity), shifted inside a border `inset` pixels wide, then mirrored
+    /// by `symmetry`. `None` when every pixel lands on itself, so drawing
+    /// can skip the lookup.
+    pub fn for_display(
+        matrix: PanelConfig,
+        remap: Option<Self>,
+        inset: u32,
+        symmetry: Symmetry,
+    ) -> Result<Option<Self>, String> {
+        let map = match (remap, inset) {
+            (map, 0) => map,
+            (map, inset) => Some(
+                map.unwrap_or_else(|| Self::identity(matrix))
+                    .inset(inset)
+                    .ok_or_else(|| {
+                        format!(
+                            "A {inset}px inset leaves nothing of a {}x{} panel",
+                            matrix.cols, matrix.rows
+                        )
+                    })?,
+            ),
+        };
+        // Mirrored in the drawable area, after the inset
+        Ok(match symmetry {
+            Symmetry::None => map,
+            symmetry => {
+                let drawable = matrix.inset(inset).unwrap_or(matrix);
+                Some(
+                    map.unwrap_or_else(|| Self::identity(drawable))
+                        .with_symmetry(symmetry),
+                )
+            }
+        })
+    }
+
+    /// Read and parse a pixel map file for a panel.
+    pub fn load(path: &Path, panel: PanelConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let source = std::fs::read_to_string(path)?;
+        Self::parse(&source, panel)
+    }
+
+    /// Parse pixel map source text for a panel.
+    pub fn parse(source: &str, panel: PanelConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut map = Self::identity(panel);
+
+        for (index, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let line_no = index + 1;
+            let values = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|part| !part.is_empty())
+                .map(|part| part.parse::<i32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Line {line_no}: {e}"))?;
+
+            let [lx, ly, px, py] = values[..] else {
+                return Err(format!(
+                    "Line {line_no}: expected 4 numbers (logical x y, physical x y), got {}",
+                    values.len()
+                )
+                .into());
+            };
+
+            if lx < 0 || ly < 0 || lx as u32 >= map.width || ly as u32 >= map.height {
+                return Err(format!(
+                    "Line {line_no}: logical position ({lx}, {ly}) is outside the {}x{} panel",
+                    map.width, map.height
+                )
+                .into());
+            }
+
+            let i = (ly as u32 * map.width + lx as u32) as usize;
+            map.targets[i] = (px, py);
+        }
+
+        Ok(map)
+    }
+
+    /// The map for the area inside a border `pixels` wide, for `--safe-inset`:
+    /// logical `(0, 0)` is this map's `(pixels, pixels)`. The border itself
+    /// is never drawn. `None` if the border covers the whole panel.
+    pub fn inset(&self, pixels: u32) -> Option<Self> {
+        let inner = PanelConfig::new(self.height, self.width).inset(pixels)?;
+        let targets = (0..inner.rows)
+            .flat_map(|y| (0..inner.cols).map(move |x| self.get(x + pixels, y + pixels)))
+            .collect();
+        Some(Self {
+            width: inner.cols,
+            height: inner.rows,
+            targets,
+            symmetry: self.symmetry,
+        })
+    }
+
+    /// This map with `symmetry` applied to logical positions first.
+    pub fn with_symmetry(self, symmetry: Symmetry) -> Self {
+        Self { symmetry, ..self }
+    }
+
+    /// Physical position for logical `(x, y)`. Positions outside the panel
+    /// map to themselves.
+    pub fn get(&self, x: u32, y: u32) -> (i32, i32) {
+        if x >= self.width || y >= self.height {
+            return (x as i32, y as i32);
+        }
+        self.targets[(y * self.width + x) as usize]
+    }
+
+    /// Every physical position logical `(x, y)` is drawn at: one without
+    /// symmetry, up to four with it, none for a pixel in a mirrored region.
+    pub fn targets(&self, x: u32, y: u32) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.symmetry
+            .reflect(x, y, self.width, self.height)
+            .map(|(x, y)| self.get(x, y))
+    }
+}
+
+/// Every physical position logical `(x, y)` is drawn at through `map`, or
+/// just `(x, y)` without one.
+pub fn logical_to_physical(
+    map: Option<&PixelMap>,
+    x: u32,
+    y: u32,
+) -> impl Iterator<Item = (i32, i32)> + '_ {
+    let unmapped = map.is_none().then_some((x as i32, y as i32));
+    map.into_iter()
+        .flat_map(move |map| map.targets(x, y))
+        .chain(unmapped)
+}
+
+/// The logical position drawn at physical `(px, py)` through `map`: with
+/// symmetry, the source pixel whose reflection lands there. `None` if
+/// nothing is drawn there, such as the inset border. Searches the whole
+/// map, so it's for tools and tests rather than per-frame use.
+pub fn physical_to_logical(map: Option<&PixelMap>, px: i32, py: i32) -> Option<(u32, u32)> {
+    let Some(map) = map else {
+        return Some((u32::try_from(px).ok()?, u32::try_from(py).ok()?));
+    };
+    (0..map.height)
+        .flat_map(|y| (0..map.width).map(move |x| (x, y)))
+        .find(|&(x, y)| map.targets(x, y).any(|target| target == (px, py)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn panel() -> PanelConfig {
+        PanelConfig::new(2, 3)
+    }
+
+    #[test]
+    fn identity_maps_to_self() {
+        let map = PixelMap::identity(panel());
+        assert_eq!(map.get(0, 0), (0, 0));
+        assert_eq!(map.get(2, 1), (2, 1));
+    }
+
+    #[test]
+    fn parses_mappings_and_keeps_unlisted_positions() {
+        let source = "# swap two pixels\n0 0 1 0\n1,0, 0,0\n\n2 1 -1 5\n";
+        let map = PixelMap::parse(source, panel()).unwrap();
+        assert_eq!(map.get(0, 0), (1, 0));
+        assert_eq!(map.get(1, 0), (0, 0));
+        assert_eq!(map.get(2, 1), (-1, 5));
+        assert_eq!(map.get(0, 1), (0, 1));
+    }
+
+    #[test]
+    fn positions_outside_the_panel_pass_through() {
+        let map = PixelMap::parse("0 0 2 1", panel()).unwrap();
+        assert_eq!(map.get(10, 10), (10, 10));
+    }
+
+    #[test]
+    fn inset_shifts_positions_through_the_original_map() {
+        let map = PixelMap::parse("1 1 0 0", PanelConfig::new(4, 4)).unwrap();
+        let inset = map.inset(1).unwrap();
+        assert_eq!(inset.get(0, 0), (0, 0)); // was (1, 1)
+        assert_eq!(inset.get(1, 1), (2, 2));
+        assert!(map.inset(2).is_none());
+    }
+
+    #[rstest]
+    #[case("0 0 1")] // too few values
+    #[case("0 0 1 1 1")] // too many values
+    #[case("0 0 a 1")] // not a number
+    #[case("3 0 0 0")] // logical x out of range
+    #[case("0 -1 0 0")] // negative logical y
+    fn rejects_invalid_lines(#[case] source: &str) {
+        assert!(PixelMap::parse(source, panel()).is_err());
+    }
+
+    fn drawn_at(map: &PixelMap, x: u32, y: u32) -> Vec<(i32, i32)> {
+        let mut targets: Vec<_> = map.targets(x, y).collect();
+        targets.sort_unstable();
+        targets
+    }
+
+    #[rstest]
+    #[case(Symmetry::None, vec![(0, 0)])]
+    #[case(Symmetry::Horizontal, vec![(0, 0), (3, 0)])]
+    #[case(Symmetry::Vertical, vec![(0, 0), (0, 2)])]
+    #[case(Symmetry::Kaleidoscope, vec![(0, 0), (0, 2), (3, 0), (3, 2)])]
+    fn symmetry_draws_source_pixels_at_their_reflections(
+        #[case] symmetry: Symmetry,
+        #[case] expected: Vec<(i32, i32)>,
+    ) {
+        let map = PixelMap::identity(PanelConfig::new(3, 4)).with_symmetry(symmetry);
+        assert_eq!(drawn_at(&map, 0, 0), expected);
+    }
+
+    #[test]
+    fn symmetry_skips_mirrored_pixels_and_draws_the_middle_once() {
+        // 3 rows: row 1 is the middle and belongs to the source half
+        let map = PixelMap::identity(PanelConfig::new(3, 4)).with_symmetry(Symmetry::Kaleidoscope);
+        assert_eq!(drawn_at(&map, 3, 0), vec![]);
+        assert_eq!(drawn_at(&map, 0, 2), vec![]);
+        assert_eq!(drawn_at(&map, 1, 1), vec![(1, 1), (2, 1)]);
+    }
+
+    #[rstest]
+    #[case(None, 0, Symmetry::None)]
+    #[case(Some("0 0 3 3\n3 3 0 0"), 0, Symmetry::None)]
+    #[case(None, 1, Symmetry::None)]
+    #[case(Some("1 1 2 2\n2 2 1 1"), 1, Symmetry::Horizontal)]
+    #[case(None, 0, Symmetry::Kaleidoscope)]
+    fn physical_to_logical_inverts_logical_to_physical(
+        #[case] remap: Option<&str>,
+        #[case] inset: u32,
+        #[case] symmetry: Symmetry,
+    ) {
+        let matrix = PanelConfig::new(4, 4);
+        let remap = remap.map(|source| PixelMap::parse(source, matrix).unwrap());
+        let map = PixelMap::for_display(matrix, remap, inset, symmetry).unwrap();
+        let drawable = matrix.inset(inset).unwrap();
+
+        for y in 0..drawable.rows {
+            for x in 0..drawable.cols {
+                for (px, py) in logical_to_physical(map.as_ref(), x, y) {
+                    assert_eq!(physical_to_logical(map.as_ref(), px, py), Some((x, y)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn for_display_needs_no_map_for_a_plain_panel() {
+        let matrix = PanelConfig::new(4, 4);
+        assert_eq!(
+            PixelMap::for_display(matrix, None, 0, Symmetry::None),
+            Ok(None)
+        );
+        assert!(PixelMap::for_display(matrix, None, 2, Symmetry::None).is_err());
+    }
+
+    #[test]
+    fn inset_border_has_no_logical_position() {
+        let map = PixelMap::for_display(PanelConfig::new(4, 4), None, 1, Symmetry::None).unwrap();
+        assert_eq!(
+            logical_to_physical(map.as_ref(), 0, 0).collect::<Vec<_>>(),
+            [(1, 1)]
+        );
+        assert_eq!(physical_to_logical(map.as_ref(), 0, 0), None);
+        assert_eq!(physical_to_logical(map.as_ref(), 2, 1), Some((1, 0)));
+    }
+
+    #[test]
+    fn symmetry_reflects_before_the_lookup() {
+        let map = PixelMap::parse("3 0 9 9", PanelConfig::new(1, 4))
+            .unwrap()
+            .with_symmetry(Symmetry::Horizontal);
+        assert_eq!(drawn_at(&map, 0, 0), vec![(0, 0), (9, 9)]);
+    }
+}
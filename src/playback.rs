@@ -0,0 +1,86 @@
+//! Counting a video's passes, and turning looping on or off mid-playback.
+//!
+//! `POST /api/v1/display/loop` reaches the render thread while a video is
+//! playing. Turning looping off doesn't cut the video short: the pass that's
+//! playing finishes and the video stops there, as if it had been started
+//! with a loop count of exactly that many passes. Turning it on keeps the
+//! video going after the current pass, however many it was started with.
+//!
+//! ## Rust concepts
+//! - `Option<u32>` for "forever" (`None`) or a fixed number of passes
+//! - `is_none_or` to test an optional limit in one expression
+
+/// How many passes of a video have played, and how many it should play.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Passes {
+    played: u32,
+    limit: Option<u32>,
+}
+
+impl Passes {
+    /// No passes played yet, stopping after `limit` of them (`None` loops
+    /// forever).
+    pub fn new(limit: Option<u32>) -> Self {
+        Self { played: 0, limit }
+    }
+
+    /// Whether the video keeps looping once this pass ends.
+    pub fn looping(&self) -> bool {
+        self.limit.is_none()
+    }
+
+    /// Loop forever, or finish the pass that's playing and stop.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.limit = if looping { None } else { Some(self.played + 1) };
+    }
+
+    /// Count the pass that just ended; returns whether to play another.
+    pub fn finish_pass(&mut self) -> bool {
+        self.played += 1;
+        self.limit.is_none_or(|limit| self.played < limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Finish passes until the video stops, returning how many played.
+    fn play_out(passes: &mut Passes) -> u32 {
+        let mut played = 1;
+        while passes.finish_pass() {
+            played += 1;
+        }
+        played
+    }
+
+    #[test]
+    fn plays_the_requested_number_of_passes() {
+        assert_eq!(play_out(&mut Passes::new(Some(3))), 3);
+        // A loop count of zero still plays the video once
+        assert_eq!(play_out(&mut Passes::new(Some(0))), 1);
+    }
+
+    #[test]
+    fn turning_looping_off_finishes_the_current_pass() {
+        let mut passes = Passes::new(None);
+        assert!(passes.finish_pass());
+        assert!(passes.finish_pass());
+
+        // Third pass playing
+        passes.set_looping(false);
+        assert!(!passes.looping());
+        assert!(!passes.finish_pass());
+    }
+
+    #[test]
+    fn turning_looping_on_continues_past_the_loop_count() {
+        let mut passes = Passes::new(Some(1));
+        passes.set_looping(true);
+        assert!(passes.looping());
+        for _ in 0..10 {
+            assert!(passes.finish_pass());
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Named presets persisted to a JSON file.
+//!
+//! A preset is any serializable value stored under a short name — the
+//! server uses it for saved display commands. The whole set is kept in
+//! memory and rewritten to disk on every change, which is fine for the
+//! handful of presets a panel has.
+//!
+//! ## Rust concepts
+//! - Generic structs with trait bounds (`T: Serialize + DeserializeOwned`)
+//! - `BTreeMap` for a map that iterates in sorted key order
+//! - Write-then-rename so a crash never leaves a half-written file
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Longest allowed preset name.
+pub const MAX_NAME_LEN: usize = 64;
+
+/// Check that a preset name is 1-64 ASCII letters, digits, `-` or `_`, so
+/// it's safe to use in URLs.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(format!(
+            "Preset name must be 1-{MAX_NAME_LEN} characters long"
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err("Preset name may only contain letters, digits, '-' and '_'".to_string());
+    }
+    Ok(())
+}
+
+/// Presets keyed by name, backed by a JSON file.
+pub struct PresetStore<T> {
+    path: PathBuf,
+    presets: BTreeMap<String, T>,
+}
+
+impl<T: Serialize + DeserializeOwned> PresetStore<T> {
+    /// Load presets from `path`. A missing file is an empty store; the file
+    /// is created on the first `insert`.
+    pub fn load(path: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        let presets = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Invalid presets file {}: {e}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self { path, presets })
+    }
+
+    /// All presets in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &T)> {
+        self.presets.iter()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&T> {
+        self.presets.get(name)
+    }
+
+    /// Add or replace a preset and write the file.
+    ///
+    /// If writing fails, the in-memory set is left unchanged.
+    pub fn insert(&mut self, name: String, value: T) -> Result<(), Box<dyn std::error::Error>> {
+        let previous = self.presets.insert(name.clone(), value);
+        if let Err(e) = self.save() {
+            match previous {
+                Some(old) => self.presets.insert(name, old),
+                None => self.presets.remove(&name),
+            };
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::to_string_pretty(&self.presets)?;
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use tempfile::TempDir;
+
+    #[rstest]
+    #[case("morning", true)]
+    #[case("clock_2-b", true)]
+    #[case("", false)]
+    #[case("has space", false)]
+    #[case("../etc", false)]
+    #[case(&"x".repeat(65), false)]
+    fn test_validate_name(#[case] name: &str, #[case] valid: bool) {
+        assert_eq!(validate_name(name).is_ok(), valid);
+    }
+
+    #[test]
+    fn missing_file_is_empty_store() {
+        let tmp = TempDir::new().unwrap();
+        let store: PresetStore<u32> = PresetStore::load(tmp.path().join("presets.json")).unwrap();
+        assert_eq!(store.iter().count(), 0);
+    }
+
+    #[test]
+    fn insert_persists_across_loads() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("presets.json");
+
+        let mut store = PresetStore::load(path.clone()).unwrap();
+        store.insert("b".to_string(), 2u32).unwrap();
+        store.insert("a".to_string(), 1u32).unwrap();
+        store.insert("b".to_string(), 3u32).unwrap();
+
+        let reloaded: PresetStore<u32> = PresetStore::load(path).unwrap();
+        let entries: Vec<_> = reloaded.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        assert_eq!(entries, vec![("a", 1), ("b", 3)]);
+        assert_eq!(reloaded.get("a"), Some(&1));
+    }
+
+    #[test]
+    fn failed_write_leaves_store_unchanged() {
+        let tmp = TempDir::new().unwrap();
+        let mut store = PresetStore::load(tmp.path().join("missing/presets.json")).unwrap();
+
+        assert!(store.insert("a".to_string(), 1u32).is_err());
+        assert_eq!(store.get("a"), None);
+    }
+
+    #[test]
+    fn invalid_file_is_an_error() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("presets.json");
+        fs::write(&path, "not json").unwrap();
+        assert!(PresetStore::<u32>::load(path).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+//! Command channel that counts what goes through it.
+//!
+//! The render thread reads commands from an unbounded `mpsc` channel, so a
+//! fast streaming client can queue frames faster than the panel shows them
+//! without any error. Both ends of this channel bump a shared counter: the
+//! difference between commands sent and commands taken off the queue is the
+//! backlog, which clients can read from the status endpoint and slow down
+//! before it grows. Each message also carries the time it was sent, so the
+//! receiver can tell how long it waited in the queue.
+//!
+//! A receiver that keeps a message up for a while can find newer ones
+//! waiting when it's done. `supersede` decides which of them still runs, so
+//! a burst of messages coalesces to the latest.
+//!
+//! ## Rust concepts
+//! - Wrapping a standard type (newtype) to add behavior around its methods
+//! - `AtomicU64` counters shared between threads without a lock
+//! - Generic structs, so the channel works for any message type
+//! - `Cell` for a value the receiver updates through `&self`
+
+use std::cell::Cell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{
+    self, Receiver, RecvError, RecvTimeoutError, SendError, Sender, TryRecvError,
+};
+use std::time::{Duration, Instant};
+
+/// Counters shared by both ends of a channel.
+#[derive(Debug, Default)]
+struct Counters {
+    sent: AtomicU64,
+    received: AtomicU64,
+}
+
+/// Sending half of a counting channel. Cheap to clone, like `Sender`.
+#[derive(Debug)]
+pub struct CountingSender<T> {
+    tx: Sender<(T, Instant)>,
+    counters: Arc<Counters>,
+}
+
+/// Receiving half of a counting channel.
+#[derive(Debug)]
+pub struct CountingReceiver<T> {
+    rx: Receiver<(T, Instant)>,
+    counters: Arc<Counters>,
+    /// When the last message taken off the queue was sent
+    last_sent_at: Cell<Option<Instant>>,
+}
+
+/// Create an unbounded channel whose ends count messages sent and received.
+pub fn counting_channel<T>() -> (CountingSender<T>, CountingReceiver<T>) {
+    let (tx, rx) = mpsc::channel();
+    let counters = Arc::new(Counters::default());
+    (
+        CountingSender {
+            tx,
+            counters: counters.clone(),
+        },
+        CountingReceiver {
+            rx,
+            counters,
+            last_sent_at: Cell::new(None),
+        },
+    )
+}
+
+// `#[derive(Clone)]` would require `T: Clone`, which the sender doesn't need
+impl<T> Clone for CountingSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            counters: self.counters.clone(),
+        }
+    }
+}
+
+impl<T> CountingSender<T> {
+    /// Send a message; only messages that reach the channel are counted.
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.tx
+            .send((msg, Instant::now()))
+            .map_err(|SendError((msg, _))| SendError(msg))?;
+        self.counters.sent.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// `(sent, received)` since the channel was created.
+    pub fn counts(&self) -> (u64, u64) {
+        // The receiver can count a message before `send` has counted it;
+        // never report more received than sent
+        let sent = self.counters.sent.load(Ordering::Relaxed);
+        let received = self.counters.received.load(Ordering::Relaxed);
+        (sent.max(received), received)
+    }
+}
+
+impl<T> CountingReceiver<T> {
+    /// Block until a message arrives, like `Receiver::recv`.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        Ok(self.taken(self.rx.recv()?))
+    }
+
+    /// Wait up to `timeout` for a message, like `Receiver::recv_timeout`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        Ok(self.taken(self.rx.recv_timeout(timeout)?))
+    }
+
+    /// Take a message if one is waiting, like `Receiver::try_recv`.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        Ok(self.taken(self.rx.try_recv()?))
+    }
+
+    /// When the message most recently taken off the queue was sent, or
+    /// `None` before the first one.
+    pub fn last_sent_at(&self) -> Option<Instant> {
+        self.last_sent_at.get()
+    }
+
+    fn taken(&self, (msg, sent_at): (T, Instant)) -> T {
+        self.counters.received.fetch_add(1, Ordering::Relaxed);
+        self.last_sent_at.set(Some(sent_at));
+        msg
+    }
+}
+
+/// A message taken off the queue, with when it was sent.
+///
+/// The receiver only remembers when the message it took last was sent, so
+/// a message put aside to handle later keeps its own send time.
+#[derive(Debug, PartialEq)]
+pub struct Queued<T> {
+    pub msg: T,
+    /// `None` for a message the receiver made itself, like a restore
+    pub sent_at: Option<Instant>,
+}
+
+impl<T> Queued<T> {
+    /// `msg`, just taken off `rx`.
+    pub fn received(msg: T, rx: &CountingReceiver<T>) -> Self {
+        Self {
+            msg,
+            sent_at: rx.last_sent_at(),
+        }
+    }
+
+    /// `msg`, made by the receiver rather than sent to it.
+    pub fn made(msg: T) -> Self {
+        Self { msg, sent_at: None }
+    }
+}
+
+/// What became of a held message when a newer one took its place.
+#[derive(Debug, PartialEq)]
+pub enum Superseded<T> {
+    /// The newer message runs first, and this one after it
+    RunsAfter(Queued<T>),
+    /// Replaced by the newer message, so it never runs
+    Dropped(Queued<T>),
+}
+
+/// Put `newer`, which arrived while `held` was kept up, in `held`'s place.
+///
+/// Messages replace each other, so rapid ones coalesce to the latest. A
+/// message `is_adjustment` picks out only jumps ahead: `held` is handed back
+/// to run after it.
+pub fn supersede<T>(
+    held: &mut Queued<T>,
+    newer: Queued<T>,
+    is_adjustment: impl FnOnce(&Queued<T>) -> bool,
+) -> Superseded<T> {
+    let adjustment = is_adjustment(&newer);
+    let replaced = std::mem::replace(held, newer);
+    if adjustment {
+        Superseded::RunsAfter(replaced)
+    } else {
+        Superseded::Dropped(replaced)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn counts_sent_and_received_messages() {
+        let (tx, rx) = counting_channel();
+        tx.send(1).unwrap();
+        tx.clone().send(2).unwrap();
+        assert_eq!(tx.counts(), (2, 0));
+
+        assert_eq!(rx.recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Ok(2));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(
+            rx.recv_timeout(Duration::ZERO),
+            Err(RecvTimeoutError::Timeout)
+        );
+        assert_eq!(tx.counts(), (2, 2));
+    }
+
+    #[test]
+    fn failed_sends_are_not_counted() {
+        let (tx, rx) = counting_channel();
+        drop(rx);
+        assert!(tx.send(1).is_err());
+        assert_eq!(tx.counts(), (0, 0));
+    }
+
+    #[test]
+    fn remembers_when_the_last_message_was_sent() {
+        let (tx, rx) = counting_channel();
+        assert_eq!(rx.last_sent_at(), None);
+
+        let before = Instant::now();
+        tx.send(1).unwrap();
+        rx.recv().unwrap();
+        let sent_at = rx.last_sent_at().unwrap();
+        assert!(sent_at >= before && sent_at <= Instant::now());
+    }
+
+    #[test]
+    fn held_messages_coalesce_to_the_latest() {
+        // Negative numbers stand in for adjustments
+        let is_adjustment = |queued: &Queued<i32>| queued.msg < 0;
+        let mut held = Queued::made(1);
+
+        // Each newer message replaces the held one
+        let dropped = supersede(&mut held, Queued::made(2), is_adjustment);
+        assert_eq!(dropped, Superseded::Dropped(Queued::made(1)));
+        let dropped = supersede(&mut held, Queued::made(3), is_adjustment);
+        assert_eq!(dropped, Superseded::Dropped(Queued::made(2)));
+        assert_eq!(held, Queued::made(3));
+
+        // An adjustment runs first and hands the held message back
+        let next = supersede(&mut held, Queued::made(-1), is_adjustment);
+        assert_eq!(next, Superseded::RunsAfter(Queued::made(3)));
+        assert_eq!(held, Queued::made(-1));
+    }
+
+    #[test]
+    fn queued_messages_keep_their_send_time() {
+        let (tx, rx) = counting_channel();
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        let first = Queued::received(rx.recv().unwrap(), &rx);
+        let second = Queued::received(rx.recv().unwrap(), &rx);
+        assert!(first.sent_at.unwrap() <= second.sent_at.unwrap());
+        assert_eq!(Queued::made(3).sent_at, None);
+    }
+}
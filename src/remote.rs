@@ -0,0 +1,152 @@
+//! Safety checks for fetching images from client-supplied URLs.
+//!
+//! Letting any client make the Pi fetch any URL is a server-side request
+//! forgery (SSRF) risk: the request would come from inside the LAN, so it
+//! could reach the router's admin page or other services that trust local
+//! traffic. Before fetching, the server resolves the host and checks every
+//! address it resolves to with `check_fetch_target`.
+//!
+//! Hosts listed with `--image-url-host` are trusted even on the LAN (e.g. a
+//! local camera). When the list is non-empty, only those hosts are allowed.
+//!
+//! ## Rust concepts
+//! - `std::net::IpAddr` and its classification helpers (`is_loopback`, ...)
+//! - Matching on enum variants that wrap different types (`V4`/`V6`)
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Whether `ip` is a globally routable address rather than loopback,
+/// private, link-local, or otherwise special.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_public_ipv4(v4),
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped().or_else(|| embedded_ipv4(v6)) {
+            Some(v4) => is_public_ipv4(v4),
+            None => is_public_ipv6(v6),
+        },
+    }
+}
+
+/// The IPv4 address a NAT64 (64:ff9b::/96) or 6to4 (2002::/16) address
+/// reaches, which is what the check has to look at.
+fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let octets = ip.octets();
+    match ip.segments() {
+        [0x64, 0xff9b, 0, 0, 0, 0, ..] => Some(Ipv4Addr::new(
+            octets[12], octets[13], octets[14], octets[15],
+        )),
+        [0x2002, ..] => Some(Ipv4Addr::new(octets[2], octets[3], octets[4], octets[5])),
+        _ => None,
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // Reserved, 240.0.0.0/4
+        || a >= 240
+        // Carrier-grade NAT, 100.64.0.0/10
+        || (a == 100 && (b & 0xC0) == 64))
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // Unique local, fc00::/7
+        || (first & 0xFE00) == 0xFC00
+        // Link-local, fe80::/10
+        || (first & 0xFFC0) == 0xFE80)
+}
+
+/// Decide whether the server may fetch from `host`, which resolved to
+/// `addrs`.
+///
+/// Explicitly allowed hosts always pass. With an allowlist, nothing else
+/// does; without one, every resolved address must be public.
+pub fn check_fetch_target(
+    host: &str,
+    addrs: impl IntoIterator<Item = IpAddr>,
+    allowed_hosts: &[String],
+) -> Result<(), String> {
+    if allowed_hosts.iter().any(|h| h.eq_ignore_ascii_case(host)) {
+        return Ok(());
+    }
+    if !allowed_hosts.is_empty() {
+        return Err(format!("Host {host} is not in the allowed list"));
+    }
+
+    let mut any = false;
+    for ip in addrs {
+        if !is_public_ip(ip) {
+            return Err(format!("Host {host} resolves to non-public address {ip}"));
+        }
+        any = true;
+    }
+    if !any {
+        return Err(format!("Host {host} did not resolve to any address"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("93.184.216.34", true)]
+    #[case("2606:2800:220:1:248:1893:25c8:1946", true)]
+    #[case("127.0.0.1", false)]
+    #[case("10.1.2.3", false)]
+    #[case("192.168.1.1", false)]
+    #[case("172.16.0.1", false)]
+    #[case("169.254.169.254", false)]
+    #[case("100.64.0.1", false)]
+    #[case("0.0.0.0", false)]
+    #[case("0.1.2.3", false)]
+    #[case("100.127.255.254", false)]
+    #[case("224.0.0.1", false)]
+    #[case("239.255.255.250", false)]
+    #[case("240.0.0.1", false)]
+    #[case("::1", false)]
+    #[case("fd00::1", false)]
+    #[case("fe80::1", false)]
+    #[case("::ffff:127.0.0.1", false)]
+    #[case("ff02::1", false)]
+    #[case("64:ff9b::7f00:1", false)]
+    #[case("64:ff9b::a00:1", false)]
+    #[case("64:ff9b::5db8:d822", true)]
+    #[case("2002:c0a8:101::1", false)]
+    #[case("2002:5db8:d822::1", true)]
+    fn test_is_public_ip(#[case] ip: &str, #[case] public: bool) {
+        assert_eq!(is_public_ip(ip.parse().unwrap()), public);
+    }
+
+    fn ips(list: &[&str]) -> Vec<IpAddr> {
+        list.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn rejects_hosts_with_any_private_address() {
+        let addrs = ips(&["93.184.216.34", "10.0.0.1"]);
+        assert!(check_fetch_target("example.com", addrs, &[]).is_err());
+        assert!(check_fetch_target("example.com", ips(&["93.184.216.34"]), &[]).is_ok());
+        assert!(check_fetch_target("example.com", ips(&[]), &[]).is_err());
+    }
+
+    #[test]
+    fn allowlist_trusts_listed_hosts_and_blocks_others() {
+        let allowed = vec!["Camera.local".to_string()];
+        assert!(check_fetch_target("camera.local", ips(&["192.168.1.9"]), &allowed).is_ok());
+        assert!(check_fetch_target("example.com", ips(&["93.184.216.34"]), &allowed).is_err());
+    }
+}
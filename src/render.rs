@@ -11,17 +11,38 @@
 //! - `try_recv()` for non-blocking channel reads
 //! - Loop labels (`'playback: loop`) for breaking out of nested loops
 
-use crate::{Color, PanelConfig, color, create_matrix};
+use crate::ansi::{Span, TextLine, TextMode, VerticalPosition};
+use crate::api::{ActiveCommand, DisplayState, DisplayStatus, VideoFinish};
+use crate::bdf::{BdfFont, FontCache};
+use crate::diagnostics::CommandLog;
+use crate::draw::{self, GaugeStyle, GradientDirection};
+use crate::effects::{self, SlideTransition, WipeDirection};
+use crate::frame;
+use crate::layout::{self, FitMode};
+use crate::pacer::Pacer;
+use crate::pixel_map::{self, PixelMap};
+use crate::playback::Passes;
+use crate::queue::{self, CountingReceiver, Superseded};
+use crate::settings::Limits;
+use crate::sim::{self, EffectKind};
+use crate::sprites::{self, Sprite};
+use crate::sysinfo::SysInfo;
+use crate::timer::{self, TimerFinish};
+use crate::{
+    Color, ColorOrder, PanelConfig, ansi, color, create_matrix_with_options, limit_brightness,
+    media,
+};
 use image::imageops::FilterType;
 use image::{ImageReader, RgbImage};
-use rpi_led_matrix::{LedCanvas, LedFont};
+use rpi_led_matrix::{LedCanvas, LedMatrix};
 use serde::Serialize;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::Receiver;
+use std::sync::mpsc::{self, RecvTimeoutError, TryRecvError};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::oneshot;
 
 // ── Commands ─────────────────────────────────────────────────────────
 
@@ -32,84 +53,836 @@ use std::time::Duration;
 /// This is sometimes called a "tagged union" or "sum type". The compiler
 /// ensures you handle every variant when pattern matching.
 pub enum RenderCommand {
-    /// Display a static image (path relative to media dir)
-    ShowImage(PathBuf),
-    /// Play a sequence of pre-extracted video frames
+    /// Display a static image, already decoded and fitted to the panel by
+    /// the sender so the render thread never blocks on a slow resize.
+    /// `source` (a path or URL) is shown in the status. With `watch`, the
+    /// image is reloaded whenever its file changes, until the next command
+    ShowImage {
+        img: RgbImage,
+        source: String,
+        watch: Option<ImageWatch>,
+    },
+    /// Reveal an image over the current content, one column or row at a time
+    WipeImage {
+        path: PathBuf,
+        direction: WipeDirection,
+        duration_ms: u32,
+    },
+    /// Show each image in a directory for `interval_ms`, with `transition`
+    /// between them, in natural name order or shuffled
+    Slideshow {
+        dir: PathBuf,
+        interval_ms: u32,
+        transition: SlideTransition,
+        shuffle: bool,
+        loop_playback: bool,
+    },
+    /// Play a sequence of pre-extracted video frames, optionally with the
+    /// video's name faded in over the first seconds
     PlayVideo {
         dir: PathBuf,
         fps: u32,
+        /// Times to play the video through; `None` loops forever
+        loop_count: Option<u32>,
+        fit: FitMode,
+        background: Color,
+        show_title: bool,
+        /// What to show once a non-looping video ends
+        on_finish: VideoFinish,
+    },
+    /// Play frames uploaded in the request, each shown for its own delay
+    PlayFrames {
+        frames: Vec<RgbImage>,
+        delays: Vec<Duration>,
         loop_playback: bool,
     },
-    /// Scroll text across the display
+    /// Scroll a line of text across the display, made of one or more
+    /// differently-colored segments drawn back to back
     ScrollText {
+        segments: Vec<Span>,
+        font: String,
+        speed: u32,
+        /// With `TextMode::Auto`, text that fits the panel once wrapped is
+        /// shown centered instead of scrolling
+        mode: TextMode,
+        /// Left edge of the text for the first pass; `None` starts off the
+        /// right edge like every later pass
+        x: Option<i32>,
+        /// Row the scrolling line sits on
+        y: VerticalPosition,
+        /// Scroll this many times, then clear the panel and go idle.
+        /// `None` scrolls until another command replaces it
+        repeat: Option<u32>,
+    },
+    /// Scroll text to the right of a fixed icon, like a phone notification.
+    /// The icon is scaled to a square as tall as the panel (at most half its
+    /// width) and the text never scrolls over it.
+    IconMarquee {
+        icon_path: PathBuf,
         text: String,
         font: String,
-        color: (u8, u8, u8),
+        color: Color,
         speed: u32,
     },
+    /// Reveal text one character at a time, wrapped and centered like
+    /// `TextMode::Auto`, then hold it
+    TypeText {
+        text: String,
+        font: String,
+        color: Color,
+        char_delay_ms: u32,
+    },
+    /// Draw several lines at once, each with its own font, color, and
+    /// position, e.g. a clock over a date
+    ShowTextGrid { lines: Vec<TextLine> },
+    /// Dashboard of the Pi's own stats (CPU temperature, load, free
+    /// memory, IP address), refreshed every `SYSINFO_INTERVAL`
+    ShowSysInfo { font: String, color: Color },
+    /// Count down from `seconds` as `mm:ss`, then run `on_finish`
+    Timer {
+        seconds: u32,
+        font: String,
+        color: Color,
+        on_finish: TimerFinish,
+        /// When the countdown began, if it's resuming after a `Temporary`
+        /// command; `None` starts it when the command runs
+        started_at: Option<Instant>,
+    },
+    /// Scroll a pre-rendered strip (e.g. client-rasterized text) right to left
+    ScrollBitmap { image: RgbImage, speed: u32 },
+    /// Render a text file (optionally ANSI-colored) line by line.
+    /// With no font given, the largest font that fits the panel is used.
+    ShowTextFile {
+        path: PathBuf,
+        font: Option<String>,
+        /// Color for text without an ANSI color
+        color: Color,
+        /// Honor ANSI SGR color codes instead of stripping them
+        ansi: bool,
+    },
     /// Display a raw RGB frame (rows*cols*3 bytes)
     ShowFrame(Vec<u8>),
-    /// Clear the display (all pixels off)
-    Clear,
-    /// Stop current playback and go idle
-    Stop,
-    /// Set display brightness (0-100)
+    /// Draw a bar or ring gauge filled to where `value` falls in `[min, max]`
+    DrawGauge {
+        value: f32,
+        min: f32,
+        max: f32,
+        color: Color,
+        style: GaugeStyle,
+        /// Dim background drawn for the unfilled part of the gauge
+        track: Option<Color>,
+    },
+    /// Draw `data` as a QR code scaled to fit the panel
+    ShowQr {
+        data: String,
+        /// Color of the dark modules
+        color: Color,
+        background: Color,
+    },
+    /// Closed polygon through `points` on a black frame, outlined or filled
+    DrawPolygon {
+        points: Vec<(i32, i32)>,
+        color: Color,
+        fill: bool,
+    },
+    /// Built-in sprite drawn at `(x, y)` in `color`, over the last frame
+    /// or, with `clear`, on a black one
+    ShowSprite {
+        name: String,
+        sprite: Sprite,
+        x: i32,
+        y: i32,
+        scale: u32,
+        color: Color,
+        clear: bool,
+    },
+    /// Static linear gradient from `from` to `to` across the panel
+    Gradient {
+        from: Color,
+        to: Color,
+        direction: GradientDirection,
+    },
+    /// Radial rainbow that rotates at `speed` degrees per second
+    ColorWheel { speed: u32 },
+    /// A simulation stepped on the render thread until replaced: Game of
+    /// Life at `speed` generations per second, or a ball bouncing at
+    /// `speed` pixels per second
+    Effect {
+        kind: EffectKind,
+        speed: u32,
+        color: Color,
+    },
+    /// Fill the display with one color (black turns all pixels off),
+    /// fading from the current frame over `fade_ms` (0 = instantly)
+    Clear { color: Color, fade_ms: u32 },
+    /// Stop advancing playback and go idle. The last frame stays on the
+    /// panel unless `clear` is set (`Clear` always blanks it).
+    Stop { clear: bool },
+    /// Set display brightness (0-100) by scaling each pixel's color
     SetBrightness(u8),
+    /// Invert every drawn pixel (`255 - channel`), before brightness
+    SetInvert(bool),
+    /// Turn looping of the playing video on or off. Off lets the current
+    /// pass finish and stops there; nothing else is affected
+    SetLoop(bool),
+    /// Set the matrix library's own brightness (1-100), which scales PWM
+    /// duty for the whole panel. Re-creates the matrix, so it interrupts
+    /// whatever is playing.
+    SetHardwareBrightness(u8),
+    /// Write the last full frame to a PNG file (doesn't change the display)
+    SaveSnapshot {
+        path: PathBuf,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+    /// Drop and re-create the matrix to recover from corrupted output, then
+    /// redraw the last frame. Stops whatever was playing.
+    ResetMatrix {
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+    /// Flash `label` for a few seconds so the panel can be found, then
+    /// carry on with whatever was showing. Handled in place like
+    /// brightness changes, so it doesn't interrupt playback for good
+    Identify { label: String },
+    /// Draw and present generated frames as fast as possible for
+    /// `BENCHMARK_DURATION`, then redraw the last frame. Stops whatever was
+    /// playing.
+    Benchmark {
+        reply: oneshot::Sender<BenchmarkResult>,
+    },
+    /// Run `cmd` for `restore_after`, then go back to what was showing
+    /// before: a playing video or animation is restarted, anything else is
+    /// redrawn as it was. A newer command cancels the return.
+    Temporary {
+        cmd: Box<RenderCommand>,
+        restore_after: Duration,
+    },
+    /// Run `cmd`, then signal `done` once it has finished or a newer
+    /// command has replaced it (a looping video only ends that way). `done`
+    /// is dropped without a signal if the command fails.
+    Tracked {
+        cmd: Box<RenderCommand>,
+        done: oneshot::Sender<()>,
+    },
+}
+
+impl RenderCommand {
+    /// Split a `Tracked` command into the command and its completion signal.
+    fn take_done(self) -> (Self, Option<oneshot::Sender<()>>) {
+        match self {
+            Self::Tracked { cmd, done } => (*cmd, Some(done)),
+            cmd => (cmd, None),
+        }
+    }
+
+    /// Whether the command adjusts or reads what's shown in place rather
+    /// than replacing it.
+    pub(crate) fn is_adjustment(&self) -> bool {
+        matches!(
+            self,
+            Self::SetBrightness(_)
+                | Self::SetInvert(_)
+                | Self::SetLoop(_)
+                | Self::SetHardwareBrightness(_)
+                | Self::SaveSnapshot { .. }
+                | Self::Identify { .. }
+        )
+    }
+
+    /// A copy of a command that keeps the panel changing (video, scrolling,
+    /// animations, watched images), so it can be restarted after a
+    /// `Temporary` command. Static content is restored from the last frame
+    /// instead, so this returns `None` for it.
+    fn resume_copy(&self) -> Option<Self> {
+        Some(match self {
+            Self::ShowImage {
+                img,
+                source,
+                watch: Some(watch),
+            } => Self::ShowImage {
+                img: img.clone(),
+                source: source.clone(),
+                watch: Some(watch.clone()),
+            },
+            Self::PlayVideo {
+                dir,
+                fps,
+                loop_count,
+                fit,
+                background,
+                show_title: _,
+                on_finish,
+            } => Self::PlayVideo {
+                dir: dir.clone(),
+                fps: *fps,
+                loop_count: *loop_count,
+                fit: *fit,
+                background: *background,
+                // Already shown when it first started
+                show_title: false,
+                on_finish: *on_finish,
+            },
+            Self::PlayFrames {
+                frames,
+                delays,
+                loop_playback,
+            } => Self::PlayFrames {
+                frames: frames.clone(),
+                delays: delays.clone(),
+                loop_playback: *loop_playback,
+            },
+            Self::ScrollText {
+                segments,
+                font,
+                speed,
+                mode,
+                x,
+                y,
+                repeat,
+            } => Self::ScrollText {
+                segments: segments.clone(),
+                font: font.clone(),
+                speed: *speed,
+                mode: *mode,
+                x: *x,
+                y: *y,
+                repeat: *repeat,
+            },
+            Self::IconMarquee {
+                icon_path,
+                text,
+                font,
+                color,
+                speed,
+            } => Self::IconMarquee {
+                icon_path: icon_path.clone(),
+                text: text.clone(),
+                font: font.clone(),
+                color: *color,
+                speed: *speed,
+            },
+            Self::ShowSysInfo { font, color } => Self::ShowSysInfo {
+                font: font.clone(),
+                color: *color,
+            },
+            Self::ScrollBitmap { image, speed } => Self::ScrollBitmap {
+                image: image.clone(),
+                speed: *speed,
+            },
+            // Picks up where the countdown is, not from the top
+            Self::Timer {
+                seconds,
+                font,
+                color,
+                on_finish,
+                started_at,
+            } => Self::Timer {
+                seconds: *seconds,
+                font: font.clone(),
+                color: *color,
+                on_finish: on_finish.clone(),
+                started_at: Some(started_at.unwrap_or_else(Instant::now)),
+            },
+            Self::ColorWheel { speed } => Self::ColorWheel { speed: *speed },
+            Self::Effect { kind, speed, color } => Self::Effect {
+                kind: *kind,
+                speed: *speed,
+                color: *color,
+            },
+            Self::Slideshow {
+                dir,
+                interval_ms,
+                transition,
+                shuffle,
+                loop_playback,
+            } => Self::Slideshow {
+                dir: dir.clone(),
+                interval_ms: *interval_ms,
+                transition: *transition,
+                shuffle: *shuffle,
+                loop_playback: *loop_playback,
+            },
+            _ => return None,
+        })
+    }
+
+    /// Short name for logs and diagnostics, e.g. `play_video`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ShowImage { .. } => "show_image",
+            Self::WipeImage { .. } => "wipe_image",
+            Self::Slideshow { .. } => "slideshow",
+            Self::PlayVideo { .. } => "play_video",
+            Self::PlayFrames { .. } => "play_frames",
+            Self::ScrollText { .. } => "scroll_text",
+            Self::IconMarquee { .. } => "icon_marquee",
+            Self::TypeText { .. } => "type_text",
+            Self::ShowTextGrid { .. } => "show_text_grid",
+            Self::ShowSysInfo { .. } => "show_sysinfo",
+            Self::Timer { .. } => "timer",
+            Self::ScrollBitmap { .. } => "scroll_bitmap",
+            Self::ShowTextFile { .. } => "show_text_file",
+            Self::ShowFrame(_) => "show_frame",
+            Self::DrawGauge { .. } => "draw_gauge",
+            Self::ShowQr { .. } => "show_qr",
+            Self::DrawPolygon { .. } => "draw_polygon",
+            Self::ShowSprite { .. } => "show_sprite",
+            Self::Gradient { .. } => "gradient",
+            Self::ColorWheel { .. } => "color_wheel",
+            Self::Effect { .. } => "effect",
+            Self::Clear { .. } => "clear",
+            Self::Stop { .. } => "stop",
+            Self::SetBrightness(_) => "set_brightness",
+            Self::SetInvert(_) => "set_invert",
+            Self::SetLoop(_) => "set_loop",
+            Self::SetHardwareBrightness(_) => "set_hardware_brightness",
+            Self::SaveSnapshot { .. } => "save_snapshot",
+            Self::ResetMatrix { .. } => "reset_matrix",
+            Self::Identify { .. } => "identify",
+            Self::Benchmark { .. } => "benchmark",
+            Self::Temporary { .. } => "temporary",
+            Self::Tracked { .. } => "tracked",
+        }
+    }
+
+    /// The command's parameters as JSON, named like the request fields, so
+    /// a client can tell exactly what's running. Uploaded images and frames
+    /// are summarized by size rather than echoed.
+    pub fn details(&self) -> serde_json::Value {
+        use serde_json::json;
+        match self {
+            Self::ShowImage { img, source, watch } => json!({
+                "path": source,
+                "width": img.width(),
+                "height": img.height(),
+                "watch": watch.is_some(),
+            }),
+            Self::WipeImage {
+                path,
+                direction,
+                duration_ms,
+            } => json!({ "path": path, "direction": direction, "duration_ms": duration_ms }),
+            Self::Slideshow {
+                dir,
+                interval_ms,
+                transition,
+                shuffle,
+                loop_playback,
+            } => json!({
+                "path": dir,
+                "interval_ms": interval_ms,
+                "transition": transition,
+                "shuffle": shuffle,
+                "loop": loop_playback,
+            }),
+            Self::PlayVideo {
+                dir,
+                fps,
+                loop_count,
+                fit,
+                background,
+                show_title,
+                on_finish,
+            } => json!({
+                "path": dir,
+                "fps": fps,
+                "loop_count": loop_count.unwrap_or(0),
+                "fit": fit,
+                "background": background,
+                "show_title": show_title,
+                "on_finish": on_finish,
+            }),
+            Self::PlayFrames {
+                frames,
+                delays,
+                loop_playback,
+            } => json!({
+                "frames": frames.len(),
+                "delays_ms": delays.iter().map(Duration::as_millis).collect::<Vec<_>>(),
+                "loop": loop_playback,
+            }),
+            Self::ScrollText {
+                segments,
+                font,
+                speed,
+                mode,
+                x,
+                y,
+                repeat,
+            } => {
+                let mut details = json!({
+                    "segments": segments,
+                    "font": font,
+                    "speed": speed,
+                    "mode": mode,
+                });
+                if let Some(repeat) = repeat {
+                    details["repeat"] = json!(repeat);
+                }
+                if let Some(x) = x {
+                    details["x"] = json!(x);
+                }
+                match y {
+                    VerticalPosition::Center => {}
+                    VerticalPosition::Pixels(y) => details["y"] = json!(y),
+                    VerticalPosition::Fraction(f) => details["y_percent"] = json!(f),
+                }
+                details
+            }
+            Self::IconMarquee {
+                icon_path,
+                text,
+                font,
+                color,
+                speed,
+            } => json!({
+                "icon": icon_path,
+                "text": text,
+                "font": font,
+                "color": color,
+                "speed": speed,
+            }),
+            Self::TypeText {
+                text,
+                font,
+                color,
+                char_delay_ms,
+            } => json!({
+                "text": text,
+                "font": font,
+                "color": color,
+                "char_delay_ms": char_delay_ms,
+            }),
+            Self::ShowTextGrid { lines } => json!({ "lines": lines }),
+            Self::ShowSysInfo { font, color } => json!({ "font": font, "color": color }),
+            Self::Timer {
+                seconds,
+                font,
+                color,
+                on_finish,
+                started_at: _,
+            } => json!({
+                "seconds": seconds,
+                "font": font,
+                "color": color,
+                "on_finish": on_finish,
+            }),
+            Self::ScrollBitmap { image, speed } => json!({
+                "width": image.width(),
+                "height": image.height(),
+                "speed": speed,
+            }),
+            Self::ShowTextFile {
+                path,
+                font,
+                color,
+                ansi,
+            } => json!({ "path": path, "font": font, "color": color, "ansi": ansi }),
+            Self::ShowFrame(data) => json!({ "bytes": data.len() }),
+            Self::DrawGauge {
+                value,
+                min,
+                max,
+                color,
+                style,
+                track,
+            } => json!({
+                "value": value,
+                "min": min,
+                "max": max,
+                "color": color,
+                "style": style,
+                "track": track,
+            }),
+            Self::ShowQr {
+                data,
+                color,
+                background,
+            } => json!({ "data": data, "color": color, "background": background }),
+            Self::DrawPolygon {
+                points,
+                color,
+                fill,
+            } => json!({ "points": points, "color": color, "fill": fill }),
+            Self::ShowSprite {
+                name,
+                sprite: _,
+                x,
+                y,
+                scale,
+                color,
+                clear,
+            } => json!({
+                "name": name,
+                "x": x,
+                "y": y,
+                "scale": scale,
+                "color": color,
+                "clear": clear,
+            }),
+            Self::Gradient {
+                from,
+                to,
+                direction,
+            } => json!({ "from": from, "to": to, "direction": direction }),
+            Self::ColorWheel { speed } => json!({ "speed": speed }),
+            Self::Effect { kind, speed, color } => {
+                json!({ "kind": kind, "speed": speed, "color": color })
+            }
+            Self::Clear { color, fade_ms } => json!({ "color": color, "fade_ms": fade_ms }),
+            Self::Stop { clear } => json!({ "clear": clear }),
+            Self::SetBrightness(value) | Self::SetHardwareBrightness(value) => {
+                json!({ "value": value })
+            }
+            Self::SetInvert(invert) => json!({ "invert": invert }),
+            Self::SetLoop(looping) => json!({ "loop": looping }),
+            Self::SaveSnapshot { path, reply: _ } => json!({ "path": path }),
+            Self::Identify { label } => json!({ "label": label }),
+            Self::ResetMatrix { .. } | Self::Benchmark { .. } => json!({}),
+            Self::Temporary { cmd, restore_after } => json!({
+                "command": cmd.name(),
+                "params": cmd.details(),
+                "restore_after_ms": restore_after.as_millis(),
+            }),
+            Self::Tracked { cmd, done: _ } => cmd.details(),
+        }
+    }
+
+    /// Undo `take_done`, for a command stashed to run later.
+    fn with_done(self, done: Option<oneshot::Sender<()>>) -> Self {
+        match done {
+            Some(done) => Self::Tracked {
+                cmd: Box::new(self),
+                done,
+            },
+            None => self,
+        }
+    }
+}
+
+/// Tell whoever sent a `Tracked` command that it has finished.
+fn signal_done(done: Option<oneshot::Sender<()>>) {
+    if let Some(done) = done {
+        let _ = done.send(());
+    }
+}
+
+/// An image file the render thread keeps watching while it's shown, so an
+/// external program can update the panel by rewriting the file.
+#[derive(Clone)]
+pub struct ImageWatch {
+    pub path: PathBuf,
+    pub fit: FitMode,
+    pub background: Color,
+    /// Clockwise rotation applied after fitting, if any
+    pub rotate_deg: Option<f32>,
+    /// Modification time of the file when it was last loaded
+    pub modified: Option<SystemTime>,
+}
+
+impl ImageWatch {
+    /// Whether the file's modification time has changed since it was last
+    /// loaded.
+    ///
+    /// The new time is recorded even if loading then fails, so a broken file
+    /// is reported once rather than on every check.
+    fn check_modified(&mut self) -> std::io::Result<bool> {
+        let modified = fs::metadata(&self.path)?.modified()?;
+        if self.modified == Some(modified) {
+            return Ok(false);
+        }
+        self.modified = Some(modified);
+        Ok(true)
+    }
+
+    /// Load and fit the image on its own thread, so decoding a large file
+    /// doesn't hold up commands. The result arrives on the returned channel.
+    fn load_in_background(&self, panel: PanelConfig) -> mpsc::Receiver<Result<RgbImage, String>> {
+        let (tx, rx) = mpsc::channel();
+        let watch = self.clone();
+        thread::spawn(move || {
+            let loaded = load_and_fit_image(&watch.path, panel, watch.fit, watch.background)
+                .map(|img| match watch.rotate_deg {
+                    Some(deg) => layout::rotate_image(&img, deg, watch.background.into()),
+                    None => img,
+                })
+                .map_err(|e| e.to_string());
+            // The render thread may have moved on to another command
+            let _ = tx.send(loaded);
+        });
+        rx
+    }
+}
+
+/// Throughput measured by `RenderCommand::Benchmark`.
+#[derive(Clone, Debug, Serialize, utoipa::ToSchema)]
+pub struct BenchmarkResult {
+    /// Frames drawn and presented
+    pub frames: u32,
+    /// Frames per second the panel sustained
+    pub fps: f64,
+    /// Average time to draw a frame to the canvas, in milliseconds
+    pub avg_draw_ms: f64,
+    /// Average time to present (swap) a frame, in milliseconds
+    pub avg_swap_ms: f64,
+}
+
+// ── Configuration ──────────────────────────────────────────────────────
+
+/// How the render thread gets pixels onto the panel.
+///
+/// `DoubleBuffered` draws into an offscreen canvas and swaps it in on the
+/// next vsync, so a frame never appears half-drawn. `Direct` draws straight
+/// onto the canvas being refreshed: updates show up without waiting for a
+/// swap, at the cost of visible tearing while a frame is being drawn. It
+/// also never allocates the offscreen canvas, saving one full frame buffer
+/// and the swap. Use `Direct` for latency-sensitive, mostly-static content
+/// or memory-constrained setups.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum CanvasStrategy {
+    #[default]
+    DoubleBuffered,
+    Direct,
+}
+
+/// Which brightness `/api/v1/brightness` controls.
+///
+/// `Software` scales every pixel's color before drawing. It works with all
+/// content and keeps color math predictable, but at low levels it throws
+/// away color depth. `Hardware` uses the matrix library's brightness, which
+/// shortens PWM on-time for the whole panel: it keeps full color depth and
+/// saves power, but changing it re-creates the matrix (a brief blank) and
+/// can change the refresh rate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum BrightnessMode {
+    #[default]
+    Software,
+    Hardware,
 }
 
-// ── Status ───────────────────────────────────────────────────────────
+/// Render-thread settings chosen at startup.
+#[derive(Clone, Debug)]
+pub struct RenderConfig {
+    /// Drawable area: the panel minus `safe_inset` on every side
+    pub panel: PanelConfig,
+    /// Border left dark around the drawable area, for bezels that cover
+    /// the edge pixels. `pixel_map` must already account for it
+    pub safe_inset: u32,
+    /// Directory to load BDF fonts from
+    pub fonts_dir: PathBuf,
+    /// Offscreen+swap or draw-in-place
+    pub canvas: CanvasStrategy,
+    /// Initial hardware (PWM) brightness, 1-100
+    pub hardware_brightness: u8,
+    /// Brightness and refresh caps (`--max-brightness`, `--max-refresh-fps`),
+    /// which a reloaded `--settings-file` can change while this runs
+    pub limits: Arc<Limits>,
+    /// Custom logical-to-physical pixel remapping, if any
+    pub pixel_map: Option<PixelMap>,
+    /// GPIO mapping name passed to the matrix library (e.g. `adafruit-hat`)
+    pub hardware_mapping: String,
+    /// Shut the matrix down after this long without commands
+    pub power_off_after: Option<Duration>,
+    /// Shortest time a display command stays up before a newer one replaces
+    /// it; brightness and other adjustments still apply right away
+    pub min_display: Duration,
+    /// Recent commands and their timings, for `GET /api/v1/diagnostics`
+    pub diagnostics: Arc<Mutex<CommandLog>>,
+    /// Leave the panel alone for a raw frame identical to the one shown
+    pub skip_duplicate_frames: bool,
+    /// Order the panel's wiring takes color channels in (`--color-order`)
+    pub color_order: ColorOrder,
+}
+
+impl RenderConfig {
+    /// Full size of the matrix, including the safe inset border.
+    fn matrix_panel(&self) -> PanelConfig {
+        PanelConfig::new(
+            self.panel.rows + self.safe_inset * 2,
+            self.panel.cols + self.safe_inset * 2,
+        )
+    }
+
+    /// Cap on software and hardware brightness, 0-100; higher requests are
+    /// clamped to it
+    fn max_brightness(&self) -> u8 {
+        self.limits.max_brightness()
+    }
 
-/// What the display is currently doing.
-#[derive(Clone, Serialize, utoipa::ToSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum DisplayState {
-    Idle,
-    ShowingImage,
-    PlayingVideo,
-    ScrollingText,
-    Streaming,
+    fn output(&self) -> Output<'_> {
+        Output {
+            map: self.pixel_map.as_ref(),
+            color_order: self.color_order,
+        }
+    }
 }
 
-/// Shared status that the HTTP server can read to report current state.
+/// The matrix plus the canvas we currently draw into.
 ///
-/// Rust concept: Arc<Mutex<T>>
-/// `Arc` = atomic reference counting (shared ownership across threads)
-/// `Mutex` = mutual exclusion (only one thread can access at a time)
-/// Together they allow the render thread to update status while the
-/// HTTP server reads it.
-#[derive(Clone, Serialize, utoipa::ToSchema)]
-pub struct DisplayStatus {
-    /// Current display state
-    pub state: DisplayState,
-    /// Currently displayed media (if any)
-    pub current_media: Option<String>,
-    /// Current frame number (for videos)
-    pub frame: Option<usize>,
-    /// Total frame count (for videos)
-    pub total_frames: Option<usize>,
-    /// Current brightness (0-100)
-    pub brightness: u8,
-    /// Server version
-    pub version: String,
-}
-
-impl DisplayStatus {
-    pub fn new() -> Self {
+/// Hides the canvas strategy from the render loop: draw into `canvas()`,
+/// then call `present()` to make it visible.
+struct Screen {
+    matrix: LedMatrix,
+    // Always `Some` — taken briefly while `swap` owns it
+    canvas: Option<LedCanvas>,
+    strategy: CanvasStrategy,
+    /// Holds swaps back to `max_refresh_fps`
+    pacer: Pacer,
+    /// Where `pacer` gets the current `max_refresh_fps` from
+    limits: Arc<Limits>,
+}
+
+impl Screen {
+    fn new(matrix: LedMatrix, config: &RenderConfig) -> Self {
+        let strategy = config.canvas;
+        let canvas = match strategy {
+            CanvasStrategy::DoubleBuffered => matrix.offscreen_canvas(),
+            CanvasStrategy::Direct => matrix.canvas(),
+        };
         Self {
-            state: DisplayState::Idle,
-            current_media: None,
-            frame: None,
-            total_frames: None,
-            brightness: 75,
-            version: env!("CARGO_PKG_VERSION").to_string(),
+            matrix,
+            canvas: Some(canvas),
+            strategy,
+            pacer: Pacer::new(config.limits.max_refresh_fps()),
+            limits: config.limits.clone(),
         }
     }
 
-    pub fn set_idle(&mut self) {
-        self.state = DisplayState::Idle;
-        self.current_media = None;
-        self.frame = None;
-        self.total_frames = None;
+    fn canvas(&mut self) -> &mut LedCanvas {
+        self.canvas
+            .as_mut()
+            .expect("canvas is only taken during swap")
+    }
+
+    /// Re-create the matrix with a new hardware brightness.
+    ///
+    /// `rpi-led-matrix` only accepts brightness as a start-up option, so
+    /// the old matrix is dropped (releasing the GPIO) and a new one built.
+    fn with_hardware_brightness(
+        self,
+        config: &RenderConfig,
+        brightness: u8,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        drop(self);
+        let matrix = create_matrix_with_options(
+            config.matrix_panel(),
+            brightness,
+            &config.hardware_mapping,
+        )?;
+        Ok(Self::new(matrix, config))
+    }
+
+    /// Make everything drawn since the last call visible, waiting first if
+    /// the last swap was too recent for `max_refresh_fps`.
+    fn present(&mut self) {
+        if self.strategy == CanvasStrategy::DoubleBuffered {
+            self.pacer.set_max_fps(self.limits.max_refresh_fps());
+            self.pacer.wait();
+            let canvas = self
+                .canvas
+                .take()
+                .expect("canvas is only taken during swap");
+            self.canvas = Some(self.matrix.swap(canvas));
+        }
     }
 }
 
@@ -127,19 +900,65 @@ pub fn load_and_resize_image(
     Ok(resized)
 }
 
+/// Load an image from disk and fit it to the panel using `fit`, filling
+/// any letterbox bars with `background`.
+pub fn load_and_fit_image(
+    path: &Path,
+    panel: PanelConfig,
+    fit: FitMode,
+    background: Color,
+) -> Result<RgbImage, Box<dyn std::error::Error>> {
+    if fit == FitMode::Stretch {
+        return load_and_resize_image(path, panel);
+    }
+    let img = ImageReader::open(path)?.decode()?.to_rgb8();
+    Ok(layout::fit_image(
+        &img,
+        panel.cols,
+        panel.rows,
+        fit,
+        background.into(),
+    ))
+}
+
+/// Render a text file into a panel-sized frame.
+///
+/// `font` names a BDF font in `fonts_dir`; with `None`, every font there is
+/// loaded and the largest one that fits the text is picked.
+pub fn render_text_file(
+    path: &Path,
+    font: Option<&str>,
+    fonts_dir: &Path,
+    panel: PanelConfig,
+    default_color: Color,
+    honor_ansi: bool,
+) -> Result<RgbImage, Box<dyn std::error::Error>> {
+    // ANSI art is often CP437 rather than UTF-8; keep what we can read
+    let bytes = fs::read(path)?;
+    let text = String::from_utf8_lossy(&bytes);
+    let lines = ansi::parse(&text, default_color, honor_ansi);
+
+    let fonts = match font {
+        Some(name) => vec![BdfFont::load_named(fonts_dir, name)?],
+        None => media::list_available_fonts(fonts_dir)
+            .iter()
+            .filter_map(|name| BdfFont::load_named(fonts_dir, name).ok())
+            .collect(),
+    };
+
+    let font = ansi::choose_font(&fonts, &lines, panel.cols, panel.rows)
+        .ok_or_else(|| format!("No usable fonts in {}", fonts_dir.display()))?;
+    Ok(ansi::render(font, &lines, panel.cols, panel.rows))
+}
+
 /// Discover and sort all frame image files in a directory.
 pub fn load_frame_paths(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
     let mut paths = Vec::new();
 
     for entry in fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
-
-        if let Some(ext) = path.extension() {
-            let ext_str = ext.to_str().unwrap_or("");
-            if ext_str == "jpg" || ext_str == "jpeg" || ext_str == "png" {
-                paths.push(path);
-            }
+        let path = entry?.path();
+        if media::has_extension(&path, media::VIDEO_FRAME_EXTENSIONS) {
+            paths.push(path);
         }
     }
 
@@ -158,208 +977,2022 @@ pub fn load_frame(path: &Path) -> Result<RgbImage, Box<dyn std::error::Error>> {
     Ok(img)
 }
 
+/// Where drawn pixels end up on the hardware: the physical positions from
+/// the pixel map, and the channel order the panel is wired for.
+#[derive(Clone, Copy, Debug)]
+pub struct Output<'a> {
+    pub map: Option<&'a PixelMap>,
+    pub color_order: ColorOrder,
+}
+
+/// Set one logical pixel, routed through the pixel map if there is one.
+/// With `--symmetry` that can be several physical pixels, or none.
+fn set_pixel(canvas: &mut LedCanvas, output: Output<'_>, x: u32, y: u32, c: Color) {
+    let c = c.reorder(output.color_order);
+    for (px, py) in pixel_map::logical_to_physical(output.map, x, y) {
+        canvas.set(px, py, &c.into());
+    }
+}
+
 /// Draw an RgbImage onto the LED canvas pixel by pixel.
-pub fn draw_frame_to_canvas(canvas: &mut LedCanvas, img: &RgbImage) {
+pub fn draw_frame_to_canvas(canvas: &mut LedCanvas, img: &RgbImage, output: Output<'_>) {
     for (x, y, pixel) in img.enumerate_pixels() {
-        let led_color = color(pixel[0], pixel[1], pixel[2]);
-        canvas.set(x as i32, y as i32, &led_color.into());
+        set_pixel(canvas, output, x, y, color(pixel[0], pixel[1], pixel[2]));
+    }
+}
+
+// ── Color adjustment helpers ─────────────────────────────────────────
+
+/// Color adjustments applied to every pixel at draw time. Shared with the
+/// render loops so they can change without interrupting playback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ColorAdjust {
+    /// Software brightness, 0-100
+    brightness: u8,
+    /// Invert colors (applied before brightness, so dimming still dims)
+    invert: bool,
+}
+
+impl Default for ColorAdjust {
+    fn default() -> Self {
+        Self {
+            brightness: 75,
+            invert: false,
+        }
+    }
+}
+
+impl ColorAdjust {
+    /// Whether drawing with these settings leaves colors unchanged.
+    fn is_identity(self) -> bool {
+        self.brightness >= 100 && !self.invert
+    }
+
+    fn apply(self, c: Color) -> Color {
+        let c = if self.invert { c.invert() } else { c };
+        c.apply_brightness(self.brightness)
+    }
+}
+
+/// Draw an image to canvas with brightness scaling and inversion applied.
+fn draw_frame_adjusted(
+    canvas: &mut LedCanvas,
+    img: &RgbImage,
+    adjust: ColorAdjust,
+    output: Output<'_>,
+) {
+    if adjust.is_identity() {
+        draw_frame_to_canvas(canvas, img, output);
+    } else {
+        for (x, y, pixel) in img.enumerate_pixels() {
+            let c = adjust.apply(Color::new(pixel[0], pixel[1], pixel[2]));
+            set_pixel(canvas, output, x, y, c);
+        }
+    }
+}
+
+/// Draw raw RGB bytes to canvas with brightness scaling and inversion.
+fn draw_raw_frame(
+    canvas: &mut LedCanvas,
+    data: &[u8],
+    panel: PanelConfig,
+    adjust: ColorAdjust,
+    output: Output<'_>,
+) {
+    for y in 0..panel.rows {
+        for x in 0..panel.cols {
+            let offset = ((y * panel.cols + x) * 3) as usize;
+            let c = adjust.apply(Color::new(data[offset], data[offset + 1], data[offset + 2]));
+            set_pixel(canvas, output, x, y, c);
+        }
+    }
+}
+
+/// Font for the video title overlay.
+const TITLE_FONT: &str = "5x8";
+/// How long the video title is shown before it starts fading.
+const TITLE_HOLD: Duration = Duration::from_secs(2);
+/// How long the video title takes to fade out.
+const TITLE_FADE: Duration = Duration::from_secs(1);
+
+/// How long `RenderCommand::Benchmark` runs.
+const BENCHMARK_DURATION: Duration = Duration::from_secs(1);
+
+/// Draw and present frames back to back for `BENCHMARK_DURATION`.
+///
+/// The frames are a rainbow shifted by one pixel, so every pixel changes
+/// on each swap without the panel flashing.
+fn run_benchmark(screen: &mut Screen, panel: PanelConfig, output: Output<'_>) -> BenchmarkResult {
+    let frames: Vec<RgbImage> = (0..2)
+        .map(|shift| {
+            RgbImage::from_fn(panel.cols, panel.rows, |x, _| {
+                Color::from_hue(((x + shift) * 360 / panel.cols) as u16).into()
+            })
+        })
+        .collect();
+
+    let mut count = 0u32;
+    let mut draw_total = Duration::ZERO;
+    let mut swap_total = Duration::ZERO;
+    let start = std::time::Instant::now();
+    while start.elapsed() < BENCHMARK_DURATION {
+        let draw_start = std::time::Instant::now();
+        draw_frame_to_canvas(screen.canvas(), &frames[count as usize % 2], output);
+        draw_total += draw_start.elapsed();
+
+        let swap_start = std::time::Instant::now();
+        screen.present();
+        swap_total += swap_start.elapsed();
+        count += 1;
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    let per_frame_ms = |total: Duration| total.as_secs_f64() * 1000.0 / count.max(1) as f64;
+    BenchmarkResult {
+        frames: count,
+        fps: count as f64 / elapsed,
+        avg_draw_ms: per_frame_ms(draw_total),
+        avg_swap_ms: per_frame_ms(swap_total),
+    }
+}
+
+/// Encode a frame as PNG and write it to `path`.
+fn save_snapshot(frame: &RgbImage, path: &Path) -> Result<(), String> {
+    frame
+        .save_with_format(path, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to save snapshot to {}: {e}", path.display()))?;
+    tracing::info!("Saved snapshot: {}", path.display());
+    Ok(())
+}
+
+/// What to go back to once a `Temporary` command's time is up.
+struct PendingRestore {
+    at: Instant,
+    target: RenderCommand,
+}
+
+/// What a `Temporary` command goes back to: the pending restore of an
+/// earlier one, else the command still running (`current`), else a still
+/// of `last_frame`, named after the media it showed.
+fn restore_target(
+    pending: Option<PendingRestore>,
+    current: Option<RenderCommand>,
+    last_frame: &RgbImage,
+    source: Option<String>,
+) -> RenderCommand {
+    pending
+        .map(|pending| pending.target)
+        .or(current)
+        .unwrap_or_else(|| RenderCommand::ShowImage {
+            img: last_frame.clone(),
+            source: source.unwrap_or_else(|| "previous frame".to_string()),
+            watch: None,
+        })
+}
+
+/// The restore target, if its time has come.
+fn take_due_restore(restore: &mut Option<PendingRestore>) -> Option<RenderCommand> {
+    restore
+        .take_if(|pending| Instant::now() >= pending.at)
+        .map(|pending| pending.target)
+}
+
+/// Check for a new command without blocking.
+///
+/// Brightness and invert changes, snapshots, and identify flashes are
+/// handled in place so they don't interrupt the caller. Any other command
+/// is returned so the caller can stash it in `pending_cmd` and break out of
+/// its loop. So is the restore target of a `Temporary` command once it's
+/// due.
+fn poll_command(
+    rx: &CountingReceiver<RenderCommand>,
+    adjust: &Mutex<ColorAdjust>,
+    status: &Mutex<DisplayStatus>,
+    last_frame: &RgbImage,
+    screen: &mut Screen,
+    config: &RenderConfig,
+    restore: &mut Option<PendingRestore>,
+) -> Option<Queued> {
+    if let Some(target) = take_due_restore(restore) {
+        return Some(Queued::made(target));
+    }
+    loop {
+        let queued = Queued::received(rx.try_recv().ok()?, rx);
+        let unhandled = handle_in_place(
+            queued,
+            adjust,
+            status,
+            last_frame,
+            Some(&mut *screen),
+            None,
+            config,
+        );
+        if unhandled.is_some() {
+            return unhandled;
+        }
+    }
+}
+
+/// Run `queued` in place if it adjusts or reads what's shown, log it in the
+/// diagnostics, and tell a tracked sender it's done. Any other command is
+/// handed back for the caller to run.
+///
+/// `passes` belongs to a playing video, the only thing `SetLoop` changes.
+/// Without a `screen` (the panel is powered off) an identify flash is handed
+/// back to wake it, and a hardware brightness is kept for when the matrix
+/// is re-created.
+fn handle_in_place(
+    queued: Queued,
+    adjust: &Mutex<ColorAdjust>,
+    status: &Mutex<DisplayStatus>,
+    last_frame: &RgbImage,
+    screen: Option<&mut Screen>,
+    passes: Option<&mut Passes>,
+    config: &RenderConfig,
+) -> Option<Queued> {
+    let Queued { msg: cmd, sent_at } = queued;
+    let (cmd, done) = cmd.take_done();
+    let (name, queued_for, started_at) = (cmd.name(), queue_wait(sent_at), SystemTime::now());
+    match (cmd, screen) {
+        (RenderCommand::SetBrightness(value), _) => {
+            let new_brightness = limit_brightness(value, config.max_brightness());
+            adjust.lock().unwrap().brightness = new_brightness;
+            status.lock().unwrap().brightness = new_brightness;
+        }
+        (RenderCommand::SetInvert(invert), _) => {
+            adjust.lock().unwrap().invert = invert;
+            status.lock().unwrap().inverted = invert;
+        }
+        (RenderCommand::SetLoop(looping), _) => {
+            if let Some(passes) = passes {
+                passes.set_looping(looping);
+                status.lock().unwrap().looping = looping;
+                tracing::info!("Loop set to {}", looping);
+            }
+        }
+        (RenderCommand::SaveSnapshot { path, reply }, _) => {
+            // The receiver may have given up waiting; nothing to do then
+            let _ = reply.send(save_snapshot(last_frame, &path));
+        }
+        (RenderCommand::Identify { label }, Some(screen)) => {
+            let current_adjust = *adjust.lock().unwrap();
+            identify(screen, config, &label, current_adjust, last_frame);
+        }
+        (RenderCommand::SetHardwareBrightness(value), None) => {
+            status.lock().unwrap().hardware_brightness =
+                limit_brightness(value, config.max_brightness()).max(1);
+        }
+        (cmd, _) => {
+            return Some(Queued {
+                msg: cmd.with_done(done),
+                sent_at,
+            });
+        }
+    }
+    config
+        .diagnostics
+        .lock()
+        .unwrap()
+        .record(name, queued_for, started_at, SystemTime::now());
+    signal_done(done);
+    None
+}
+
+/// A command waiting its turn in the render loop, with when it was sent.
+type Queued = queue::Queued<RenderCommand>;
+
+impl Queued {
+    /// Whether the command, tracked or not, is an in-place adjustment.
+    fn is_adjustment(&self) -> bool {
+        match &self.msg {
+            RenderCommand::Tracked { cmd, .. } => cmd.is_adjustment(),
+            cmd => cmd.is_adjustment(),
+        }
+    }
+}
+
+/// Take `newer`, which arrived while `held` was kept up for `min_display`.
+///
+/// A display command replaces `held`, whose sender is told it's done. An
+/// adjustment takes `held`'s place to run right away, and `held` is returned
+/// to run after it.
+fn supersede(held: &mut Queued, newer: Queued) -> Option<Queued> {
+    match queue::supersede(held, newer, Queued::is_adjustment) {
+        Superseded::RunsAfter(held) => Some(held),
+        Superseded::Dropped(replaced) => {
+            signal_done(replaced.msg.take_done().1);
+            None
+        }
+    }
+}
+
+/// How long a command sent at `sent_at` waited in the queue.
+fn queue_wait(sent_at: Option<Instant>) -> Duration {
+    sent_at.map_or(Duration::ZERO, |sent_at| sent_at.elapsed())
+}
+
+/// Log why a command failed and note it in the diagnostics log. A failed
+/// command has nothing to resume, so `current` is cleared, and it isn't what
+/// the panel shows, so it's cleared from the status too.
+fn command_failed(
+    config: &RenderConfig,
+    status: &Mutex<DisplayStatus>,
+    current: &mut Option<RenderCommand>,
+    error: String,
+) {
+    *current = None;
+    status.lock().unwrap().command = None;
+    tracing::error!("{}", error);
+    config.diagnostics.lock().unwrap().fail(error);
+}
+
+/// Shut the matrix down until the next command arrives.
+///
+/// Blanking the panel still leaves the matrix library refreshing it, so the
+/// matrix is dropped entirely, which stops its refresh thread. Brightness
+/// and invert changes (e.g. from the brightness schedule) and snapshots are
+/// handled without waking the panel; a hardware brightness is kept for when
+/// the matrix is re-created. Returns a fresh screen and the command that
+/// woke it, or `None` if the channel closed or the matrix can't be
+/// re-created.
+fn power_off_until_command(
+    mut screen: Screen,
+    rx: &CountingReceiver<RenderCommand>,
+    adjust: &Arc<Mutex<ColorAdjust>>,
+    status: &Arc<Mutex<DisplayStatus>>,
+    last_frame: &RgbImage,
+    config: &RenderConfig,
+) -> Option<(Screen, Queued)> {
+    tracing::info!("Powering the panel off until the next command");
+    screen.canvas().clear();
+    screen.present();
+    drop(screen);
+    {
+        let mut s = status.lock().unwrap();
+        s.set_idle();
+        s.state = DisplayState::PoweredOff;
+    }
+
+    let cmd = loop {
+        let Ok(cmd) = rx.recv() else {
+            tracing::info!("Render thread: channel closed, shutting down.");
+            return None;
+        };
+        let queued = Queued::received(cmd, rx);
+        if let Some(queued) =
+            handle_in_place(queued, adjust, status, last_frame, None, None, config)
+        {
+            break queued;
+        }
+    };
+
+    tracing::info!("Command received, powering the panel back on");
+    let hardware_brightness = status.lock().unwrap().hardware_brightness;
+    match create_matrix_with_options(
+        config.matrix_panel(),
+        hardware_brightness,
+        &config.hardware_mapping,
+    ) {
+        Ok(matrix) => Some((Screen::new(matrix, config), cmd)),
+        Err(e) => {
+            tracing::error!("Failed to re-initialize LED matrix: {}", e);
+            None
+        }
+    }
+}
+
+/// How often commands are checked while holding a display for
+/// `min_display`.
+const HOLD_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How often the system stats dashboard re-reads its values.
+const SYSINFO_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the system stats dashboard checks for commands between reads.
+const SYSINFO_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Time between frames of a fade started by `Clear`.
+const FADE_STEP: Duration = Duration::from_millis(20);
+
+/// How long a slideshow transition takes, out of each image's interval.
+const SLIDE_TRANSITION_MS: u32 = 500;
+
+/// How often a slideshow checks for commands while an image is up.
+const SLIDE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often a watched image's file is checked for changes.
+const IMAGE_WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Wait for a command like `recv_timeout` (or `recv` with no timeout),
+/// reloading and redrawing the watched image whenever its file changes.
+///
+/// The image is decoded off this thread; a command that arrives meanwhile
+/// is returned right away and the half-done reload is dropped.
+fn recv_watching(
+    rx: &CountingReceiver<RenderCommand>,
+    timeout: Option<Duration>,
+    watch: &mut ImageWatch,
+    screen: &mut Screen,
+    adjust: &Mutex<ColorAdjust>,
+    last_frame: &mut RgbImage,
+    config: &RenderConfig,
+) -> Result<RenderCommand, RecvTimeoutError> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut loading: Option<mpsc::Receiver<Result<RgbImage, String>>> = None;
+    loop {
+        let wait = deadline.map_or(IMAGE_WATCH_INTERVAL, |deadline| {
+            deadline
+                .saturating_duration_since(Instant::now())
+                .min(IMAGE_WATCH_INTERVAL)
+        });
+        match rx.recv_timeout(wait) {
+            Err(RecvTimeoutError::Timeout)
+                if deadline.is_none_or(|deadline| Instant::now() < deadline) => {}
+            received => return received,
+        }
+
+        if let Some(pending) = &loading {
+            match pending.try_recv() {
+                Ok(Ok(img)) => {
+                    let current_adjust = *adjust.lock().unwrap();
+                    draw_frame_adjusted(screen.canvas(), &img, current_adjust, config.output());
+                    screen.present();
+                    *last_frame = img;
+                    tracing::info!("Reloaded image: {}", watch.path.display());
+                }
+                Ok(Err(e)) => {
+                    tracing::error!("Failed to reload image {}: {}", watch.path.display(), e);
+                }
+                Err(TryRecvError::Empty) => continue,
+                Err(TryRecvError::Disconnected) => {}
+            }
+            loading = None;
+        }
+
+        match watch.check_modified() {
+            Ok(true) => loading = Some(watch.load_in_background(config.panel)),
+            Ok(false) => {}
+            Err(e) => {
+                tracing::error!("Failed to reload image {}: {}", watch.path.display(), e);
+            }
+        }
+    }
+}
+
+/// Font for the identify label.
+const IDENTIFY_FONT: &str = "6x10";
+/// How often a running timer checks whether its readout changed.
+const TIMER_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Times a finished timer blinks its `00:00` with `TimerFinish::Flash`.
+const TIMER_FLASHES: u32 = 5;
+
+/// How long each half of a timer blink lasts.
+const TIMER_FLASH_TIME: Duration = Duration::from_millis(300);
+
+/// Number of identify flashes, alternating normal and inverted.
+const IDENTIFY_FLASHES: u32 = 6;
+/// How long each identify flash is shown.
+const IDENTIFY_FLASH_TIME: Duration = Duration::from_millis(500);
+
+/// Flash `label`, alternating white-on-black and inverted, then redraw
+/// `last_frame`. Without the font, the panel flashes plain white.
+fn identify(
+    screen: &mut Screen,
+    config: &RenderConfig,
+    label: &str,
+    adjust: ColorAdjust,
+    last_frame: &RgbImage,
+) {
+    let panel = config.panel;
+    let output = config.output();
+
+    let font_path = config.fonts_dir.join(format!("{IDENTIFY_FONT}.bdf"));
+    let frame = match BdfFont::load(&font_path) {
+        Ok(font) => {
+            let spans = [Span {
+                text: label.to_string(),
+                color: color(255, 255, 255),
+            }];
+            let lines = ansi::wrap(&font, &spans, panel.cols);
+            ansi::render_centered(&font, &lines, panel.cols, panel.rows)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to load font {}: {}", font_path.display(), e);
+            RgbImage::new(panel.cols, panel.rows)
+        }
+    };
+    let mut inverted = frame.clone();
+    image::imageops::invert(&mut inverted);
+
+    tracing::info!("Identifying panel: {}", label);
+    for flash in 0..IDENTIFY_FLASHES {
+        let img = if flash % 2 == 0 { &frame } else { &inverted };
+        draw_frame_adjusted(screen.canvas(), img, adjust, output);
+        screen.present();
+        thread::sleep(IDENTIFY_FLASH_TIME);
     }
+
+    draw_frame_adjusted(screen.canvas(), last_frame, adjust, output);
+    screen.present();
 }
 
-// ── Brightness helpers ───────────────────────────────────────────────
+/// Switch the matrix to a new hardware brightness and redraw `last_frame`.
+///
+/// If the new matrix can't be created, the previous brightness is restored.
+/// Returns `None` only if the matrix can't be brought back at all.
+fn change_hardware_brightness(
+    screen: Screen,
+    config: &RenderConfig,
+    value: u8,
+    status: &Mutex<DisplayStatus>,
+    last_frame: &RgbImage,
+    adjust: ColorAdjust,
+) -> Option<Screen> {
+    let value = limit_brightness(value, config.max_brightness()).max(1);
+    let previous = status.lock().unwrap().hardware_brightness;
+
+    let mut screen = match screen.with_hardware_brightness(config, value) {
+        Ok(screen) => {
+            status.lock().unwrap().hardware_brightness = value;
+            tracing::info!("Hardware brightness set to {}", value);
+            screen
+        }
+        Err(e) => {
+            tracing::error!("Failed to set hardware brightness to {}: {}", value, e);
+            match create_matrix_with_options(
+                config.matrix_panel(),
+                previous,
+                &config.hardware_mapping,
+            ) {
+                Ok(matrix) => Screen::new(matrix, config),
+                Err(e) => {
+                    tracing::error!("Failed to re-initialize LED matrix: {}", e);
+                    return None;
+                }
+            }
+        }
+    };
+
+    draw_frame_adjusted(screen.canvas(), last_frame, adjust, config.output());
+    screen.present();
+    Some(screen)
+}
+
+/// Apply brightness and inversion to an entire image, returning a new image.
+fn adjust_image(img: &RgbImage, adjust: ColorAdjust) -> RgbImage {
+    if adjust.is_identity() {
+        return img.clone();
+    }
+
+    let mut result = img.clone();
+    for pixel in result.pixels_mut() {
+        let c = adjust.apply(Color::new(pixel[0], pixel[1], pixel[2]));
+        pixel[0] = c.r;
+        pixel[1] = c.g;
+        pixel[2] = c.b;
+    }
+    result
+}
+
+// ── Render loop ──────────────────────────────────────────────────────
+
+/// Main render loop — runs on a dedicated thread, owns the LED matrix.
+///
+/// This function never returns until the channel is closed (sender dropped).
+/// It receives commands and executes them, updating shared status along the way.
+///
+/// ## Interrupt pattern
+/// During long-running operations (video playback, text scrolling), we use
+/// `try_recv()` between frames to check for new commands. If a new command
+/// arrives, we store it in `pending_cmd` and break out of the playback loop.
+/// The main loop then processes the pending command instead of blocking on
+/// `recv()`.
+pub fn render_loop(
+    rx: CountingReceiver<RenderCommand>,
+    status: Arc<Mutex<DisplayStatus>>,
+    config: RenderConfig,
+) {
+    let panel = config.panel;
+    let fonts_dir = &config.fonts_dir;
+    let output = config.output();
+    // Parsed fonts, reused across text commands
+    let mut fonts = FontCache::new(config.fonts_dir.clone());
+
+    // Initialize the matrix — if this fails, we can't do anything
+    let hardware_brightness = limit_brightness(config.hardware_brightness, config.max_brightness());
+    let matrix = match create_matrix_with_options(
+        config.matrix_panel(),
+        hardware_brightness,
+        &config.hardware_mapping,
+    ) {
+        Ok(m) => m,
+        Err(e) => {
+            tracing::error!("Failed to initialize LED matrix: {}", e);
+            return;
+        }
+    };
+
+    let mut screen = Screen::new(matrix, &config);
+    // Start within the cap too, not only after the first brightness command
+    let initial_adjust = ColorAdjust {
+        brightness: limit_brightness(ColorAdjust::default().brightness, config.max_brightness()),
+        ..ColorAdjust::default()
+    };
+    {
+        let mut s = status.lock().unwrap();
+        s.hardware_brightness = hardware_brightness.clamp(1, 100);
+        s.brightness = initial_adjust.brightness;
+        s.max_brightness = config.max_brightness();
+    }
+
+    // Copy of the last full frame drawn, used as the starting point for
+    // transitions and for snapshots
+    let mut last_frame = RgbImage::new(panel.cols, panel.rows);
+
+    // Shared brightness and invert — can be updated without interrupting playback
+    let adjust = Arc::new(Mutex::new(initial_adjust));
+
+    // Pending command — set when a playback loop is interrupted
+    let mut pending_cmd: Option<Queued> = None;
+
+    // Image file reloaded on change while it's shown
+    let mut watched: Option<ImageWatch> = None;
+
+    // When the last display command started, for `min_display`
+    let mut shown_at: Option<Instant> = None;
+
+    // Copy of the running video or animation, to restart after a
+    // `Temporary` command, and the pending return to it
+    let mut current: Option<RenderCommand> = None;
+    let mut restore: Option<PendingRestore> = None;
+
+    // Set when a video ends with `VideoFinish::Dark`: power off as soon as
+    // no command is waiting
+    let mut power_off_next = false;
+
+    tracing::info!("Render thread started, waiting for commands...");
+
+    loop {
+        // A command ends once the loop comes back for the next one
+        config.diagnostics.lock().unwrap().finish(SystemTime::now());
+
+        // Get the next command: either a pending one or wait for a new one
+        let mut queued = if let Some(queued) = pending_cmd.take() {
+            queued
+        } else {
+            let received = if let Some(pending) = &restore {
+                // Wake up in time to go back to what was showing
+                match rx.recv_timeout(pending.at.saturating_duration_since(Instant::now())) {
+                    Err(RecvTimeoutError::Timeout) => {
+                        Ok(Queued::made(restore.take().unwrap().target))
+                    }
+                    received => received.map(|cmd| Queued::received(cmd, &rx)),
+                }
+            } else if std::mem::take(&mut power_off_next) {
+                // Power off right away, unless a command is already waiting
+                rx.try_recv()
+                    .map(|cmd| Queued::received(cmd, &rx))
+                    .map_err(|e| match e {
+                        TryRecvError::Empty => RecvTimeoutError::Timeout,
+                        TryRecvError::Disconnected => RecvTimeoutError::Disconnected,
+                    })
+            } else {
+                match (&mut watched, config.power_off_after) {
+                    (Some(watch), timeout) => recv_watching(
+                        &rx,
+                        timeout,
+                        watch,
+                        &mut screen,
+                        &adjust,
+                        &mut last_frame,
+                        &config,
+                    ),
+                    (None, Some(timeout)) => rx.recv_timeout(timeout),
+                    (None, None) => rx.recv().map_err(RecvTimeoutError::from),
+                }
+                .map(|cmd| Queued::received(cmd, &rx))
+            };
+            match received {
+                Ok(queued) => queued,
+                Err(RecvTimeoutError::Timeout) => {
+                    match power_off_until_command(
+                        screen,
+                        &rx,
+                        &adjust,
+                        &status,
+                        &last_frame,
+                        &config,
+                    ) {
+                        Some((new_screen, queued)) => {
+                            screen = new_screen;
+                            let current_adjust = *adjust.lock().unwrap();
+                            draw_frame_adjusted(
+                                screen.canvas(),
+                                &last_frame,
+                                current_adjust,
+                                output,
+                            );
+                            screen.present();
+                            status.lock().unwrap().set_idle();
+                            queued
+                        }
+                        None => break,
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    tracing::info!("Render thread: channel closed, shutting down.");
+                    break;
+                }
+            }
+        };
+
+        // Keep what's shown up for at least `min_display`. Commands arriving
+        // meanwhile replace this one, so only the latest is shown
+        if !queued.is_adjustment()
+            && let Some(shown_at) = shown_at
+        {
+            let hold_until = shown_at + config.min_display;
+            loop {
+                let now = Instant::now();
+                if now >= hold_until {
+                    break;
+                }
+                match poll_command(
+                    &rx,
+                    &adjust,
+                    &status,
+                    &last_frame,
+                    &mut screen,
+                    &config,
+                    &mut restore,
+                ) {
+                    Some(newer) => {
+                        if let Some(held) = supersede(&mut queued, newer) {
+                            pending_cmd = Some(held);
+                            break;
+                        }
+                    }
+                    None => thread::sleep((hold_until - now).min(HOLD_POLL_INTERVAL)),
+                }
+            }
+        }
+        let Queued { msg: cmd, sent_at } = queued;
+        let (mut cmd, mut done) = cmd.take_done();
+
+        let mut restore_after = None;
+        if let RenderCommand::Temporary {
+            cmd: inner,
+            restore_after: after,
+        } = cmd
+        {
+            cmd = *inner;
+            restore_after = Some(after);
+        }
+
+        // Anything but an in-place adjustment replaces the watched image
+        if !cmd.is_adjustment() {
+            watched = None;
+            shown_at = Some(Instant::now());
+
+            restore = restore_after.map(|after| {
+                // Back-to-back temporary commands all return to what was
+                // showing before the first one
+                let source = status.lock().unwrap().current_media.clone();
+                let target = restore_target(restore.take(), current.take(), &last_frame, source);
+                PendingRestore {
+                    at: Instant::now() + after,
+                    target,
+                }
+            });
+            current = cmd.resume_copy();
+            let mut s = status.lock().unwrap();
+            s.command = Some(ActiveCommand {
+                name: cmd.name().to_string(),
+                params: cmd.details(),
+            });
+            // Set again if the command turns out to be a looping video
+            s.looping = false;
+        }
+
+        config.diagnostics.lock().unwrap().start(
+            cmd.name(),
+            queue_wait(sent_at),
+            SystemTime::now(),
+        );
+
+        match cmd {
+            RenderCommand::Clear { color, fade_ms } => {
+                if fade_ms > 0 {
+                    let start = last_frame.clone();
+                    let fade = Duration::from_millis(fade_ms as u64);
+                    let started = Instant::now();
+                    tracing::info!("Fading to {:?} ({}ms)", color, fade_ms);
+
+                    loop {
+                        if let Some(new_cmd) = poll_command(
+                            &rx,
+                            &adjust,
+                            &status,
+                            &last_frame,
+                            &mut screen,
+                            &config,
+                            &mut restore,
+                        ) {
+                            // Whatever was faded so far stays as the baseline
+                            pending_cmd = Some(new_cmd);
+                            break;
+                        }
+
+                        // From the time taken, so the fade lasts `fade_ms`
+                        // however long drawing takes
+                        let t = (started.elapsed().as_secs_f32() / fade.as_secs_f32()).min(1.0);
+                        last_frame = effects::fade_frame(&start, color.into(), t);
+                        let current_adjust = *adjust.lock().unwrap();
+                        draw_frame_adjusted(screen.canvas(), &last_frame, current_adjust, output);
+                        screen.present();
+
+                        if t >= 1.0 {
+                            break;
+                        }
+                        let remaining = fade.saturating_sub(started.elapsed());
+                        thread::sleep(remaining.min(FADE_STEP));
+                    }
+                }
+
+                if pending_cmd.is_none() {
+                    last_frame = RgbImage::from_pixel(panel.cols, panel.rows, color.into());
+                    let current_adjust = *adjust.lock().unwrap();
+                    draw_frame_adjusted(screen.canvas(), &last_frame, current_adjust, output);
+                    screen.present();
+                    status.lock().unwrap().set_idle();
+                }
+            }
+
+            RenderCommand::Stop { clear } => {
+                if clear {
+                    screen.canvas().clear();
+                    screen.present();
+                    last_frame = RgbImage::new(panel.cols, panel.rows);
+                }
+                status.lock().unwrap().set_idle();
+            }
+
+            RenderCommand::SetBrightness(value) => {
+                let new_brightness = limit_brightness(value, config.max_brightness());
+                adjust.lock().unwrap().brightness = new_brightness;
+                status.lock().unwrap().brightness = new_brightness;
+            }
+
+            RenderCommand::SetInvert(invert) => {
+                adjust.lock().unwrap().invert = invert;
+                status.lock().unwrap().inverted = invert;
+                // Redraw right away so the whole screen flips, not just
+                // whatever is drawn next
+                let current_adjust = *adjust.lock().unwrap();
+                draw_frame_adjusted(screen.canvas(), &last_frame, current_adjust, output);
+                screen.present();
+            }
+
+            RenderCommand::SetLoop(looping) => {
+                tracing::info!("No video playing, ignoring loop {}", looping);
+            }
+
+            RenderCommand::SetHardwareBrightness(value) => {
+                let current_adjust = *adjust.lock().unwrap();
+                match change_hardware_brightness(
+                    screen,
+                    &config,
+                    value,
+                    &status,
+                    &last_frame,
+                    current_adjust,
+                ) {
+                    Some(new_screen) => screen = new_screen,
+                    None => return,
+                }
+            }
+
+            RenderCommand::SaveSnapshot { path, reply } => {
+                // The receiver may have given up waiting; nothing to do then
+                let _ = reply.send(save_snapshot(&last_frame, &path));
+            }
+
+            RenderCommand::Identify { label } => {
+                let current_adjust = *adjust.lock().unwrap();
+                identify(&mut screen, &config, &label, current_adjust, &last_frame);
+            }
+
+            RenderCommand::ResetMatrix { reply } => {
+                let hardware_brightness = status.lock().unwrap().hardware_brightness;
+                screen = match screen.with_hardware_brightness(&config, hardware_brightness) {
+                    Ok(new_screen) => new_screen,
+                    Err(e) => {
+                        tracing::error!("Failed to re-initialize LED matrix: {}", e);
+                        let _ = reply.send(Err(format!("Failed to re-initialize LED matrix: {e}")));
+                        return;
+                    }
+                };
+
+                let current_adjust = *adjust.lock().unwrap();
+                draw_frame_adjusted(screen.canvas(), &last_frame, current_adjust, output);
+                screen.present();
+                status.lock().unwrap().set_idle();
+                tracing::info!("LED matrix reset");
+                let _ = reply.send(Ok(()));
+            }
+
+            RenderCommand::Benchmark { reply } => {
+                let result = run_benchmark(&mut screen, panel, output);
+                tracing::info!(
+                    "Benchmark: {} frames, {:.1} fps, draw={:.2}ms swap={:.2}ms",
+                    result.frames,
+                    result.fps,
+                    result.avg_draw_ms,
+                    result.avg_swap_ms
+                );
+
+                let current_adjust = *adjust.lock().unwrap();
+                draw_frame_adjusted(screen.canvas(), &last_frame, current_adjust, output);
+                screen.present();
+                status.lock().unwrap().set_idle();
+                let _ = reply.send(result);
+            }
+
+            RenderCommand::ShowImage { img, source, watch } => {
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::ShowingImage;
+                    s.current_media = Some(source.clone());
+                    s.frame = None;
+                    s.total_frames = None;
+                }
+
+                let current_adjust = *adjust.lock().unwrap();
+                draw_frame_adjusted(screen.canvas(), &img, current_adjust, output);
+                screen.present();
+                last_frame = img;
+                if watch.is_some() {
+                    tracing::info!("Displaying image: {} (watching for changes)", source);
+                } else {
+                    tracing::info!("Displaying image: {}", source);
+                }
+                watched = watch;
+            }
+
+            RenderCommand::WipeImage {
+                path,
+                direction,
+                duration_ms,
+            } => {
+                let path_str = path.display().to_string();
+                let img = match load_and_resize_image(&path, panel) {
+                    Ok(img) => img,
+                    Err(e) => {
+                        command_failed(
+                            &config,
+                            &status,
+                            &mut current,
+                            format!("Failed to load image {path_str}: {e}"),
+                        );
+                        continue;
+                    }
+                };
+
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::ShowingImage;
+                    s.current_media = Some(path_str.clone());
+                    s.frame = None;
+                    s.total_frames = None;
+                }
+
+                let steps = direction.steps(panel.cols, panel.rows).max(1);
+                let step_delay = Duration::from_millis(duration_ms as u64 / steps as u64);
+                tracing::info!("Wiping to image: {} ({}ms)", path_str, duration_ms);
+
+                'wipe: for revealed in 1..=steps {
+                    if let Some(new_cmd) = poll_command(
+                        &rx,
+                        &adjust,
+                        &status,
+                        &last_frame,
+                        &mut screen,
+                        &config,
+                        &mut restore,
+                    ) {
+                        // Keep whatever was revealed so far as the new baseline
+                        last_frame =
+                            effects::wipe_frame(&last_frame, &img, direction, revealed - 1);
+                        pending_cmd = Some(new_cmd);
+                        break 'wipe;
+                    }
+
+                    let frame = effects::wipe_frame(&last_frame, &img, direction, revealed);
+                    let current_adjust = *adjust.lock().unwrap();
+                    draw_frame_adjusted(screen.canvas(), &frame, current_adjust, output);
+                    screen.present();
+
+                    if revealed < steps {
+                        thread::sleep(step_delay);
+                    }
+                }
+
+                if pending_cmd.is_none() {
+                    last_frame = img;
+                }
+            }
+
+            RenderCommand::Slideshow {
+                dir,
+                interval_ms,
+                transition,
+                shuffle,
+                loop_playback,
+            } => {
+                let mut paths = media::slideshow_images(&dir);
+                if paths.is_empty() {
+                    command_failed(
+                        &config,
+                        &status,
+                        &mut current,
+                        format!("No images for slideshow in {}", dir.display()),
+                    );
+                    continue;
+                }
+                tracing::info!(
+                    "Starting slideshow: {} ({} images, {}ms each)",
+                    dir.display(),
+                    paths.len(),
+                    interval_ms
+                );
+
+                let steps = transition.steps(panel.cols, panel.rows);
+                let step_delay =
+                    Duration::from_millis((SLIDE_TRANSITION_MS.min(interval_ms) / steps) as u64);
+                let interval = Duration::from_millis(interval_ms as u64);
+
+                'slideshow: loop {
+                    if shuffle {
+                        let seed = SystemTime::now()
+                            .duration_since(SystemTime::UNIX_EPOCH)
+                            .map_or(0, |since| since.as_nanos() as u64);
+                        media::shuffle(&mut paths, seed);
+                    }
+
+                    let mut shown_any = false;
+                    for (index, path) in paths.iter().enumerate() {
+                        let img = match load_and_resize_image(path, panel) {
+                            Ok(img) => img,
+                            Err(e) => {
+                                tracing::warn!("Skipping slide {}: {}", path.display(), e);
+                                continue;
+                            }
+                        };
+                        let shown_at = Instant::now();
+                        shown_any = true;
+
+                        {
+                            let mut s = status.lock().unwrap();
+                            s.state = DisplayState::ShowingImage;
+                            s.current_media = Some(path.display().to_string());
+                            s.frame = Some(index);
+                            s.total_frames = Some(paths.len());
+                        }
+
+                        for step in 1..=steps {
+                            if let Some(new_cmd) = poll_command(
+                                &rx,
+                                &adjust,
+                                &status,
+                                &last_frame,
+                                &mut screen,
+                                &config,
+                                &mut restore,
+                            ) {
+                                // Keep the partial transition as the new baseline
+                                last_frame = transition.frame(&last_frame, &img, step - 1, steps);
+                                pending_cmd = Some(new_cmd);
+                                break 'slideshow;
+                            }
+
+                            let frame = transition.frame(&last_frame, &img, step, steps);
+                            let current_adjust = *adjust.lock().unwrap();
+                            draw_frame_adjusted(screen.canvas(), &frame, current_adjust, output);
+                            screen.present();
+
+                            if step < steps {
+                                thread::sleep(step_delay);
+                            }
+                        }
+                        last_frame = img;
+
+                        // The last image of a one-shot slideshow stays up,
+                        // as a still with nothing left to resume
+                        if !loop_playback && index + 1 == paths.len() {
+                            current = None;
+                            break 'slideshow;
+                        }
+
+                        while shown_at.elapsed() < interval {
+                            if let Some(new_cmd) = poll_command(
+                                &rx,
+                                &adjust,
+                                &status,
+                                &last_frame,
+                                &mut screen,
+                                &config,
+                                &mut restore,
+                            ) {
+                                pending_cmd = Some(new_cmd);
+                                break 'slideshow;
+                            }
+                            thread::sleep(
+                                interval
+                                    .saturating_sub(shown_at.elapsed())
+                                    .min(SLIDE_POLL_INTERVAL),
+                            );
+                        }
+                    }
+
+                    if !shown_any {
+                        command_failed(
+                            &config,
+                            &status,
+                            &mut current,
+                            format!("No slideshow images in {} could be loaded", dir.display()),
+                        );
+                        break;
+                    }
+                    if !loop_playback {
+                        break;
+                    }
+                }
+            }
+
+            RenderCommand::ShowTextFile {
+                path,
+                font,
+                color,
+                ansi,
+            } => {
+                let path_str = path.display().to_string();
+                match render_text_file(&path, font.as_deref(), fonts_dir, panel, color, ansi) {
+                    Ok(img) => {
+                        {
+                            let mut s = status.lock().unwrap();
+                            s.state = DisplayState::ShowingText;
+                            s.current_media = Some(path_str.clone());
+                            s.frame = None;
+                            s.total_frames = None;
+                        }
+
+                        let current_adjust = *adjust.lock().unwrap();
+                        draw_frame_adjusted(screen.canvas(), &img, current_adjust, output);
+                        screen.present();
+                        last_frame = img;
+                        tracing::info!("Displaying text file: {}", path_str);
+                    }
+                    Err(e) => {
+                        command_failed(
+                            &config,
+                            &status,
+                            &mut current,
+                            format!("Failed to render text file {path_str}: {e}"),
+                        );
+                    }
+                }
+            }
+
+            RenderCommand::ShowTextGrid { lines } => {
+                let loaded = match lines
+                    .iter()
+                    .map(|line| fonts.get(&line.font))
+                    .collect::<Result<Vec<_>, _>>()
+                {
+                    Ok(loaded) => loaded,
+                    Err(e) => {
+                        command_failed(
+                            &config,
+                            &status,
+                            &mut current,
+                            format!("Failed to load font: {e}"),
+                        );
+                        continue;
+                    }
+                };
+                let stacked: Vec<_> = loaded.iter().map(|font| &**font).zip(&lines).collect();
+                let img = ansi::render_stacked(&stacked, panel.cols, panel.rows);
+
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::ShowingText;
+                    s.current_media = Some(
+                        lines
+                            .iter()
+                            .map(|line| line.text.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" / "),
+                    );
+                    s.frame = None;
+                    s.total_frames = None;
+                }
+
+                let current_adjust = *adjust.lock().unwrap();
+                draw_frame_adjusted(screen.canvas(), &img, current_adjust, output);
+                screen.present();
+                last_frame = img;
+            }
+
+            RenderCommand::ShowSysInfo { font, color } => {
+                let loaded = match fonts.get(&font) {
+                    Ok(loaded) => loaded,
+                    Err(e) => {
+                        command_failed(
+                            &config,
+                            &status,
+                            &mut current,
+                            format!("Failed to load font: {e}"),
+                        );
+                        continue;
+                    }
+                };
+
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::ShowingText;
+                    s.current_media = Some("system stats".to_string());
+                    s.frame = None;
+                    s.total_frames = None;
+                }
+                tracing::info!("Showing system stats");
+
+                let mut last_read: Option<Instant> = None;
+                loop {
+                    if let Some(new_cmd) = poll_command(
+                        &rx,
+                        &adjust,
+                        &status,
+                        &last_frame,
+                        &mut screen,
+                        &config,
+                        &mut restore,
+                    ) {
+                        pending_cmd = Some(new_cmd);
+                        break;
+                    }
+
+                    if last_read.is_none_or(|at| at.elapsed() >= SYSINFO_INTERVAL) {
+                        let lines: Vec<_> = SysInfo::read()
+                            .lines()
+                            .into_iter()
+                            .map(|text| TextLine {
+                                text,
+                                font: font.clone(),
+                                color,
+                                y: None,
+                            })
+                            .collect();
+                        let stacked: Vec<_> = lines.iter().map(|line| (&*loaded, line)).collect();
+                        let img = ansi::render_stacked(&stacked, panel.cols, panel.rows);
+
+                        let current_adjust = *adjust.lock().unwrap();
+                        draw_frame_adjusted(screen.canvas(), &img, current_adjust, output);
+                        screen.present();
+                        last_frame = img;
+                        last_read = Some(Instant::now());
+                    }
+
+                    thread::sleep(SYSINFO_POLL_INTERVAL);
+                }
+            }
+
+            RenderCommand::Timer {
+                seconds,
+                font: font_name,
+                color,
+                on_finish,
+                started_at,
+            } => {
+                let font = match fonts.get(&font_name) {
+                    Ok(f) => f,
+                    Err(e) => {
+                        command_failed(
+                            &config,
+                            &status,
+                            &mut current,
+                            format!("Failed to load font: {e}"),
+                        );
+                        continue;
+                    }
+                };
+                let started_at = started_at.unwrap_or_else(Instant::now);
+                let render = |text: &str| {
+                    let spans = [Span {
+                        text: text.to_string(),
+                        color,
+                    }];
+                    let lines = ansi::wrap(&font, &spans, panel.cols);
+                    ansi::render_centered(&font, &lines, panel.cols, panel.rows)
+                };
+
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::ShowingText;
+                    s.current_media = Some(format!("timer {}", timer::format_mm_ss(seconds)));
+                    s.frame = None;
+                    s.total_frames = None;
+                }
+                tracing::info!("Timer: {}", timer::format_mm_ss(seconds));
+
+                // Redraw only when the whole seconds left change
+                let mut shown = None;
+                let finished = loop {
+                    if let Some(new_cmd) = poll_command(
+                        &rx,
+                        &adjust,
+                        &status,
+                        &last_frame,
+                        &mut screen,
+                        &config,
+                        &mut restore,
+                    ) {
+                        pending_cmd = Some(new_cmd);
+                        break false;
+                    }
+
+                    let left = timer::seconds_left(seconds, started_at.elapsed());
+                    if shown != Some(left) {
+                        let frame = render(&timer::format_mm_ss(left));
+                        let current_adjust = *adjust.lock().unwrap();
+                        draw_frame_adjusted(screen.canvas(), &frame, current_adjust, output);
+                        screen.present();
+                        last_frame = frame;
+                        shown = Some(left);
+                    }
+                    if left == 0 {
+                        break true;
+                    }
+
+                    thread::sleep(TIMER_POLL_INTERVAL);
+                };
+                if finished {
+                    tracing::info!("Timer finished");
+                    // Resuming a finished timer would run its finish action
+                    // again; a temporary command comes back to the last frame
+                    current = None;
+                    match on_finish {
+                        TimerFinish::Flash => {
+                            // Blank and readout in turn, ending on the readout
+                            let blank = RgbImage::new(panel.cols, panel.rows);
+                            for flash in 1..=TIMER_FLASHES * 2 {
+                                if let Some(new_cmd) = poll_command(
+                                    &rx,
+                                    &adjust,
+                                    &status,
+                                    &last_frame,
+                                    &mut screen,
+                                    &config,
+                                    &mut restore,
+                                ) {
+                                    pending_cmd = Some(new_cmd);
+                                    break;
+                                }
+                                let img = if flash % 2 == 1 { &blank } else { &last_frame };
+                                let current_adjust = *adjust.lock().unwrap();
+                                draw_frame_adjusted(screen.canvas(), img, current_adjust, output);
+                                screen.present();
+                                thread::sleep(TIMER_FLASH_TIME);
+                            }
+                        }
+                        TimerFinish::Clear => {
+                            screen.canvas().clear();
+                            screen.present();
+                            last_frame = RgbImage::new(panel.cols, panel.rows);
+                        }
+                        TimerFinish::Text(text) => {
+                            let frame = render(&text);
+                            let current_adjust = *adjust.lock().unwrap();
+                            draw_frame_adjusted(screen.canvas(), &frame, current_adjust, output);
+                            screen.present();
+                            last_frame = frame;
+                        }
+                    }
+                    if pending_cmd.is_none() {
+                        status.lock().unwrap().set_idle();
+                    }
+                }
+            }
+
+            RenderCommand::ShowFrame(mut data) => {
+                // While the next swap has to wait for `max_refresh_fps`,
+                // newer frames replace this one rather than queue behind it
+                while pending_cmd.is_none()
+                    && !screen.pacer.remaining(Instant::now()).is_zero()
+                    && let Ok(next) = rx.try_recv()
+                {
+                    let (next, next_done) = next.take_done();
+                    match next {
+                        RenderCommand::ShowFrame(newer) => {
+                            data = newer;
+                            signal_done(std::mem::replace(&mut done, next_done));
+                        }
+                        next => {
+                            pending_cmd = Some(Queued::received(next.with_done(next_done), &rx))
+                        }
+                    }
+                }
+                let expected = panel.frame_byte_count();
+                if config.skip_duplicate_frames && frame::is_duplicate(last_frame.as_raw(), &data) {
+                    status.lock().unwrap().duplicate_frames_skipped += 1;
+                } else if data.len() == expected {
+                    let current_adjust = *adjust.lock().unwrap();
+                    draw_raw_frame(screen.canvas(), &data, panel, current_adjust, output);
+                    screen.present();
+                    if let Some(img) = RgbImage::from_raw(panel.cols, panel.rows, data) {
+                        last_frame = img;
+                    }
+                } else {
+                    command_failed(
+                        &config,
+                        &status,
+                        &mut current,
+                        format!(
+                            "Invalid frame size: expected {expected} bytes, got {}",
+                            data.len()
+                        ),
+                    );
+                }
+            }
+
+            RenderCommand::DrawGauge {
+                value,
+                min,
+                max,
+                color,
+                style,
+                track,
+            } => {
+                let fraction = draw::gauge_fraction(value, min, max);
+                let img = draw::gauge(panel.cols, panel.rows, fraction, style, color, track);
+
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::Drawing;
+                    s.current_media = Some(format!("gauge {value} in [{min}, {max}]"));
+                    s.frame = None;
+                    s.total_frames = None;
+                }
+
+                let current_adjust = *adjust.lock().unwrap();
+                draw_frame_adjusted(screen.canvas(), &img, current_adjust, output);
+                screen.present();
+                last_frame = img;
+            }
+
+            RenderCommand::ShowQr {
+                data,
+                color,
+                background,
+            } => {
+                let img = match draw::qr_code(&data, panel.cols, panel.rows, color, background) {
+                    Ok(img) => img,
+                    Err(e) => {
+                        command_failed(&config, &status, &mut current, e);
+                        continue;
+                    }
+                };
+
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::Drawing;
+                    s.current_media = Some(format!("qr {data}"));
+                    s.frame = None;
+                    s.total_frames = None;
+                }
+
+                let current_adjust = *adjust.lock().unwrap();
+                draw_frame_adjusted(screen.canvas(), &img, current_adjust, output);
+                screen.present();
+                last_frame = img;
+            }
+
+            RenderCommand::DrawPolygon {
+                points,
+                color,
+                fill,
+            } => {
+                let img = draw::polygon(panel.cols, panel.rows, &points, color, fill);
+
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::Drawing;
+                    s.current_media = Some(format!("polygon ({} points)", points.len()));
+                    s.frame = None;
+                    s.total_frames = None;
+                }
+
+                let current_adjust = *adjust.lock().unwrap();
+                draw_frame_adjusted(screen.canvas(), &img, current_adjust, output);
+                screen.present();
+                last_frame = img;
+            }
+
+            RenderCommand::ShowSprite {
+                name,
+                sprite,
+                x,
+                y,
+                scale,
+                color,
+                clear,
+            } => {
+                let mut img = if clear {
+                    RgbImage::new(panel.cols, panel.rows)
+                } else {
+                    last_frame.clone()
+                };
+                sprites::blit(&mut img, &sprite, x, y, scale, color.into());
+
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::Drawing;
+                    s.current_media = Some(format!("sprite {name}"));
+                    s.frame = None;
+                    s.total_frames = None;
+                }
+
+                let current_adjust = *adjust.lock().unwrap();
+                draw_frame_adjusted(screen.canvas(), &img, current_adjust, output);
+                screen.present();
+                last_frame = img;
+            }
+
+            RenderCommand::Gradient {
+                from,
+                to,
+                direction,
+            } => {
+                let img = draw::gradient(panel.cols, panel.rows, from, to, direction);
 
-/// Draw an image to canvas with brightness scaling applied.
-fn draw_frame_with_brightness(canvas: &mut LedCanvas, img: &RgbImage, brightness: u8) {
-    if brightness >= 100 {
-        draw_frame_to_canvas(canvas, img);
-    } else {
-        for (x, y, pixel) in img.enumerate_pixels() {
-            let c = Color::new(pixel[0], pixel[1], pixel[2]).apply_brightness(brightness);
-            canvas.set(x as i32, y as i32, &c.into());
-        }
-    }
-}
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::Drawing;
+                    s.current_media = Some("gradient".to_string());
+                    s.frame = None;
+                    s.total_frames = None;
+                }
 
-/// Draw raw RGB bytes to canvas with brightness scaling.
-fn draw_raw_frame(canvas: &mut LedCanvas, data: &[u8], panel: PanelConfig, brightness: u8) {
-    for y in 0..panel.rows {
-        for x in 0..panel.cols {
-            let offset = ((y * panel.cols + x) * 3) as usize;
-            let c = Color::new(data[offset], data[offset + 1], data[offset + 2])
-                .apply_brightness(brightness);
-            canvas.set(x as i32, y as i32, &c.into());
-        }
-    }
-}
+                let current_adjust = *adjust.lock().unwrap();
+                draw_frame_adjusted(screen.canvas(), &img, current_adjust, output);
+                screen.present();
+                last_frame = img;
+            }
 
-/// Apply brightness to an entire image, returning a new image.
-fn apply_brightness_to_image(img: &RgbImage, brightness: u8) -> RgbImage {
-    if brightness >= 100 {
-        return img.clone();
-    }
+            RenderCommand::ScrollBitmap { image, speed } => {
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::ScrollingText;
+                    s.current_media = Some(format!("bitmap {}x{}", image.width(), image.height()));
+                    s.frame = None;
+                    s.total_frames = None;
+                }
 
-    let mut result = img.clone();
-    for pixel in result.pixels_mut() {
-        let c = Color::new(pixel[0], pixel[1], pixel[2]).apply_brightness(brightness);
-        pixel[0] = c.r;
-        pixel[1] = c.g;
-        pixel[2] = c.b;
-    }
-    result
-}
+                // Like text: from the right edge until fully off the left
+                let start_x = panel.cols as i32;
+                let end_x = -(image.width() as i32);
+                let mut scroll = effects::Scroll::new(start_x, end_x, speed);
+                let mut last_step = Instant::now();
+
+                loop {
+                    let polled_at = Instant::now();
+                    if let Some(new_cmd) = poll_command(
+                        &rx,
+                        &adjust,
+                        &status,
+                        &last_frame,
+                        &mut screen,
+                        &config,
+                        &mut restore,
+                    ) {
+                        pending_cmd = Some(new_cmd);
+                        break;
+                    }
+                    // An in-place Identify can take seconds; don't jump ahead
+                    last_step += polled_at.elapsed();
+
+                    let frame = effects::scroll_frame(&image, scroll.x(), panel.cols, panel.rows);
+                    let current_adjust = *adjust.lock().unwrap();
+                    draw_frame_adjusted(screen.canvas(), &frame, current_adjust, output);
+                    screen.present();
+                    last_frame = frame;
+
+                    thread::sleep(scroll.until_next_pixel());
+                    let now = Instant::now();
+                    scroll.advance(now - last_step);
+                    last_step = now;
+                }
+            }
 
-// ── Render loop ──────────────────────────────────────────────────────
+            RenderCommand::IconMarquee {
+                icon_path,
+                text,
+                font: font_name,
+                color,
+                speed,
+            } => {
+                let icon_size = panel.rows.min(panel.cols / 2);
+                let icon = match load_and_fit_image(
+                    &icon_path,
+                    PanelConfig::new(icon_size, icon_size),
+                    FitMode::Contain,
+                    Color::new(0, 0, 0),
+                ) {
+                    Ok(img) => img,
+                    Err(e) => {
+                        command_failed(
+                            &config,
+                            &status,
+                            &mut current,
+                            format!("Failed to load icon {}: {e}", icon_path.display()),
+                        );
+                        continue;
+                    }
+                };
+                let font = match fonts.get(&font_name) {
+                    Ok(f) => f,
+                    Err(e) => {
+                        command_failed(
+                            &config,
+                            &status,
+                            &mut current,
+                            format!("Failed to load font: {e}"),
+                        );
+                        continue;
+                    }
+                };
 
-/// Main render loop — runs on a dedicated thread, owns the LED matrix.
-///
-/// This function never returns until the channel is closed (sender dropped).
-/// It receives commands and executes them, updating shared status along the way.
-///
-/// ## Interrupt pattern
-/// During long-running operations (video playback, text scrolling), we use
-/// `try_recv()` between frames to check for new commands. If a new command
-/// arrives, we store it in `pending_cmd` and break out of the playback loop.
-/// The main loop then processes the pending command instead of blocking on
-/// `recv()`.
-pub fn render_loop(
-    rx: Receiver<RenderCommand>,
-    status: Arc<Mutex<DisplayStatus>>,
-    fonts_dir: PathBuf,
-    panel: PanelConfig,
-) {
-    // Initialize the matrix — if this fails, we can't do anything
-    let matrix = match create_matrix(panel) {
-        Ok(m) => m,
-        Err(e) => {
-            tracing::error!("Failed to initialize LED matrix: {}", e);
-            return;
-        }
-    };
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::ScrollingText;
+                    s.current_media = Some(text.clone());
+                    s.frame = None;
+                    s.total_frames = None;
+                }
 
-    let mut canvas = matrix.offscreen_canvas();
+                let strip = font.render(&text, color.into(), image::Rgb([0, 0, 0]), 0);
+                // Positions are relative to the text area right of the icon
+                let start_x = (panel.cols - icon_size) as i32;
+                let end_x = -(strip.width() as i32);
+                let mut scroll = effects::Scroll::new(start_x, end_x, speed);
+                let mut last_step = Instant::now();
+
+                loop {
+                    let polled_at = Instant::now();
+                    if let Some(new_cmd) = poll_command(
+                        &rx,
+                        &adjust,
+                        &status,
+                        &last_frame,
+                        &mut screen,
+                        &config,
+                        &mut restore,
+                    ) {
+                        pending_cmd = Some(new_cmd);
+                        break;
+                    }
+                    // An in-place Identify can take seconds; don't jump ahead
+                    last_step += polled_at.elapsed();
+
+                    let frame =
+                        effects::marquee_frame(&icon, &strip, scroll.x(), panel.cols, panel.rows);
+                    let current_adjust = *adjust.lock().unwrap();
+                    draw_frame_adjusted(screen.canvas(), &frame, current_adjust, output);
+                    screen.present();
+                    last_frame = frame;
+
+                    thread::sleep(scroll.until_next_pixel());
+                    let now = Instant::now();
+                    scroll.advance(now - last_step);
+                    last_step = now;
+                }
+            }
 
-    // Shared brightness — can be updated without interrupting playback
-    let brightness = Arc::new(Mutex::new(75u8));
+            RenderCommand::PlayFrames {
+                frames,
+                delays,
+                loop_playback,
+            } => {
+                let total_frames = frames.len();
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::PlayingVideo;
+                    s.current_media = Some("uploaded frames".to_string());
+                    s.frame = Some(0);
+                    s.total_frames = Some(total_frames);
+                }
+                tracing::info!("Playing {} uploaded frames", total_frames);
+
+                'frames: loop {
+                    for (i, (frame, delay)) in frames.iter().zip(&delays).enumerate() {
+                        if let Some(new_cmd) = poll_command(
+                            &rx,
+                            &adjust,
+                            &status,
+                            &last_frame,
+                            &mut screen,
+                            &config,
+                            &mut restore,
+                        ) {
+                            pending_cmd = Some(new_cmd);
+                            break 'frames;
+                        }
 
-    // Pending command — set when a playback loop is interrupted
-    let mut pending_cmd: Option<RenderCommand> = None;
+                        let current_adjust = *adjust.lock().unwrap();
+                        draw_frame_adjusted(screen.canvas(), frame, current_adjust, output);
+                        screen.present();
+                        last_frame = frame.clone();
+                        status.lock().unwrap().frame = Some(i);
 
-    tracing::info!("Render thread started, waiting for commands...");
+                        thread::sleep(*delay);
+                    }
 
-    loop {
-        // Get the next command: either a pending one or wait for a new one
-        let cmd = if let Some(cmd) = pending_cmd.take() {
-            cmd
-        } else {
-            match rx.recv() {
-                Ok(cmd) => cmd,
-                Err(_) => {
-                    tracing::info!("Render thread: channel closed, shutting down.");
-                    break;
+                    if !loop_playback {
+                        status.lock().unwrap().set_idle();
+                        current = None;
+                        break;
+                    }
                 }
             }
-        };
 
-        match cmd {
-            RenderCommand::Clear => {
-                canvas.clear();
-                canvas = matrix.swap(canvas);
-                status.lock().unwrap().set_idle();
-            }
+            RenderCommand::TypeText {
+                text,
+                font: font_name,
+                color,
+                char_delay_ms,
+            } => {
+                let font = match fonts.get(&font_name) {
+                    Ok(f) => f,
+                    Err(e) => {
+                        command_failed(
+                            &config,
+                            &status,
+                            &mut current,
+                            format!("Failed to load font: {e}"),
+                        );
+                        continue;
+                    }
+                };
 
-            RenderCommand::Stop => {
-                status.lock().unwrap().set_idle();
-            }
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::ShowingText;
+                    s.current_media = Some(text.clone());
+                    s.frame = None;
+                    s.total_frames = None;
+                }
 
-            RenderCommand::SetBrightness(value) => {
-                let new_brightness = value.min(100);
-                *brightness.lock().unwrap() = new_brightness;
-                status.lock().unwrap().brightness = new_brightness;
+                let lines = ansi::wrap(&font, &[Span { text, color }], panel.cols);
+                let total = ansi::char_count(&lines);
+                let char_delay = Duration::from_millis(char_delay_ms as u64);
+
+                for visible in 1..=total {
+                    if let Some(new_cmd) = poll_command(
+                        &rx,
+                        &adjust,
+                        &status,
+                        &last_frame,
+                        &mut screen,
+                        &config,
+                        &mut restore,
+                    ) {
+                        pending_cmd = Some(new_cmd);
+                        break;
+                    }
+
+                    let frame = ansi::render_centered_partial(
+                        &font, &lines, visible, panel.cols, panel.rows,
+                    );
+                    let current_adjust = *adjust.lock().unwrap();
+                    draw_frame_adjusted(screen.canvas(), &frame, current_adjust, output);
+                    screen.present();
+                    last_frame = frame;
+
+                    if visible < total {
+                        thread::sleep(char_delay);
+                    }
+                }
             }
 
-            RenderCommand::ShowImage(path) => {
-                let path_str = path.display().to_string();
+            RenderCommand::ColorWheel { speed } => {
                 {
                     let mut s = status.lock().unwrap();
-                    s.state = DisplayState::ShowingImage;
-                    s.current_media = Some(path_str.clone());
+                    s.state = DisplayState::Animating;
+                    s.current_media = Some("color wheel".to_string());
                     s.frame = None;
                     s.total_frames = None;
                 }
 
-                match load_and_resize_image(&path, panel) {
-                    Ok(img) => {
-                        let current_brightness = *brightness.lock().unwrap();
-                        draw_frame_with_brightness(&mut canvas, &img, current_brightness);
-                        canvas = matrix.swap(canvas);
-                        tracing::info!("Displaying image: {}", path_str);
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to load image {}: {}", path_str, e);
-                        status.lock().unwrap().set_idle();
+                let wheel = effects::ColorWheel::new(panel.cols, panel.rows);
+                let frame_delay = Duration::from_millis(33); // ~30 fps
+                let start = std::time::Instant::now();
+                tracing::info!("Color wheel at {} deg/s", speed);
+
+                loop {
+                    if let Some(new_cmd) = poll_command(
+                        &rx,
+                        &adjust,
+                        &status,
+                        &last_frame,
+                        &mut screen,
+                        &config,
+                        &mut restore,
+                    ) {
+                        pending_cmd = Some(new_cmd);
+                        break;
                     }
+
+                    let phase = start.elapsed().as_secs_f32() * speed as f32;
+                    let frame = wheel.frame(phase);
+                    let current_adjust = *adjust.lock().unwrap();
+                    draw_frame_adjusted(screen.canvas(), &frame, current_adjust, output);
+                    screen.present();
+                    last_frame = frame;
+
+                    thread::sleep(frame_delay);
                 }
             }
 
-            RenderCommand::ShowFrame(data) => {
-                let expected = panel.frame_byte_count();
-                if data.len() == expected {
-                    let current_brightness = *brightness.lock().unwrap();
-                    draw_raw_frame(&mut canvas, &data, panel, current_brightness);
-                    canvas = matrix.swap(canvas);
-                } else {
-                    tracing::error!(
-                        "Invalid frame size: expected {} bytes, got {}",
-                        expected,
-                        data.len()
-                    );
+            RenderCommand::Effect { kind, speed, color } => {
+                {
+                    let mut s = status.lock().unwrap();
+                    s.state = DisplayState::Animating;
+                    s.current_media = Some(kind.label().to_string());
+                    s.frame = None;
+                    s.total_frames = None;
+                }
+
+                let seed = || {
+                    SystemTime::now()
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .map_or(0, |since| since.as_nanos() as u64)
+                };
+                let mut life = sim::Life::random(panel.cols, panel.rows, seed());
+                // The board two generations back, to spot blinkers
+                let mut before = None;
+                let mut ball = sim::Bounce::new(panel.cols, panel.rows, speed);
+                let frame_delay = match kind {
+                    EffectKind::GameOfLife => Duration::from_secs(1) / speed.max(1),
+                    EffectKind::Bounce => Duration::from_millis(33), // ~30 fps
+                };
+                let mut last_step = Instant::now();
+                tracing::info!("{} at speed {}", kind.label(), speed);
+
+                loop {
+                    let polled_at = Instant::now();
+                    if let Some(new_cmd) = poll_command(
+                        &rx,
+                        &adjust,
+                        &status,
+                        &last_frame,
+                        &mut screen,
+                        &config,
+                        &mut restore,
+                    ) {
+                        pending_cmd = Some(new_cmd);
+                        break;
+                    }
+                    // An in-place Identify can take seconds; don't jump ahead
+                    last_step += polled_at.elapsed();
+
+                    let frame = match kind {
+                        EffectKind::GameOfLife => life.frame(color),
+                        EffectKind::Bounce => ball.frame(color),
+                    };
+                    let current_adjust = *adjust.lock().unwrap();
+                    draw_frame_adjusted(screen.canvas(), &frame, current_adjust, output);
+                    screen.present();
+                    last_frame = frame;
+
+                    thread::sleep(frame_delay);
+                    match kind {
+                        EffectKind::GameOfLife => {
+                            let next = life.step();
+                            // Dead, still, or blinking: start over
+                            if next.population() == 0
+                                || next == life
+                                || before.as_ref() == Some(&next)
+                            {
+                                life = sim::Life::random(panel.cols, panel.rows, seed());
+                                before = None;
+                            } else {
+                                before = Some(std::mem::replace(&mut life, next));
+                            }
+                        }
+                        EffectKind::Bounce => {
+                            let now = Instant::now();
+                            ball.step(now - last_step);
+                            last_step = now;
+                        }
+                    }
                 }
             }
 
             RenderCommand::PlayVideo {
                 dir,
                 fps,
-                loop_playback,
+                loop_count,
+                fit,
+                background,
+                show_title,
+                on_finish,
             } => {
                 let dir_str = dir.display().to_string();
 
                 let frame_paths = match load_frame_paths(&dir) {
                     Ok(p) => p,
                     Err(e) => {
-                        tracing::error!("Failed to load video frames from {}: {}", dir_str, e);
+                        command_failed(
+                            &config,
+                            &status,
+                            &mut current,
+                            format!("Failed to load video frames from {dir_str}: {e}"),
+                        );
                         continue;
                     }
                 };
 
                 // Get current brightness before loading frames
-                let current_brightness = *brightness.lock().unwrap();
+                let current_adjust = *adjust.lock().unwrap();
 
                 // Pre-load all frames into memory with brightness pre-applied
                 tracing::info!(
-                    "Pre-loading {} frames from {} (brightness: {})...",
+                    "Pre-loading {} frames from {} (brightness: {}, inverted: {})...",
                     frame_paths.len(),
                     dir_str,
-                    current_brightness
+                    current_adjust.brightness,
+                    current_adjust.invert
                 );
                 let mut frames: Vec<RgbImage> = Vec::new();
+                // The same frames before brightness and invert, kept for
+                // `last_frame`: every redraw of it applies them again
+                let mut fitted_frames: Vec<RgbImage> = Vec::new();
+                let mut failed = 0;
                 for (i, path) in frame_paths.iter().enumerate() {
                     match load_frame(path) {
                         Ok(img) => {
+                            // Fit once here so playback only ever draws panel-sized frames
+                            let img = layout::fit_image(
+                                &img,
+                                panel.cols,
+                                panel.rows,
+                                fit,
+                                background.into(),
+                            );
                             // Pre-apply brightness to eliminate per-pixel math during playback
-                            let adjusted = apply_brightness_to_image(&img, current_brightness);
+                            let adjusted = adjust_image(&img, current_adjust);
                             frames.push(adjusted);
+                            fitted_frames.push(img);
                         }
                         Err(e) => {
                             tracing::warn!("Failed to pre-load frame {}: {}", i, e);
-                            // Continue with frames we have
+                            // Hold the previous frame (black at the start) so a
+                            // corrupt file doesn't shift the rest of the timing
+                            let previous = frames
+                                .last()
+                                .cloned()
+                                .unwrap_or_else(|| RgbImage::new(panel.cols, panel.rows));
+                            frames.push(previous);
+                            let previous = fitted_frames
+                                .last()
+                                .cloned()
+                                .unwrap_or_else(|| RgbImage::new(panel.cols, panel.rows));
+                            fitted_frames.push(previous);
+                            failed += 1;
                         }
                     }
                 }
 
-                if frames.is_empty() {
-                    tracing::error!("No frames loaded from {}", dir_str);
+                if failed > 0 {
+                    status.lock().unwrap().failed_video_frames += failed as u64;
+                }
+                if failed == frames.len() {
+                    command_failed(
+                        &config,
+                        &status,
+                        &mut current,
+                        format!("No frames loaded from {dir_str}"),
+                    );
                     continue;
                 }
+                if failed > 0 {
+                    tracing::warn!(
+                        "{} of {} frames in {} failed to load and were replaced by the previous frame",
+                        failed,
+                        frames.len(),
+                        dir_str
+                    );
+                }
 
                 let frame_count = frames.len();
                 let frame_duration = Duration::from_millis(1000 / fps.max(1) as u64);
@@ -370,6 +3003,7 @@ pub fn render_loop(
                     s.current_media = Some(dir_str.clone());
                     s.frame = Some(0);
                     s.total_frames = Some(frame_count);
+                    s.looping = loop_count.is_none();
                 }
 
                 tracing::info!(
@@ -379,7 +3013,29 @@ pub fn render_loop(
                     fps
                 );
 
+                // Title overlay for the start of playback. It's composited per
+                // frame (the frames stay untouched for later loops), so skip it
+                // rather than fail the video if the font is missing.
+                let mut title = None;
+                if show_title {
+                    match fonts.get(TITLE_FONT) {
+                        Ok(font) => {
+                            let name = dir
+                                .file_name()
+                                .map_or(dir_str.clone(), |n| n.to_string_lossy().into_owned());
+                            title = Some((font, name));
+                        }
+                        Err(e) => tracing::warn!("Failed to load title font: {}", e),
+                    }
+                }
+                // Not inverted: the title band stays dark either way
+                let title_color = color(255, 255, 255)
+                    .apply_brightness(current_adjust.brightness)
+                    .into();
+                let playback_start = std::time::Instant::now();
+
                 let mut frame_index = 0;
+                let mut passes = Passes::new(loop_count);
 
                 // Track frame timing for performance debugging
                 let mut slow_frame_count = 0;
@@ -388,38 +3044,66 @@ pub fn render_loop(
                 'playback: loop {
                     let frame_start = std::time::Instant::now();
 
-                    // Check for new commands (non-blocking)
-                    if let Ok(new_cmd) = rx.try_recv() {
-                        // Brightness changes won't affect current playback (already applied to frames)
-                        match new_cmd {
-                            RenderCommand::SetBrightness(value) => {
-                                let new_brightness = value.min(100);
-                                *brightness.lock().unwrap() = new_brightness;
-                                status.lock().unwrap().brightness = new_brightness;
-                                tracing::info!(
-                                    "Brightness set to {} (will apply to next video)",
-                                    new_brightness
-                                );
-                                // Continue playback with current frames
-                            }
-                            _ => {
-                                // Any other command interrupts playback
-                                pending_cmd = Some(new_cmd);
-                                break 'playback;
-                            }
-                        }
+                    if let Some(target) = take_due_restore(&mut restore) {
+                        pending_cmd = Some(Queued::made(target));
+                        break 'playback;
+                    }
+
+                    // Check for new commands (non-blocking). Brightness
+                    // and invert only apply to the next video, as the
+                    // frames are already adjusted
+                    if let Ok(new_cmd) = rx.try_recv()
+                        && let Some(new_cmd) = handle_in_place(
+                            Queued::received(new_cmd, &rx),
+                            &adjust,
+                            &status,
+                            &last_frame,
+                            Some(&mut screen),
+                            Some(&mut passes),
+                            &config,
+                        )
+                    {
+                        // Any other command interrupts playback
+                        pending_cmd = Some(new_cmd);
+                        break 'playback;
+                    }
+
+                    // Drop frames that come faster than `max_refresh_fps`
+                    // rather than slow the video down; the last one is always
+                    // shown, as it's what `hold` leaves up
+                    if frame_index + 1 < frame_count
+                        && !screen.pacer.remaining(Instant::now()).is_zero()
+                    {
+                        frame_index += 1;
+                        thread::sleep(frame_duration);
+                        continue 'playback;
                     }
 
                     // Draw frame from pre-loaded memory (brightness already applied)
-                    let img = &frames[frame_index];
+                    let mut img = &frames[frame_index];
+                    let titled;
+                    if let Some((font, name)) = &title {
+                        let opacity = effects::fade_out_opacity(
+                            playback_start.elapsed(),
+                            TITLE_HOLD,
+                            TITLE_FADE,
+                        );
+                        if opacity > 0.0 {
+                            titled = effects::title_overlay(img, font, name, title_color, opacity);
+                            img = &titled;
+                        } else {
+                            title = None;
+                        }
+                    }
 
                     let draw_start = std::time::Instant::now();
-                    draw_frame_to_canvas(&mut canvas, img);
+                    draw_frame_to_canvas(screen.canvas(), img, output);
                     let draw_time = draw_start.elapsed();
 
                     let swap_start = std::time::Instant::now();
-                    canvas = matrix.swap(canvas);
+                    screen.present();
                     let swap_time = swap_start.elapsed();
+                    last_frame.clone_from(&fitted_frames[frame_index]);
 
                     // Log timing details for first few frames
                     let frame_time = frame_start.elapsed();
@@ -457,13 +3141,24 @@ pub fn render_loop(
                     frame_index += 1;
 
                     if frame_index >= frame_count {
-                        if loop_playback {
+                        if passes.finish_pass() {
                             frame_index = 0;
                         } else {
-                            // Clear display when non-looping video finishes
-                            canvas.clear();
-                            canvas = matrix.swap(canvas);
-                            status.lock().unwrap().set_idle();
+                            match on_finish {
+                                VideoFinish::Clear => {
+                                    screen.canvas().clear();
+                                    screen.present();
+                                    last_frame = RgbImage::new(panel.cols, panel.rows);
+                                }
+                                VideoFinish::Hold => {}
+                                VideoFinish::Dark => {
+                                    // Wake up to a blank panel, not the old frame
+                                    last_frame = RgbImage::new(panel.cols, panel.rows);
+                                    power_off_next = true;
+                                }
+                            }
+                            status.lock().unwrap().finish_video(on_finish);
+                            current = None;
                             if slow_frame_count > 0 {
                                 tracing::warn!(
                                     "Video finished with {} slow frames out of {}",
@@ -481,73 +3176,452 @@ pub fn render_loop(
             }
 
             RenderCommand::ScrollText {
-                text,
+                segments,
                 font: font_name,
-                color: (r, g, b),
                 speed,
+                mode,
+                x,
+                y,
+                repeat,
             } => {
-                let font_path = fonts_dir.join(format!("{font_name}.bdf"));
-                let font = match LedFont::new(&font_path) {
+                let font = match fonts.get(&font_name) {
                     Ok(f) => f,
                     Err(e) => {
-                        tracing::error!("Failed to load font {}: {}", font_path.display(), e);
+                        command_failed(
+                            &config,
+                            &status,
+                            &mut current,
+                            format!("Failed to load font: {e}"),
+                        );
                         continue;
                     }
                 };
 
+                if mode == TextMode::Auto {
+                    let lines = ansi::wrap(&font, &segments, panel.cols);
+                    let (_, text_height) = ansi::text_size(&font, &lines);
+                    if text_height <= panel.rows as i32 {
+                        {
+                            let mut s = status.lock().unwrap();
+                            s.state = DisplayState::ShowingText;
+                            s.current_media =
+                                Some(segments.iter().map(|seg| seg.text.as_str()).collect());
+                            s.frame = None;
+                            s.total_frames = None;
+                        }
+
+                        let img = ansi::render_centered(&font, &lines, panel.cols, panel.rows);
+                        let current_adjust = *adjust.lock().unwrap();
+                        draw_frame_adjusted(screen.canvas(), &img, current_adjust, output);
+                        screen.present();
+                        last_frame = img;
+                        signal_done(done);
+                        continue;
+                    }
+                    tracing::info!("Text is too tall for the panel when wrapped, scrolling");
+                }
+
+                // Scroll from right edge to off the left side, then loop
+                // (or stop after `repeat` passes). The first pass may
+                // start from `x` instead
+                let text_width: i32 = segments.iter().map(|seg| font.text_width(&seg.text)).sum();
+                let start_x = panel.cols as i32;
+                let end_x = -text_width;
+                let y_pos = y.baseline(&font, panel.rows);
+                let first_x = x.unwrap_or(start_x);
+                // The server already turns these away; this catches wrapped
+                // text in `TextMode::Auto` that turned out to need scrolling
+                let top = y.top(&font, panel.rows);
+                if !effects::scroll_start_visible(
+                    first_x,
+                    top,
+                    text_width,
+                    font.height(),
+                    panel.rows,
+                ) {
+                    command_failed(
+                        &config,
+                        &status,
+                        &mut current,
+                        format!(
+                            "Text starting at ({first_x}, {top}) would never be visible on a {}x{} panel",
+                            panel.cols, panel.rows
+                        ),
+                    );
+                    continue;
+                }
+
                 {
                     let mut s = status.lock().unwrap();
                     s.state = DisplayState::ScrollingText;
-                    s.current_media = Some(text.clone());
+                    s.current_media = Some(segments.iter().map(|seg| seg.text.as_str()).collect());
                     s.frame = None;
                     s.total_frames = None;
                 }
 
-                // Scroll from right edge to off the left side, then loop
-                let text_width = (text.len() as i32) * 8;
-                let start_x = panel.cols as i32;
-                let end_x = -text_width;
-                let y_pos = 40; // Roughly vertically centered
-                let scroll_delay = Duration::from_millis(1000 / speed.max(1) as u64);
-
-                let mut x = start_x;
-                // Cache brightness locally to avoid mutex lock on every frame
-                let mut current_brightness = *brightness.lock().unwrap();
+                let mut scroll = effects::Scroll::new(start_x, end_x, speed).starting_at(first_x);
+                let mut last_step = Instant::now();
+                // Cache adjustments locally to avoid mutex lock on every frame
+                let mut current_adjust = *adjust.lock().unwrap();
 
                 'scroll: loop {
+                    if let Some(target) = take_due_restore(&mut restore) {
+                        pending_cmd = Some(Queued::made(target));
+                        break 'scroll;
+                    }
+
                     // Check for new commands (non-blocking)
+                    let polled_at = Instant::now();
                     if let Ok(new_cmd) = rx.try_recv() {
-                        // Allow brightness changes without interrupting scrolling
-                        match new_cmd {
-                            RenderCommand::SetBrightness(value) => {
-                                current_brightness = value.min(100);
-                                *brightness.lock().unwrap() = current_brightness;
-                                status.lock().unwrap().brightness = current_brightness;
-                                // Continue scrolling
-                            }
-                            _ => {
+                        // Brightness and the like change without interrupting scrolling
+                        match handle_in_place(
+                            Queued::received(new_cmd, &rx),
+                            &adjust,
+                            &status,
+                            &last_frame,
+                            Some(&mut screen),
+                            None,
+                            &config,
+                        ) {
+                            Some(new_cmd) => {
                                 // Any other command interrupts scrolling
                                 pending_cmd = Some(new_cmd);
                                 break 'scroll;
                             }
+                            None => current_adjust = *adjust.lock().unwrap(),
                         }
                     }
+                    // An in-place Identify can take seconds; don't jump ahead
+                    last_step += polled_at.elapsed();
+
+                    // Text is rendered into a frame so it goes through the
+                    // same drawing path (and pixel map) as everything else
+                    let mut frame = RgbImage::new(panel.cols, panel.rows);
+                    let mut pen_x = scroll.x();
+                    for seg in &segments {
+                        pen_x =
+                            font.draw_text(&mut frame, &seg.text, pen_x, y_pos, seg.color.into());
+                    }
+                    draw_frame_adjusted(screen.canvas(), &frame, current_adjust, output);
+                    screen.present();
+                    last_frame = frame;
+
+                    thread::sleep(scroll.until_next_pixel());
+                    let now = Instant::now();
+                    scroll.advance(now - last_step);
+                    last_step = now;
+
+                    if repeat.is_some_and(|repeat| scroll.passes() >= repeat) {
+                        // The text has just scrolled off, so this only
+                        // makes the blank panel official
+                        screen.canvas().clear();
+                        screen.present();
+                        last_frame = RgbImage::new(panel.cols, panel.rows);
+                        status.lock().unwrap().set_idle();
+                        // Done, so a temporary command shouldn't replay it
+                        current = None;
+                        break 'scroll;
+                    }
+                }
+            }
 
-                    // Calculate text color with current brightness
-                    let text_color = Color::new(r, g, b).apply_brightness(current_brightness);
+            // Only `Display::run` creates these, and never nests them
+            RenderCommand::Tracked { .. } => {
+                tracing::warn!("Ignoring a command tracked twice");
+                continue;
+            }
 
-                    canvas.clear();
-                    canvas.draw_text(&font, &text, x, y_pos, &text_color.into(), 0, false);
-                    canvas = matrix.swap(canvas);
+            RenderCommand::Temporary { .. } => {
+                tracing::warn!("Ignoring a temporary command nested in another");
+                continue;
+            }
+        }
 
-                    x -= 1;
-                    if x < end_x {
-                        x = start_x;
-                    }
+        signal_done(done);
+    }
+}
 
-                    thread::sleep(scroll_delay);
-                }
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn bounce() -> RenderCommand {
+        RenderCommand::Effect {
+            kind: EffectKind::Bounce,
+            speed: 10,
+            color: Color::new(255, 0, 0),
+        }
+    }
+
+    #[test]
+    fn temporary_commands_resume_what_is_still_running() {
+        let frame = RgbImage::from_pixel(4, 2, image::Rgb([1, 2, 3]));
+        let target = restore_target(None, bounce().resume_copy(), &frame, None);
+        assert_eq!(target.name(), "effect");
+
+        // An earlier temporary command's target wins
+        let pending = PendingRestore {
+            at: Instant::now(),
+            target: RenderCommand::ShowImage {
+                img: frame.clone(),
+                source: "images/a.png".to_string(),
+                watch: None,
+            },
+        };
+        let target = restore_target(Some(pending), bounce().resume_copy(), &frame, None);
+        assert_eq!(target.name(), "show_image");
+    }
+
+    #[test]
+    fn finished_or_failed_commands_restore_the_last_frame() {
+        // What the render loop leaves in `current` once a one-shot video
+        // finishes or a command fails
+        let current = None;
+        let frame = RgbImage::from_pixel(4, 2, image::Rgb([1, 2, 3]));
+        let target = restore_target(None, current, &frame, Some("videos/eyes".to_string()));
+        let RenderCommand::ShowImage { img, source, watch } = target else {
+            panic!("expected a still, got {}", target.name());
+        };
+        assert_eq!(img, frame);
+        assert_eq!(source, "videos/eyes");
+        assert!(watch.is_none());
+    }
+
+    #[test]
+    fn queued_commands_keep_their_own_send_time() {
+        let (tx, rx) = crate::queue::counting_channel();
+        tx.send(bounce()).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        tx.send(bounce()).unwrap();
+
+        let first = Queued::received(rx.recv().unwrap(), &rx);
+        let second = Queued::received(rx.recv().unwrap(), &rx);
+        assert!(
+            queue_wait(first.sent_at) >= queue_wait(second.sent_at) + Duration::from_millis(20)
+        );
+        assert_eq!(queue_wait(Queued::made(bounce()).sent_at), Duration::ZERO);
+    }
+
+    #[test]
+    fn watched_images_load_off_the_render_thread() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("watched.png");
+        RgbImage::from_pixel(2, 2, image::Rgb([0, 255, 0]))
+            .save(&path)
+            .unwrap();
+        let mut watch = ImageWatch {
+            path,
+            fit: FitMode::Stretch,
+            background: Color::new(0, 0, 0),
+            rotate_deg: None,
+            modified: None,
+        };
+
+        assert!(watch.check_modified().unwrap());
+        assert!(!watch.check_modified().unwrap());
+        let img = watch
+            .load_in_background(PanelConfig::new(4, 8))
+            .recv()
+            .unwrap()
+            .unwrap();
+        assert_eq!(img.dimensions(), (8, 4));
+        assert_eq!(img.get_pixel(0, 0), &image::Rgb([0, 255, 0]));
+    }
+
+    /// A 64x32 panel with the embedded fonts and no limits.
+    fn test_config() -> RenderConfig {
+        RenderConfig {
+            panel: PanelConfig::new(32, 64),
+            safe_inset: 0,
+            fonts_dir: PathBuf::new(),
+            canvas: CanvasStrategy::default(),
+            hardware_brightness: 100,
+            limits: Arc::new(Limits::new(crate::settings::RuntimeSettings {
+                max_brightness: 100,
+                max_refresh_fps: None,
+            })),
+            pixel_map: None,
+            hardware_mapping: "regular".to_string(),
+            power_off_after: None,
+            min_display: Duration::ZERO,
+            diagnostics: Arc::new(Mutex::new(CommandLog::default())),
+            skip_duplicate_frames: false,
+            color_order: ColorOrder::default(),
+        }
+    }
+
+    #[test]
+    fn failed_commands_are_cleared_from_the_status() {
+        let config = test_config();
+        config
+            .diagnostics
+            .lock()
+            .unwrap()
+            .start("effect", Duration::ZERO, SystemTime::now());
+        let status = Mutex::new(DisplayStatus::new());
+        status.lock().unwrap().command = Some(ActiveCommand {
+            name: "effect".to_string(),
+            params: bounce().details(),
+        });
+        let mut current = bounce().resume_copy();
+
+        command_failed(&config, &status, &mut current, "Broken".to_string());
+        assert!(current.is_none());
+        assert!(status.lock().unwrap().command.is_none());
+        let log = config.diagnostics.lock().unwrap();
+        assert_eq!(
+            log.records().last().unwrap().error.as_deref(),
+            Some("Broken")
+        );
+    }
+
+    #[test]
+    fn commands_held_for_min_display_coalesce_to_the_latest() {
+        let tracked = |cmd| {
+            let (done, finished) = oneshot::channel();
+            (
+                Queued::made(RenderCommand::Tracked {
+                    cmd: Box::new(cmd),
+                    done,
+                }),
+                finished,
+            )
+        };
+        let (mut held, mut first_done) = tracked(bounce());
+        let (second, mut second_done) = tracked(RenderCommand::Stop { clear: false });
+
+        // Each newer display command replaces the held one
+        assert!(supersede(&mut held, second).is_none());
+        assert!(first_done.try_recv().is_ok());
+        let third = Queued::made(RenderCommand::Clear {
+            color: Color::new(0, 0, 0),
+            fade_ms: 0,
+        });
+        assert!(supersede(&mut held, third).is_none());
+        assert!(second_done.try_recv().is_ok());
+        assert_eq!(held.msg.name(), "clear");
+
+        // An adjustment runs first and hands the held command back
+        let next = supersede(&mut held, Queued::made(RenderCommand::SetBrightness(50)));
+        assert_eq!(next.unwrap().msg.name(), "clear");
+        assert_eq!(held.msg.name(), "set_brightness");
+    }
+
+    #[test]
+    #[ignore = "drives the LED matrix; run on a Pi with --ignored"]
+    fn tracked_text_that_fits_resolves_through_the_render_loop() {
+        let (tx, rx) = crate::queue::counting_channel();
+        let status = Arc::new(Mutex::new(DisplayStatus::new()));
+        let render = thread::spawn({
+            let status = status.clone();
+            move || render_loop(rx, status, test_config())
+        });
+
+        // Auto mode shows text that fits without scrolling, then is done
+        let (done, finished) = oneshot::channel();
+        let text = RenderCommand::ScrollText {
+            segments: vec![Span {
+                text: "Hi".to_string(),
+                color: Color::new(255, 255, 255),
+            }],
+            font: crate::bdf::FALLBACK_FONT.to_string(),
+            speed: 30,
+            mode: TextMode::Auto,
+            x: None,
+            y: VerticalPosition::default(),
+            repeat: None,
+        };
+        tx.send(RenderCommand::Tracked {
+            cmd: Box::new(text),
+            done,
+        })
+        .unwrap();
+        assert_eq!(finished.blocking_recv(), Ok(()));
+        assert_eq!(status.lock().unwrap().state, DisplayState::ShowingText);
+
+        drop(tx);
+        render.join().unwrap();
+    }
+
+    #[test]
+    fn in_place_commands_are_logged_and_signaled() {
+        let config = test_config();
+        let adjust = Mutex::new(ColorAdjust::default());
+        let status = Mutex::new(DisplayStatus::new());
+        let frame = RgbImage::new(64, 32);
+        let run = |cmd| {
+            let (done, mut finished) = oneshot::channel();
+            let queued = Queued::made(RenderCommand::Tracked {
+                cmd: Box::new(cmd),
+                done,
+            });
+            let unhandled = handle_in_place(queued, &adjust, &status, &frame, None, None, &config);
+            (unhandled, finished.try_recv().is_ok())
+        };
+
+        let (unhandled, done) = run(RenderCommand::SetBrightness(40));
+        assert!(unhandled.is_none() && done);
+        assert_eq!(status.lock().unwrap().brightness, 40);
+        // Powered off, a hardware brightness waits for the matrix
+        let (unhandled, done) = run(RenderCommand::SetHardwareBrightness(0));
+        assert!(unhandled.is_none() && done);
+        assert_eq!(status.lock().unwrap().hardware_brightness, 1);
+        let names: Vec<_> = config
+            .diagnostics
+            .lock()
+            .unwrap()
+            .records()
+            .map(|record| record.command)
+            .collect();
+        assert_eq!(names, ["set_brightness", "set_hardware_brightness"]);
+
+        // Anything else goes back to the caller, still tracked
+        for cmd in [
+            bounce(),
+            RenderCommand::Identify {
+                label: "A".to_string(),
+            },
+        ] {
+            let (unhandled, done) = run(cmd);
+            assert!(!done);
+            assert!(matches!(
+                unhandled.unwrap().msg,
+                RenderCommand::Tracked { .. }
+            ));
         }
+        assert_eq!(config.diagnostics.lock().unwrap().records().count(), 2);
+    }
+
+    #[test]
+    fn set_loop_changes_the_playing_video_without_interrupting_it() {
+        let config = test_config();
+        let adjust = Mutex::new(ColorAdjust::default());
+        let status = Mutex::new(DisplayStatus::new());
+        status.lock().unwrap().looping = true;
+        let frame = RgbImage::new(64, 32);
+        let mut passes = Passes::new(None);
+
+        let queued = Queued::made(RenderCommand::SetLoop(false));
+        let pending = handle_in_place(
+            queued,
+            &adjust,
+            &status,
+            &frame,
+            None,
+            Some(&mut passes),
+            &config,
+        );
+        assert!(pending.is_none());
+        assert!(!passes.looping());
+        assert!(!status.lock().unwrap().looping);
+        // The pass that's playing is the last
+        assert!(!passes.finish_pass());
+
+        // With no video playing there's nothing to change
+        let queued = Queued::made(RenderCommand::SetLoop(true));
+        let pending = handle_in_place(queued, &adjust, &status, &frame, None, None, &config);
+        assert!(pending.is_none());
+        assert!(!status.lock().unwrap().looping);
     }
 }
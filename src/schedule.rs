@@ -0,0 +1,157 @@
+//! Brightness schedule: automatic day/night dimming.
+//!
+//! A schedule is a list of `(time of day, brightness)` points. Between two
+//! points the brightness changes linearly, and after the last point of the
+//! day it fades towards the first point of the next day, so
+//! `07:00=80, 22:00=10` dims slowly through the evening and brightens
+//! through the early morning. To switch sharply instead, add two points a
+//! minute apart.
+//!
+//! This module only does the math on seconds since midnight; the server
+//! reads the local clock and sends the brightness commands.
+//!
+//! ## Rust concepts
+//! - `split_once` to parse `key=value` style arguments
+//! - `windows(2)` to compare neighbors and `partition_point` to binary-search
+//!   a sorted `Vec`
+//! - `rem_euclid` for wrap-around arithmetic
+
+/// Seconds in a day.
+pub const DAY_SECONDS: u32 = 24 * 60 * 60;
+
+/// Parse a schedule point written as `HH:MM=brightness`, e.g. `22:30=10`.
+///
+/// Returns the time as seconds since midnight and the brightness (0-100).
+pub fn parse_point(s: &str) -> Result<(u32, u8), String> {
+    let (time, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Expected HH:MM=brightness, got {s:?}"))?;
+    let (hours, minutes) = time
+        .split_once(':')
+        .ok_or_else(|| format!("Expected a HH:MM time, got {time:?}"))?;
+
+    let hours: u32 = hours
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid hour in {time:?}"))?;
+    let minutes: u32 = minutes
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid minute in {time:?}"))?;
+    if hours > 23 || minutes > 59 {
+        return Err(format!("Time {time:?} is not between 00:00 and 23:59"));
+    }
+
+    let brightness: u8 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid brightness {value:?}"))?;
+    if brightness > 100 {
+        return Err(format!("Brightness {brightness} is not between 0 and 100"));
+    }
+
+    Ok((hours * 3600 + minutes * 60, brightness))
+}
+
+/// Brightness points over a day, sorted by time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BrightnessSchedule {
+    /// `(seconds since midnight, brightness)`, sorted and with unique times
+    points: Vec<(u32, u8)>,
+}
+
+impl BrightnessSchedule {
+    /// Build a schedule from points in any order. There must be at least
+    /// one point and no two at the same time.
+    pub fn new(mut points: Vec<(u32, u8)>) -> Result<Self, String> {
+        if points.is_empty() {
+            return Err("A brightness schedule needs at least one point".to_string());
+        }
+        points.sort_by_key(|&(time, _)| time);
+        if let Some(pair) = points.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            let (time, _) = pair[0];
+            return Err(format!(
+                "Two brightness points at {:02}:{:02}",
+                time / 3600,
+                time / 60 % 60
+            ));
+        }
+        Ok(Self { points })
+    }
+
+    /// Brightness at `seconds` since midnight, interpolated between the
+    /// surrounding points (wrapping around midnight).
+    pub fn brightness_at(&self, seconds: u32) -> u8 {
+        let seconds = seconds % DAY_SECONDS;
+        let n = self.points.len();
+
+        // First point strictly after `seconds`; the one before it is the
+        // point we're fading away from. Both wrap around the day.
+        let next = self.points.partition_point(|&(time, _)| time <= seconds);
+        let (from_time, from) = self.points[(next + n - 1) % n];
+        let (to_time, to) = self.points[next % n];
+
+        let span = (to_time as i64 - from_time as i64).rem_euclid(DAY_SECONDS as i64);
+        if span == 0 {
+            return from;
+        }
+        let elapsed = (seconds as i64 - from_time as i64).rem_euclid(DAY_SECONDS as i64);
+        let value = from as i64 + (to as i64 - from as i64) * elapsed / span;
+        value as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    const fn at(hours: u32, minutes: u32) -> u32 {
+        hours * 3600 + minutes * 60
+    }
+
+    #[rstest]
+    #[case("07:00=80", Ok((at(7, 0), 80)))]
+    #[case("22:30=10", Ok((at(22, 30), 10)))]
+    #[case("0:05=0", Ok((at(0, 5), 0)))]
+    fn parses_valid_points(#[case] s: &str, #[case] expected: Result<(u32, u8), String>) {
+        assert_eq!(parse_point(s), expected);
+    }
+
+    #[rstest]
+    #[case("07:00")] // no brightness
+    #[case("0700=80")] // no colon
+    #[case("24:00=80")] // hour out of range
+    #[case("07:60=80")] // minute out of range
+    #[case("07:00=101")] // brightness out of range
+    #[case("07:00=bright")] // not a number
+    fn rejects_invalid_points(#[case] s: &str) {
+        assert!(parse_point(s).is_err());
+    }
+
+    #[test]
+    fn new_rejects_empty_and_duplicate_times() {
+        assert!(BrightnessSchedule::new(vec![]).is_err());
+        assert!(BrightnessSchedule::new(vec![(at(7, 0), 80), (at(7, 0), 10)]).is_err());
+    }
+
+    #[test]
+    fn single_point_is_constant() {
+        let schedule = BrightnessSchedule::new(vec![(at(12, 0), 40)]).unwrap();
+        assert_eq!(schedule.brightness_at(at(0, 0)), 40);
+        assert_eq!(schedule.brightness_at(at(18, 0)), 40);
+    }
+
+    #[rstest]
+    #[case(at(7, 0), 80)] // exactly on a point
+    #[case(at(14, 30), 45)] // halfway through the day fade
+    #[case(at(22, 0), 10)]
+    #[case(at(2, 30), 45)] // halfway through the overnight fade, past midnight
+    #[case(at(23, 0), 17)] // overnight fade, before midnight
+    fn interpolates_and_wraps_around_midnight(#[case] seconds: u32, #[case] expected: u8) {
+        // Points given out of order on purpose
+        let schedule = BrightnessSchedule::new(vec![(at(22, 0), 10), (at(7, 0), 80)]).unwrap();
+        assert_eq!(schedule.brightness_at(seconds), expected);
+    }
+}
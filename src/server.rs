@@ -10,20 +10,49 @@
 //! - Serde `Deserialize` for parsing JSON request bodies
 //! - `tower-http` middleware for CORS
 
-use crate::PanelConfig;
+use crate::ansi::{Span, TextLine, TextMode, VerticalPosition};
+use crate::api::{
+    ActiveCommand, BrightnessRequest, ControlMessage, DisplayState, DisplayStatus, EffectiveConfig,
+    ImageRequest, StopRequest, TextRequest, TextSegment, VideoFinish, VideoRequest, default_fps,
+    default_speed,
+};
+use crate::bdf::BdfFont;
+use crate::diagnostics::{CommandLog, CommandRecord};
+use crate::draw::{self, BitmapFormat, GaugeStyle, GradientDirection};
+use crate::effects::{self, SlideTransition, WipeDirection};
+use crate::frame::{self, Checksum, ColorLut, PixelFormat};
+use crate::layout::{self, FitMode, ScaleFilter};
 use crate::media::{self, MediaEntry, VideoEntry};
-use crate::render::{DisplayState, DisplayStatus, RenderCommand};
+use crate::presets::{self, PresetStore};
+use crate::queue::CountingSender;
+use crate::render::{
+    BenchmarkResult, BrightnessMode, ImageWatch, RenderCommand, load_and_fit_image,
+};
+use crate::schedule::BrightnessSchedule;
+use crate::settings::{Limits, RuntimeSettings, SettingsFile};
+use crate::sim::EffectKind;
+use crate::sprites::{self, SPRITE_SIZE};
+use crate::timer::TimerFinish;
+use crate::{Color, PanelConfig};
 use axum::Router;
 use axum::body::Bytes;
-use axum::extract::State;
-use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
-use axum::http::StatusCode;
-use axum::response::{IntoResponse, Json};
+use axum::extract::ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade, close_code};
+use axum::extract::{DefaultBodyLimit, Path, Query, Request, State};
+use axum::http::{Method, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Json, Response};
 use axum::routing::{get, post};
-use serde::Deserialize;
-use std::path::PathBuf;
-use std::sync::mpsc::Sender;
+use base64::Engine;
+use chrono::Timelike;
+use image::RgbImage;
+use rpi_led_matrix::LedFont;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::CorsLayer;
 use tower_http::trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer};
 use tracing::Level;
@@ -32,6 +61,23 @@ use utoipa_swagger_ui::SwaggerUi;
 
 // ── App State ────────────────────────────────────────────────────────
 
+/// One panel's render thread, as seen from the HTTP side.
+#[derive(Clone)]
+pub struct Display {
+    /// Channel to send commands to the panel's render thread
+    pub command_tx: CountingSender<RenderCommand>,
+    /// Shared display status (render thread writes, handlers read)
+    pub status: Arc<Mutex<DisplayStatus>>,
+    /// Set when an explicit brightness command overrides the brightness
+    /// schedule or light sensor, until `/api/v1/brightness/schedule/resume`
+    pub schedule_override: Arc<AtomicBool>,
+    /// Recent commands the render thread ran (render thread writes,
+    /// `/api/v1/diagnostics` reads)
+    pub diagnostics: Arc<Mutex<CommandLog>>,
+    /// When the server started, for `uptime_secs`
+    pub started_at: std::time::Instant,
+}
+
 /// Shared application state, passed to every handler via axum's `State` extractor.
 ///
 /// Rust concept: CLONE for Arc
@@ -40,14 +86,110 @@ use utoipa_swagger_ui::SwaggerUi;
 /// inside must be cheaply cloneable. `Arc` makes that possible for shared data.
 #[derive(Clone)]
 pub struct AppState {
-    /// Channel to send commands to the render thread
-    pub command_tx: Sender<RenderCommand>,
-    /// Shared display status (render thread writes, handlers read)
-    pub status: Arc<Mutex<DisplayStatus>>,
+    /// Render thread of each independent panel, keyed by panel id (0 is
+    /// the default)
+    pub displays: Arc<BTreeMap<u32, Display>>,
     /// Root directory for media files (images/, videos/)
     pub media_dir: PathBuf,
-    /// Panel dimensions
+    /// Directory the render thread loads BDF fonts from
+    pub fonts_dir: PathBuf,
+    /// Panel dimensions, shared by every panel
     pub panel: PanelConfig,
+    /// Whether `/api/v1/brightness` sets software or hardware brightness
+    pub brightness_mode: BrightnessMode,
+    /// Largest request body accepted, in bytes; bigger bodies get 413
+    pub max_body_bytes: usize,
+    /// Whether `/api/v1/display/stop` blanks the panel by default
+    pub stop_clears: bool,
+    /// What a non-looping video leaves on the panel when the request
+    /// doesn't say
+    pub video_finish: VideoFinish,
+    /// Most WebSocket stream clients one panel accepts at a time; frames
+    /// from several clients would interleave into garbage
+    pub max_stream_clients: u32,
+    /// Saved presets, persisted to a JSON file
+    pub presets: Arc<Mutex<PresetStore<Preset>>>,
+    /// Day/night brightness points applied automatically, if configured
+    pub brightness_schedule: Option<Arc<BrightnessSchedule>>,
+    /// Whether a light sensor sets brightness automatically
+    pub light_sensor: bool,
+    /// Font for text requests that don't name one
+    pub default_font: String,
+    /// Color for text requests that don't give one
+    pub default_text_color: Color,
+    /// Hosts `/api/v1/display/image/url` may fetch from even on the LAN;
+    /// when non-empty, no other hosts are allowed
+    #[cfg(feature = "remote-images")]
+    pub image_url_hosts: Vec<String>,
+    /// With `--appliance`, the endpoints that may still change the display,
+    /// as exact paths; `None` serves every endpoint
+    pub appliance: Option<Vec<String>>,
+    /// Brightness and refresh caps, shared with the render threads and
+    /// changed by a reloaded `--settings-file`
+    pub limits: Arc<Limits>,
+    /// Settings the server started with, for `GET /api/v1/config`
+    pub config: Arc<EffectiveConfig>,
+}
+
+impl Display {
+    /// Snapshot of the status, with the command queue counters and uptime
+    /// filled in.
+    fn status(&self) -> DisplayStatus {
+        let mut status = self.status.lock().unwrap().clone();
+        (status.commands_sent, status.commands_processed) = self.command_tx.counts();
+        status.uptime_secs = self.started_at.elapsed().as_secs();
+        status
+    }
+
+    /// Send `cmd` and wait until the render thread has finished it, or has
+    /// moved on to a newer command. Fails if the command couldn't be run
+    /// (e.g. a missing font) or the render thread is gone.
+    pub async fn run(&self, cmd: RenderCommand) -> Result<(), String> {
+        let (done, finished) = oneshot::channel();
+        self.command_tx
+            .send(RenderCommand::Tracked {
+                cmd: Box::new(cmd),
+                done,
+            })
+            .map_err(|_| "Render thread gone".to_string())?;
+        finished
+            .await
+            .map_err(|_| "Command failed or the render thread stopped".to_string())
+    }
+
+    /// Flash `label` to identify the panel, resolving once the flash is over.
+    pub async fn flash(&self, label: impl Into<String>) -> Result<(), String> {
+        self.run(RenderCommand::Identify {
+            label: label.into(),
+        })
+        .await
+    }
+}
+
+impl AppState {
+    /// The display for a `?panel=` id, or 404 if no such panel is configured.
+    fn display(&self, panel: u32) -> Result<&Display, (StatusCode, String)> {
+        self.displays
+            .get(&panel)
+            .ok_or((StatusCode::NOT_FOUND, format!("Unknown panel: {panel}")))
+    }
+
+    /// Whether appliance mode (if on) still serves the changing endpoint `path`.
+    fn appliance_allows(&self, path: &str) -> bool {
+        self.appliance
+            .as_ref()
+            .is_none_or(|allowed| allowed.iter().any(|p| p == path))
+    }
+
+    /// `font`, or `--default-font` if the request left it out.
+    fn font_or_default(&self, font: Option<String>) -> String {
+        font.unwrap_or_else(|| self.default_font.clone())
+    }
+
+    /// `color`, or `--default-text-color` if the request left it out.
+    fn text_color_or_default(&self, color: Option<Color>) -> Color {
+        color.unwrap_or(self.default_text_color)
+    }
 }
 
 // ── OpenAPI Documentation ────────────────────────────────────────────
@@ -56,30 +198,113 @@ pub struct AppState {
 #[openapi(
     paths(
         get_status,
+        get_diagnostics,
+        get_config,
+        get_panels,
+        post_system_reset,
+        get_system_benchmark,
+        post_system_identify,
         get_images,
         get_videos,
+        get_formats,
         get_fonts,
+        post_fonts_refresh,
+        get_font_preview,
         post_display_image,
+        post_display_wipe,
+        post_display_slideshow,
         post_display_video,
+        post_display_frames,
         post_display_text,
+        post_display_text_lines,
+        post_display_sysinfo,
+        post_display_raw_text,
+        post_display_textfile,
+        post_display_gauge,
+        post_display_qr,
+        post_display_polygon,
+        post_display_sprite,
+        post_display_temporary,
+        post_display_gradient,
+        post_display_color_wheel,
+        post_display_effect,
+        post_display_marquee,
+        post_display_type,
+        post_display_timer,
+        post_display_save,
         post_display_clear,
         post_display_stop,
+        post_display_invert,
+        post_display_loop,
         post_brightness,
+        post_brightness_schedule_resume,
+        get_presets,
+        post_presets,
+        post_preset_activate,
     ),
     components(schemas(
         DisplayStatus,
+        ActiveCommand,
+        CommandRecord,
+        EffectiveConfig,
+        PanelStatus,
+        BenchmarkResult,
         DisplayState,
         media::MediaEntry,
         media::VideoEntry,
+        media::SortKey,
+        FormatsResponse,
+        FontRefreshResponse,
+        FontError,
         ImageRequest,
+        WipeRequest,
+        SlideshowRequest,
+        SlideTransition,
+        WipeDirection,
+        FitMode,
         VideoRequest,
+        VideoFinish,
+        FramesRequest,
         TextRequest,
+        TextSegment,
+        TextMode,
+        TextLinesRequest,
+        TextLineRequest,
+        SysInfoRequest,
+        RawTextRequest,
+        BitmapFormat,
+        TextFileRequest,
+        GaugeRequest,
+        GaugeStyle,
+        QrRequest,
+        PolygonRequest,
+        SpriteRequest,
+        GradientRequest,
+        GradientDirection,
+        ColorWheelRequest,
+        EffectRequest,
+        EffectKind,
+        MarqueeRequest,
+        TypeTextRequest,
+        TimerRequest,
+        TimerFinish,
+        SaveRequest,
+        ClearRequest,
+        StopRequest,
         BrightnessRequest,
+        InvertRequest,
+        LoopRequest,
+        PresetCommand,
+        TemporaryRequest,
+        Preset,
+        PresetRequest,
+        PresetEntry,
     )),
     tags(
         (name = "display", description = "Display control endpoints"),
         (name = "media", description = "Media discovery endpoints"),
         (name = "system", description = "System status endpoints"),
+        (name = "presets", description = "Saved display presets"),
     ),
     info(
         title = "LED Matrix API",
@@ -89,330 +314,3383 @@ pub struct AppState {
 )]
 pub struct ApiDoc;
 
+/// Endpoints only built with the `remote-images` feature, merged into
+/// `ApiDoc` at startup.
+#[cfg(feature = "remote-images")]
+#[derive(OpenApi)]
+#[openapi(paths(post_display_image_url), components(schemas(ImageUrlRequest)))]
+struct RemoteImagesApi;
+
 // ── Request/Response types ───────────────────────────────────────────
 
-#[derive(Deserialize, utoipa::ToSchema)]
-pub struct ImageRequest {
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct WipeRequest {
     /// Path to image file relative to media directory
     #[schema(example = "images/test.png")]
     path: String,
+    /// Direction the wipe edge travels: left, right, up, or down
+    #[serde(default = "default_wipe_direction")]
+    #[schema(example = "right", default = "right")]
+    direction: WipeDirection,
+    /// Total duration of the wipe in milliseconds
+    #[serde(default = "default_wipe_duration")]
+    #[schema(example = 500, default = 500)]
+    duration_ms: u32,
 }
 
-#[derive(Deserialize, utoipa::ToSchema)]
-pub struct VideoRequest {
-    /// Path to video directory relative to media directory. Use GET /api/videos to list available videos.
-    #[schema(example = "videos/eyes_25")]
+fn default_wipe_direction() -> WipeDirection {
+    WipeDirection::Right
+}
+
+fn default_wipe_duration() -> u32 {
+    500
+}
+
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SlideshowRequest {
+    /// Directory of PNG/JPEG images relative to media directory, shown in
+    /// natural name order (`img2` before `img10`)
+    #[serde(default = "default_slideshow_path")]
+    #[schema(example = "images", default = "images")]
     path: String,
-    /// Frames per second. Typical range: 15-60. Higher fps = smoother but more CPU intensive.
+    /// How long each image stays up, in milliseconds, including the transition
+    #[serde(default = "default_slide_interval")]
+    #[schema(example = 10000, default = 5000)]
+    interval_ms: u32,
+    /// How to move to the next image: cut, fade, wipe_left, wipe_right, wipe_up, or wipe_down
+    #[serde(default)]
+    #[schema(example = "wipe_left", default = "fade")]
+    transition: SlideTransition,
+    /// Show the images in a random order, reshuffled on every loop
+    #[serde(default)]
+    #[schema(example = true, default = false)]
+    shuffle: bool,
+    /// Start over after the last image; false leaves the last image up
+    #[serde(default = "default_slideshow_loop", rename = "loop")]
+    #[schema(example = true, default = true)]
+    loop_playback: bool,
+}
+
+fn default_slideshow_path() -> String {
+    "images".to_string()
+}
+
+fn default_slide_interval() -> u32 {
+    5000
+}
+
+fn default_slideshow_loop() -> bool {
+    true
+}
+
+/// Shortest slideshow interval; the transition alone takes up to half a second.
+const MIN_SLIDE_INTERVAL_MS: u32 = 500;
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct FramesRequest {
+    /// Base64-encoded raw RGB frames, each exactly rows*cols*3 bytes
+    #[schema(example = json!(["AAAA...", "/wAA..."]))]
+    frames: Vec<String>,
+    /// How long to show each frame in milliseconds, one per frame (like a
+    /// GIF). When empty, every frame is shown for 1000/fps ms
+    #[serde(default)]
+    #[schema(example = json!([1000, 80]))]
+    delays_ms: Vec<u32>,
+    /// Frames per second, used when `delays_ms` is empty
     #[serde(default = "default_fps")]
-    #[schema(example = 25, default = 30)]
+    #[schema(example = 10, default = 30)]
     fps: u32,
-    /// Loop playback indefinitely. Set to true to repeat video, false to play once and clear screen.
+    /// Loop the animation indefinitely instead of playing it once
     #[serde(default, rename = "loop")]
     #[schema(example = true, default = false)]
     loop_playback: bool,
 }
 
-fn default_fps() -> u32 {
-    30
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct TextLinesRequest {
+    /// Lines drawn together on one frame, top to bottom
+    lines: Vec<TextLineRequest>,
 }
 
-#[derive(Deserialize, utoipa::ToSchema)]
-pub struct TextRequest {
-    /// Text to display
+/// One independently styled line of a multi-line text frame.
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct TextLineRequest {
+    #[schema(example = "12:34")]
     text: String,
-    /// BDF font name. Available fonts: 4x6, 5x7, 5x8, 6x9, 6x10, 6x12, 6x13, 6x13B, 6x13O, 7x13, 7x13B, 7x13O, 7x14, 7x14B, 8x13, 8x13B, 8x13O, 9x15, 9x15B, 9x18, 9x18B, 10x20, and more in fonts/bdf/
-    #[serde(default = "default_font")]
-    #[schema(example = "6x13", default = "6x13")]
-    font: String,
-    /// RGB color array [red, green, blue] where each value is 0-255. Examples: [255, 0, 0] = red, [0, 255, 0] = green, [0, 0, 255] = blue, [255, 255, 255] = white
+    /// BDF font name, defaulting to the server's --default-font
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "9x15B")]
+    font: Option<String>,
+    /// Color, defaulting to the server's --default-text-color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "[255, 200, 0]")]
+    color: Option<Color>,
+    /// Top of the line in pixels from the top of the panel. Defaults to
+    /// directly below the previous line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 0)]
+    y: Option<i32>,
+}
+
+#[derive(Clone, Default, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SysInfoRequest {
+    /// BDF font name, defaulting to the server's --default-font
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "6x13")]
+    font: Option<String>,
+    /// Color, defaulting to the server's --default-text-color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "[0, 255, 0]")]
+    color: Option<Color>,
+}
+
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct RawTextRequest {
+    /// Bitmap width in pixels
+    #[schema(example = 40)]
+    width: u32,
+    /// Bitmap height in pixels. Centered vertically on the panel
+    #[schema(example = 16)]
+    height: u32,
+    /// Base64-encoded pixels in `format`. Nonzero pixels are drawn in `color`
+    #[schema(example = "AAAA...")]
+    bitmap: String,
+    /// Pixel layout: gray8 (one byte per pixel) or mono1 (one bit per pixel, rows padded to bytes)
+    #[serde(default)]
+    #[schema(example = "mono1", default = "gray8")]
+    format: BitmapFormat,
+    /// Color for "on" pixels
     #[serde(default = "default_color")]
-    #[schema(value_type = Vec<u8>, example = "[255, 255, 255]")]
-    color: (u8, u8, u8),
+    #[schema(example = "[255, 255, 255]")]
+    color: Color,
     /// Scroll speed in pixels per second. Typical range: 10-100
     #[serde(default = "default_speed")]
     #[schema(example = 30, default = 30)]
     speed: u32,
 }
 
-fn default_font() -> String {
-    "6x13".to_string()
+fn default_color() -> Color {
+    Color::new(255, 255, 255)
 }
 
-fn default_color() -> (u8, u8, u8) {
-    (255, 255, 255)
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct TextFileRequest {
+    /// Path to a .txt or .ans file relative to media directory
+    #[schema(example = "text/logo.ans")]
+    path: String,
+    /// BDF font name. Omit to pick the largest font that fits the whole file on the panel
+    #[schema(example = "5x7")]
+    font: Option<String>,
+    /// Color for text without an ANSI color, defaulting to the server's --default-text-color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "[255, 255, 255]")]
+    color: Option<Color>,
+    /// Honor ANSI color escape codes. When false they are stripped and everything uses `color`
+    #[serde(default = "default_ansi")]
+    #[schema(example = true, default = true)]
+    ansi: bool,
 }
 
-fn default_speed() -> u32 {
-    30
+fn default_ansi() -> bool {
+    true
 }
 
-#[derive(Deserialize, utoipa::ToSchema)]
-pub struct BrightnessRequest {
-    /// Brightness level (0-100)
-    #[schema(example = 75, minimum = 0, maximum = 100)]
-    value: u8,
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct GaugeRequest {
+    /// Current value to show
+    #[schema(example = 42.0)]
+    value: f32,
+    /// Value at which the gauge is empty
+    #[serde(default)]
+    #[schema(example = 0.0, default = 0.0)]
+    min: f32,
+    /// Value at which the gauge is full
+    #[serde(default = "default_gauge_max")]
+    #[schema(example = 100.0, default = 100.0)]
+    max: f32,
+    /// Color for the filled part
+    #[serde(default = "default_gauge_color")]
+    #[schema(example = "[0, 255, 0]")]
+    color: Color,
+    /// Gauge style: bar_horizontal, bar_vertical, or ring
+    #[serde(default = "default_gauge_style")]
+    #[schema(example = "bar_horizontal", default = "bar_horizontal")]
+    style: GaugeStyle,
+    /// Draw a dim track behind the unfilled part of the gauge
+    #[serde(default = "default_gauge_track")]
+    #[schema(example = true, default = true)]
+    track: bool,
 }
 
-// ── Router ───────────────────────────────────────────────────────────
+fn default_gauge_max() -> f32 {
+    100.0
+}
 
-/// Build the axum router with all API endpoints.
-pub fn create_router(state: AppState) -> Router {
-    Router::new()
-        .merge(
-            SwaggerUi::new("/docs")
-                .url("/api-docs/openapi.json", ApiDoc::openapi())
-                .config(
-                    utoipa_swagger_ui::Config::new(["/api-docs/openapi.json"])
-                        .validator_url("none"),
-                ),
-        )
-        .route("/api/v1/status", get(get_status))
-        .route("/api/v1/images", get(get_images))
-        .route("/api/v1/videos", get(get_videos))
-        .route("/api/v1/fonts", get(get_fonts))
-        .route("/api/v1/display/image", post(post_display_image))
-        .route("/api/v1/display/video", post(post_display_video))
-        .route("/api/v1/display/text", post(post_display_text))
-        .route("/api/v1/display/frame", post(post_display_frame))
-        .route("/api/v1/display/stream", get(ws_display_stream))
-        .route("/api/v1/display/clear", post(post_display_clear))
-        .route("/api/v1/display/stop", post(post_display_stop))
-        .route("/api/v1/brightness", post(post_brightness))
-        .layer(
-            TraceLayer::new_for_http()
-                .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
-                .on_response(DefaultOnResponse::new().level(Level::INFO)),
-        )
-        .layer(CorsLayer::permissive())
-        .with_state(state)
+fn default_gauge_color() -> Color {
+    Color::new(0, 255, 0)
 }
 
-// ── Handlers ─────────────────────────────────────────────────────────
+fn default_gauge_style() -> GaugeStyle {
+    GaugeStyle::BarHorizontal
+}
 
-/// GET /api/v1/status — return current display state
-#[utoipa::path(
-    get,
-    path = "/api/v1/status",
-    tag = "system",
-    responses(
-        (status = 200, description = "Current display status", body = DisplayStatus)
-    )
-)]
-async fn get_status(State(state): State<AppState>) -> Json<DisplayStatus> {
-    let status = state.status.lock().unwrap().clone();
-    Json(status)
+fn default_gauge_track() -> bool {
+    true
 }
 
-/// GET /api/v1/images — list available images
-#[utoipa::path(
-    get,
-    path = "/api/v1/images",
-    tag = "media",
-    responses(
-        (status = 200, description = "List of available images", body = Vec<MediaEntry>)
-    )
-)]
-async fn get_images(State(state): State<AppState>) -> Json<Vec<media::MediaEntry>> {
-    let images = media::list_images(&state.media_dir);
-    Json(images)
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct QrRequest {
+    /// Text to encode, e.g. a URL or a `WIFI:S:name;T:WPA;P:password;;` string
+    #[schema(example = "https://example.com")]
+    data: String,
+    /// Color for the dark modules
+    #[serde(default = "default_qr_color")]
+    #[schema(example = "[0, 0, 0]")]
+    color: Color,
+    /// Color for the light modules and quiet zone
+    #[serde(default = "default_color")]
+    #[schema(example = "[255, 255, 255]")]
+    background: Color,
 }
 
-/// GET /api/v1/videos — list available video directories
-#[utoipa::path(
-    get,
-    path = "/api/v1/videos",
-    tag = "media",
-    responses(
-        (status = 200, description = "List of available videos", body = Vec<VideoEntry>)
-    )
-)]
-async fn get_videos(State(state): State<AppState>) -> Json<Vec<media::VideoEntry>> {
-    let videos = media::list_videos(&state.media_dir);
-    Json(videos)
+fn default_qr_color() -> Color {
+    Color::new(0, 0, 0)
 }
 
-/// GET /api/v1/fonts — list available BDF fonts
-#[utoipa::path(
-    get,
-    path = "/api/v1/fonts",
-    tag = "media",
-    responses(
-        (status = 200, description = "List of available font names", body = Vec<String>)
-    )
-)]
-async fn get_fonts(State(state): State<AppState>) -> Json<Vec<String>> {
-    let fonts = media::list_fonts(&state.media_dir);
-    Json(fonts)
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct PolygonRequest {
+    /// Corners as [x, y] pixel pairs, joined in order and closed back to
+    /// the first. Two points draw a line. Points may lie off the panel
+    #[schema(value_type = Vec<Vec<i32>>, example = "[[32, 8], [56, 52], [8, 52]]")]
+    points: Vec<(i32, i32)>,
+    /// Color of the outline or fill
+    #[serde(default = "default_color")]
+    #[schema(example = "[255, 128, 0]")]
+    color: Color,
+    /// Fill the inside instead of drawing only the outline
+    #[serde(default = "default_polygon_fill")]
+    #[schema(example = true, default = true)]
+    fill: bool,
 }
 
-/// POST /api/v1/display/image — display a static image
-#[utoipa::path(
-    post,
-    path = "/api/v1/display/image",
-    tag = "display",
-    request_body = ImageRequest,
-    responses(
-        (status = 200, description = "Image displayed successfully"),
-        (status = 404, description = "Image not found"),
-        (status = 400, description = "Invalid path")
-    )
-)]
-async fn post_display_image(
-    State(state): State<AppState>,
-    Json(req): Json<ImageRequest>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    let full_path = validate_media_path(&state.media_dir, &req.path)?;
+fn default_polygon_fill() -> bool {
+    true
+}
 
-    state
-        .command_tx
-        .send(RenderCommand::ShowImage(full_path))
-        .map_err(|_| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Render thread gone".to_string(),
-            )
-        })?;
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SpriteRequest {
+    /// Built-in sprite: check, cross, up, down, left, right, heart, dot,
+    /// sun, cloud, rain, or snow
+    #[schema(example = "check")]
+    name: String,
+    /// Left edge in pixels. Defaults to centering the sprite
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 4)]
+    x: Option<i32>,
+    /// Top edge in pixels. Defaults to centering the sprite
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 4)]
+    y: Option<i32>,
+    /// Size of each sprite pixel; sprites are 8x8, so 4 makes a 32x32 icon
+    #[serde(default = "default_sprite_scale")]
+    #[schema(example = 4, default = 1, minimum = 1, maximum = 16)]
+    scale: u32,
+    /// Color of the sprite's lit pixels
+    #[serde(default = "default_color")]
+    #[schema(example = "[0, 255, 0]")]
+    color: Color,
+    /// Draw on a black panel instead of over what's shown
+    #[serde(default)]
+    #[schema(example = false, default = false)]
+    clear: bool,
+}
 
-    Ok(StatusCode::OK)
+fn default_sprite_scale() -> u32 {
+    1
 }
 
-/// POST /api/v1/display/video — play a video (directory of frame images)
-#[utoipa::path(
-    post,
-    path = "/api/v1/display/video",
-    tag = "display",
-    request_body = VideoRequest,
-    responses(
-        (status = 200, description = "Video playback started"),
-        (status = 404, description = "Video directory not found"),
-        (status = 400, description = "Invalid path")
-    )
-)]
-async fn post_display_video(
-    State(state): State<AppState>,
-    Json(req): Json<VideoRequest>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    let full_path = validate_media_path(&state.media_dir, &req.path)?;
+/// Largest sprite scale; at 16 an 8x8 sprite already covers 128x128 pixels.
+const MAX_SPRITE_SCALE: u32 = 16;
 
-    state
-        .command_tx
-        .send(RenderCommand::PlayVideo {
-            dir: full_path,
-            fps: req.fps,
-            loop_playback: req.loop_playback,
-        })
-        .map_err(|_| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Render thread gone".to_string(),
-            )
-        })?;
+/// Furthest a polygon point may be from the panel's origin, in pixels.
+/// Keeps a stray huge coordinate from making the outline take forever.
+const MAX_POLYGON_COORD: i32 = 4096;
 
-    Ok(StatusCode::OK)
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct GradientRequest {
+    /// Color where the gradient starts
+    #[schema(example = "[255, 0, 0]")]
+    from: Color,
+    /// Color where the gradient ends
+    #[schema(example = "[0, 0, 255]")]
+    to: Color,
+    /// Which way the gradient runs
+    #[serde(default)]
+    direction: GradientDirection,
 }
 
-/// POST /api/v1/display/text — scroll text across the display
-#[utoipa::path(
-    post,
-    path = "/api/v1/display/text",
-    tag = "display",
-    request_body = TextRequest,
-    responses(
-        (status = 200, description = "Text scrolling started"),
-    )
-)]
-async fn post_display_text(
-    State(state): State<AppState>,
-    Json(req): Json<TextRequest>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    state
-        .command_tx
-        .send(RenderCommand::ScrollText {
-            text: req.text,
-            font: req.font,
-            color: req.color,
-            speed: req.speed,
-        })
-        .map_err(|_| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Render thread gone".to_string(),
-            )
-        })?;
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct TypeTextRequest {
+    /// Text to type out. Wrapped to the panel width and centered
+    #[schema(example = "Hello, world")]
+    text: String,
+    /// BDF font name (see GET /api/v1/fonts)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "6x13")]
+    font: Option<String>,
+    /// Color, defaulting to the server's --default-text-color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "[0, 255, 0]")]
+    color: Option<Color>,
+    /// Pause before each next character appears, in milliseconds
+    #[serde(default = "default_char_delay_ms")]
+    #[schema(example = 80, default = 100)]
+    char_delay_ms: u32,
+}
 
-    Ok(StatusCode::OK)
+fn default_char_delay_ms() -> u32 {
+    100
 }
 
-/// POST /api/v1/display/frame — push a raw RGB frame
-///
-/// Expects `application/octet-stream` body with exactly rows*cols*3 bytes.
-async fn post_display_frame(
-    State(state): State<AppState>,
-    body: Bytes,
-) -> Result<StatusCode, (StatusCode, String)> {
-    let expected = state.panel.frame_byte_count();
-    if body.len() != expected {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            format!(
-                "Expected {} bytes ({}x{}x3 RGB), got {} bytes",
-                expected,
-                state.panel.cols,
-                state.panel.rows,
-                body.len()
-            ),
-        ));
-    }
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct TimerRequest {
+    /// Length of the countdown in seconds, shown as `mm:ss`
+    #[schema(example = 300)]
+    seconds: u32,
+    /// BDF font name (see GET /api/v1/fonts)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "9x15B")]
+    font: Option<String>,
+    /// Color, defaulting to the server's --default-text-color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "[255, 120, 0]")]
+    color: Option<Color>,
+    /// What happens at zero: `"flash"`, `"clear"`, or `{"text": "Done!"}`
+    #[serde(default)]
+    on_finish: TimerFinish,
+}
 
-    state
-        .command_tx
-        .send(RenderCommand::ShowFrame(body.to_vec()))
-        .map_err(|_| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Render thread gone".to_string(),
-            )
-        })?;
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct MarqueeRequest {
+    /// Icon image relative to the media directory, shown at the left edge
+    #[schema(example = "images/mail.png")]
+    icon: String,
+    /// Text scrolled to the right of the icon
+    #[schema(example = "New message from Sam")]
+    text: String,
+    /// BDF font name (see GET /api/v1/fonts)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "6x13")]
+    font: Option<String>,
+    /// Color for the text, defaulting to the server's --default-text-color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "[255, 255, 255]")]
+    color: Option<Color>,
+    /// Scroll speed in pixels per second. Typical range: 10-100
+    #[serde(default = "default_speed")]
+    #[schema(example = 30, default = 30)]
+    speed: u32,
+}
 
-    Ok(StatusCode::OK)
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct ColorWheelRequest {
+    /// Rotation speed in degrees per second. 0 holds the wheel still
+    #[serde(default = "default_color_wheel_speed")]
+    #[schema(example = 90, default = 90)]
+    speed: u32,
 }
 
-/// POST /api/v1/display/clear — clear the display
-#[utoipa::path(
-    post,
-    path = "/api/v1/display/clear",
-    tag = "display",
-    responses(
-        (status = 200, description = "Display cleared"),
-    )
-)]
-async fn post_display_clear(
-    State(state): State<AppState>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    state.command_tx.send(RenderCommand::Clear).map_err(|_| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Render thread gone".to_string(),
-        )
-    })?;
+fn default_color_wheel_speed() -> u32 {
+    90
+}
 
-    Ok(StatusCode::OK)
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct EffectRequest {
+    /// game_of_life: Conway's Game of Life from a random board, reseeded
+    /// when it dies out or settles. bounce: a ball bouncing off the edges
+    #[schema(example = "game_of_life")]
+    kind: EffectKind,
+    /// Generations per second for game_of_life, pixels per second for bounce
+    #[serde(default = "default_effect_speed")]
+    #[schema(example = 10, default = 10)]
+    speed: u32,
+    /// Color of the live cells or the ball, defaulting to the server's --default-text-color. Either [red, green, blue] with each value 0-255 or a string, e.g. [255, 0, 0], "#ff0000", "rgb(255,0,0)", and "red" are all red
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "[0, 255, 0]")]
+    color: Option<Color>,
 }
 
-/// POST /api/v1/display/stop — stop current playback
-#[utoipa::path(
-    post,
-    path = "/api/v1/display/stop",
-    tag = "display",
-    responses(
-        (status = 200, description = "Playback stopped"),
-    )
-)]
-async fn post_display_stop(
+fn default_effect_speed() -> u32 {
+    10
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct SaveRequest {
+    /// Destination PNG path relative to media directory. The parent directory must exist.
+    #[schema(example = "images/capture.png")]
+    path: String,
+}
+
+#[derive(Clone, Default, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct ClearRequest {
+    /// Color to fill the panel with
+    #[serde(default)]
+    #[schema(example = "[10, 0, 20]", default = "[0, 0, 0]")]
+    color: Color,
+    /// Fade from the current frame to the color over this many
+    /// milliseconds. 0 or omitted clears instantly.
+    #[serde(default)]
+    #[schema(example = 500, default = 0)]
+    fade_ms: u32,
+}
+
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct InvertRequest {
+    /// Draw every pixel in its opposite color (255 - channel)
+    #[schema(example = true)]
+    enabled: bool,
+}
+
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct LoopRequest {
+    /// Keep the playing video looping; `false` lets the current pass finish
+    /// and stops there
+    #[schema(example = false)]
+    enabled: bool,
+}
+
+/// A display command that can be saved in a preset, tagged by `type`.
+///
+/// Each variant takes the same fields as the matching display endpoint,
+/// e.g. `{"type": "image", "path": "images/test.png"}`.
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PresetCommand {
+    Image(ImageRequest),
+    Wipe(WipeRequest),
+    Slideshow(SlideshowRequest),
+    Video(VideoRequest),
+    Text(TextRequest),
+    TextLines(TextLinesRequest),
+    Sysinfo(SysInfoRequest),
+    RawText(RawTextRequest),
+    TextFile(TextFileRequest),
+    Gauge(GaugeRequest),
+    Qr(QrRequest),
+    Polygon(PolygonRequest),
+    Sprite(SpriteRequest),
+    Gradient(GradientRequest),
+    ColorWheel(ColorWheelRequest),
+    Effect(EffectRequest),
+    Marquee(MarqueeRequest),
+    TypeText(TypeTextRequest),
+    Timer(TimerRequest),
+    Clear(ClearRequest),
+    Stop(StopRequest),
+    Brightness(BrightnessRequest),
+    Invert(InvertRequest),
+}
+
+/// A saved command, optionally with a brightness to apply first.
+#[derive(Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct Preset {
+    /// Command replayed when the preset is activated
+    command: PresetCommand,
+    /// Brightness (0-100) set before the command, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 40)]
+    brightness: Option<u8>,
+}
+
+/// A command shown for a while before the panel goes back to what it was
+/// showing, e.g. a notification over a clock.
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct TemporaryRequest {
+    /// Command to show, in the same format as a preset's command
+    command: PresetCommand,
+    /// How long to show it before going back, in milliseconds
+    #[serde(default = "default_restore_after")]
+    #[schema(example = 3000, default = 5000)]
+    restore_after_ms: u32,
+}
+
+fn default_restore_after() -> u32 {
+    5000
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct PresetRequest {
+    /// Preset name: letters, digits, '-' and '_' (max 64)
+    #[schema(example = "morning")]
+    name: String,
+    #[serde(flatten)]
+    preset: Preset,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct PresetEntry {
+    name: String,
+    #[serde(flatten)]
+    preset: Preset,
+}
+
+/// File types the server can display.
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct FormatsResponse {
+    /// Image extensions, without the dot
+    #[schema(example = json!(["png", "jpg", "jpeg"]))]
+    images: Vec<&'static str>,
+    /// Extensions of the frame images inside a video directory
+    #[schema(example = json!(["png", "jpg", "jpeg"]))]
+    videos: Vec<&'static str>,
+}
+
+/// Result of re-scanning and validating the fonts directory.
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct FontRefreshResponse {
+    /// Fonts that parsed successfully
+    fonts: Vec<String>,
+    /// Fonts that failed to parse, with the reason
+    failed: Vec<FontError>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct FontError {
+    /// Font name (file name without .bdf)
+    name: String,
+    /// Parse error reported by the font loader
+    error: String,
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct FontPreviewQuery {
+    /// Sample text to render
+    #[serde(default = "default_preview_text")]
+    #[param(example = "Hello", default = "Hello")]
+    text: String,
+}
+
+/// Source frame size a stream client sends, if it isn't the panel size.
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct StreamQuery {
+    /// Width of incoming frames; must be given with `h`
+    #[param(example = 128)]
+    w: Option<u32>,
+    /// Height of incoming frames; must be given with `w`
+    #[param(example = 128)]
+    h: Option<u32>,
+    /// How frames are scaled to the panel: nearest or bilinear
+    #[serde(default)]
+    #[param(example = "bilinear", default = "nearest")]
+    filter: ScaleFilter,
+    /// crc32: each frame ends with a 4-byte big-endian CRC32 of its RGB data
+    #[serde(default)]
+    #[param(example = "crc32", default = "none")]
+    checksum: Checksum,
+    /// Gamma to apply to each frame, for clients that send linear RGB
+    #[param(example = 2.2, default = 1.0)]
+    gamma: Option<f32>,
+    /// Brightness (0-100) to scale each frame by, on top of the panel's
+    #[param(example = 80, default = 100)]
+    brightness: Option<u8>,
+    /// Panel id to stream to
+    #[serde(default)]
+    #[param(example = 1, default = 0)]
+    panel: u32,
+}
+
+/// Filter, order, and page a media listing. The total number of matching
+/// entries, before paging, is returned in the `X-Total-Count` header.
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct ListQuery {
+    /// Only list entries whose name starts with this
+    #[serde(default)]
+    #[param(example = "sunset")]
+    prefix: String,
+    /// name (A to Z), size (largest first), or mtime (newest first)
+    #[serde(default)]
+    #[param(example = "mtime", default = "name")]
+    sort: media::SortKey,
+    /// Entries to skip, after filtering and sorting
+    #[serde(default)]
+    #[param(example = 20, default = 0)]
+    offset: usize,
+    /// Most entries to return. Defaults to all of them
+    #[param(example = 20)]
+    limit: Option<usize>,
+}
+
+impl ListQuery {
+    /// The requested page of `entries`, with the total as a header.
+    fn apply<T: media::Listed + Serialize>(self, entries: Vec<T>) -> impl IntoResponse {
+        let (page, total) = media::page(entries, &self.prefix, self.sort, self.offset, self.limit);
+        ([(TOTAL_COUNT_HEADER, total.to_string())], Json(page))
+    }
+}
+
+/// Response header carrying a listing's size before paging.
+const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
+/// Which panel a request is for, when the server drives several.
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct PanelQuery {
+    /// Panel id (see GET /api/v1/panels)
+    #[serde(default)]
+    #[param(example = 1, default = 0)]
+    panel: u32,
+}
+
+/// Per-panel status, as listed by `GET /api/v1/panels`.
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct PanelStatus {
+    /// Panel id, used as `?panel=` on display endpoints
+    id: u32,
+    status: DisplayStatus,
+}
+
+/// Opt-in integrity check for raw frames.
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct FrameQuery {
+    /// crc32: each frame ends with a 4-byte big-endian CRC32 of its RGB data
+    #[serde(default)]
+    #[param(example = "crc32", default = "none")]
+    checksum: Checksum,
+    /// rgba: 4 bytes per pixel, composited over black before display
+    #[serde(default)]
+    #[param(example = "rgba", default = "rgb")]
+    format: PixelFormat,
+}
+
+/// An error response. Most are plain text; the ones a client can correct
+/// on its own carry the numbers it needs as JSON.
+#[derive(Debug)]
+pub enum ApiError {
+    /// Status and a plain-text explanation
+    Text(StatusCode, String),
+    /// A raw frame of the wrong size, as 400 with a JSON body
+    FrameSize(FrameSizeError),
+}
+
+impl From<(StatusCode, String)> for ApiError {
+    fn from((status, message): (StatusCode, String)) -> Self {
+        Self::Text(status, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Text(status, message) => (status, message).into_response(),
+            Self::FrameSize(error) => (StatusCode::BAD_REQUEST, Json(error)).into_response(),
+        }
+    }
+}
+
+/// Body of a 400 for a raw frame of the wrong size, so a client can resize
+/// its frames and retry.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct FrameSizeError {
+    /// The same explanation a plain-text error would give
+    pub error: String,
+    /// Bytes a frame needs for this panel in the requested format
+    pub expected_bytes: usize,
+    /// Bytes the frame had, after any checksum
+    pub got_bytes: usize,
+    pub rows: u32,
+    pub cols: u32,
+}
+
+/// Largest source frame side a stream client may declare.
+const MAX_STREAM_SIDE: u32 = 1024;
+
+fn default_preview_text() -> String {
+    "Hello".to_string()
+}
+
+/// Longest sample text a preview will render, to keep images small.
+const MAX_PREVIEW_CHARS: usize = 64;
+
+/// How often the status WebSocket checks the status for changes.
+const STATUS_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+// ── Request → command conversion ─────────────────────────────────────
+//
+// Each display request validates itself and becomes a `RenderCommand`.
+// Handlers send the result straight away; presets store the request and
+// convert it when activated, so paths and fonts are checked again then.
+
+/// Validate a font name against the fonts directory and built-in fonts.
+///
+/// Font names become file paths on the render thread, so only accept fonts
+/// that actually exist.
+fn require_font(state: &AppState, name: &str) -> Result<(), (StatusCode, String)> {
+    if media::list_available_fonts(&state.fonts_dir)
+        .iter()
+        .any(|f| f == name)
+    {
+        Ok(())
+    } else {
+        Err((StatusCode::NOT_FOUND, format!("Font not found: {name}")))
+    }
+}
+
+impl ImageRequest {
+    /// Decodes and fits the image, which is slow on a Pi Zero: call it via
+    /// `run_blocking` from async code.
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        let path = validate_media_path(&state.media_dir, &self.path)?;
+        if let Some(deg) = self.rotate_deg
+            && !(-360.0..=360.0).contains(&deg)
+        {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "rotate_deg must be between -360 and 360".to_string(),
+            ));
+        }
+
+        let background = self.background;
+        // Read before loading, so a write during the load is picked up by the watch
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let mut img =
+            load_and_fit_image(&path, state.panel, self.fit, background).map_err(|e| {
+                (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!("Failed to load image {}: {e}", self.path),
+                )
+            })?;
+        if let Some(deg) = self.rotate_deg.filter(|&deg| deg != 0.0) {
+            img = layout::rotate_image(&img, deg, background.into());
+        }
+        let watch = self.watch.then(|| ImageWatch {
+            path: path.clone(),
+            fit: self.fit,
+            background,
+            rotate_deg: self.rotate_deg.filter(|&deg| deg != 0.0),
+            modified,
+        });
+        Ok(RenderCommand::ShowImage {
+            img,
+            source: path.display().to_string(),
+            watch,
+        })
+    }
+}
+
+/// Run blocking work (image decoding and resizing) on tokio's blocking
+/// pool, so it stalls neither the async workers nor the render thread.
+async fn run_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, (StatusCode, String)> + Send + 'static,
+) -> Result<T, (StatusCode, String)> {
+    tokio::task::spawn_blocking(work).await.map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Image processing failed".to_string(),
+        )
+    })?
+}
+
+impl WipeRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        Ok(RenderCommand::WipeImage {
+            path: validate_media_path(&state.media_dir, &self.path)?,
+            direction: self.direction,
+            duration_ms: self.duration_ms,
+        })
+    }
+}
+
+impl SlideshowRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        if self.interval_ms < MIN_SLIDE_INTERVAL_MS {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("interval_ms must be at least {MIN_SLIDE_INTERVAL_MS}"),
+            ));
+        }
+        let dir = validate_media_path(&state.media_dir, &self.path)?;
+        if media::slideshow_images(&dir).is_empty() {
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("No PNG or JPEG images in {}", self.path),
+            ));
+        }
+
+        Ok(RenderCommand::Slideshow {
+            dir,
+            interval_ms: self.interval_ms,
+            transition: self.transition,
+            shuffle: self.shuffle,
+            loop_playback: self.loop_playback,
+        })
+    }
+}
+
+impl VideoRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        let dir = validate_media_path(&state.media_dir, &self.path)?;
+        // Caught here, a wrong path gets a useful error instead of failing
+        // on the render thread after the request has returned
+        if media::count_frames(&dir) == 0 {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "{} has no video frames: a video is a directory of .{} images (see GET /api/v1/videos)",
+                    self.path,
+                    media::VIDEO_FRAME_EXTENSIONS.join(", .")
+                ),
+            ));
+        }
+
+        Ok(RenderCommand::PlayVideo {
+            dir,
+            fps: self.fps,
+            loop_count: match (self.loop_count, self.loop_playback) {
+                (Some(0), _) | (None, true) => None,
+                (Some(count), _) => Some(count),
+                (None, false) => Some(1),
+            },
+            fit: self.fit,
+            background: self.background,
+            show_title: self.show_title,
+            on_finish: self.on_finish.unwrap_or(state.video_finish),
+        })
+    }
+}
+
+impl TextRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        if self.repeat == Some(0) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "repeat must be at least 1".to_string(),
+            ));
+        }
+        let y = match (self.y, self.y_percent) {
+            (None, None) => VerticalPosition::Center,
+            (Some(y), None) => VerticalPosition::Pixels(y),
+            (None, Some(f)) if (0.0..=1.0).contains(&f) => VerticalPosition::Fraction(f),
+            (None, Some(_)) => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "y_percent must be between 0.0 and 1.0".to_string(),
+                ));
+            }
+            (Some(_), Some(_)) => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "Give y or y_percent, not both".to_string(),
+                ));
+            }
+        };
+
+        let font = state.font_or_default(self.font);
+        require_font(state, &font)?;
+        let segments = if self.segments.is_empty() {
+            vec![Span {
+                text: self.text,
+                color: state.text_color_or_default(self.color),
+            }]
+        } else {
+            self.segments
+                .into_iter()
+                .map(|seg| Span {
+                    text: seg.text,
+                    color: state.text_color_or_default(seg.color),
+                })
+                .collect()
+        };
+
+        // Only an explicit position can put the text out of sight, and then
+        // its size decides
+        if self.mode == TextMode::Scroll
+            && (self.x.is_some() || self.y.is_some())
+            && let Ok(bdf) = BdfFont::load_named(&state.fonts_dir, &font)
+        {
+            let (rows, cols) = (state.panel.rows, state.panel.cols);
+            let width = segments.iter().map(|seg| bdf.text_width(&seg.text)).sum();
+            let x = self.x.unwrap_or(cols as i32);
+            let top = y.top(&bdf, rows);
+            if !effects::scroll_start_visible(x, top, width, bdf.height(), rows) {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "Text starting at ({x}, {top}) would never be visible on a {cols}x{rows} panel"
+                    ),
+                ));
+            }
+        }
+
+        Ok(RenderCommand::ScrollText {
+            segments,
+            font,
+            speed: self.speed,
+            mode: self.mode,
+            x: self.x,
+            y,
+            repeat: self.repeat,
+        })
+    }
+}
+
+impl SysInfoRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        let font = state.font_or_default(self.font);
+        require_font(state, &font)?;
+        Ok(RenderCommand::ShowSysInfo {
+            font,
+            color: state.text_color_or_default(self.color),
+        })
+    }
+}
+
+impl TextLinesRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        if self.lines.is_empty() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Give at least one line".to_string(),
+            ));
+        }
+
+        let lines = self
+            .lines
+            .into_iter()
+            .map(|line| {
+                let font = state.font_or_default(line.font);
+                require_font(state, &font)?;
+                Ok(TextLine {
+                    text: line.text,
+                    font,
+                    color: state.text_color_or_default(line.color),
+                    y: line.y,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(RenderCommand::ShowTextGrid { lines })
+    }
+}
+
+impl RawTextRequest {
+    fn into_command(self, _state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        if self.width == 0 || self.height == 0 {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Bitmap width and height must be nonzero".to_string(),
+            ));
+        }
+
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(&self.bitmap)
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid base64: {e}")))?;
+
+        let image = draw::bitmap_image(self.width, self.height, &data, self.format, self.color)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+        Ok(RenderCommand::ScrollBitmap {
+            image,
+            speed: self.speed,
+        })
+    }
+}
+
+impl FramesRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        if self.frames.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, "No frames given".to_string()));
+        }
+        let delays = frame::frame_delays(self.frames.len(), &self.delays_ms, self.fps)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+        let expected = state.panel.frame_byte_count();
+        let frames = self
+            .frames
+            .iter()
+            .enumerate()
+            .map(|(i, encoded)| {
+                let data = base64::engine::general_purpose::STANDARD
+                    .decode(encoded)
+                    .map_err(|e| {
+                        (
+                            StatusCode::BAD_REQUEST,
+                            format!("Frame {i}: invalid base64: {e}"),
+                        )
+                    })?;
+                if data.len() != expected {
+                    return Err((
+                        StatusCode::BAD_REQUEST,
+                        format!(
+                            "Frame {i}: expected {expected} bytes, got {}{}",
+                            data.len(),
+                            frame::size_hint(data.len())
+                        ),
+                    ));
+                }
+                Ok(RgbImage::from_raw(state.panel.cols, state.panel.rows, data)
+                    .expect("length checked above"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(RenderCommand::PlayFrames {
+            frames,
+            delays,
+            loop_playback: self.loop_playback,
+        })
+    }
+}
+
+impl TextFileRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        let path = validate_media_path(&state.media_dir, &self.path)?;
+        if let Some(name) = &self.font {
+            require_font(state, name)?;
+        }
+
+        Ok(RenderCommand::ShowTextFile {
+            path,
+            font: self.font,
+            color: state.text_color_or_default(self.color),
+            ansi: self.ansi,
+        })
+    }
+}
+
+impl GaugeRequest {
+    fn into_command(self, _state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        let color = self.color;
+        // The track is the fill color at low brightness, so it reads as "empty"
+        let track = self.track.then(|| color.apply_brightness(15));
+
+        Ok(RenderCommand::DrawGauge {
+            value: self.value,
+            min: self.min,
+            max: self.max,
+            color,
+            style: self.style,
+            track,
+        })
+    }
+}
+
+impl QrRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        let (color, background) = (self.color, self.background);
+
+        // Encoding is cheap; do it here too so bad requests get a 400
+        draw::qr_code(
+            &self.data,
+            state.panel.cols,
+            state.panel.rows,
+            color,
+            background,
+        )
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+        Ok(RenderCommand::ShowQr {
+            data: self.data,
+            color,
+            background,
+        })
+    }
+}
+
+impl PolygonRequest {
+    fn into_command(self, _state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        if self.points.len() < 2 {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "A polygon needs at least two points".to_string(),
+            ));
+        }
+        let in_range = |v: i32| (-MAX_POLYGON_COORD..=MAX_POLYGON_COORD).contains(&v);
+        if !self.points.iter().all(|&(x, y)| in_range(x) && in_range(y)) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Polygon points must be within ±{MAX_POLYGON_COORD}"),
+            ));
+        }
+
+        Ok(RenderCommand::DrawPolygon {
+            points: self.points,
+            color: self.color,
+            fill: self.fill,
+        })
+    }
+}
+
+impl SpriteRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        let Some(sprite) = sprites::get(&self.name) else {
+            let names: Vec<_> = sprites::names().collect();
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!(
+                    "Unknown sprite: {} (available: {})",
+                    self.name,
+                    names.join(", ")
+                ),
+            ));
+        };
+        if !(1..=MAX_SPRITE_SCALE).contains(&self.scale) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("scale must be between 1 and {MAX_SPRITE_SCALE}"),
+            ));
+        }
+
+        let size = (SPRITE_SIZE * self.scale) as i32;
+        let centered = |side: u32| (side as i32 - size) / 2;
+        Ok(RenderCommand::ShowSprite {
+            name: self.name,
+            sprite,
+            x: self.x.unwrap_or_else(|| centered(state.panel.cols)),
+            y: self.y.unwrap_or_else(|| centered(state.panel.rows)),
+            scale: self.scale,
+            color: self.color,
+            clear: self.clear,
+        })
+    }
+}
+
+impl GradientRequest {
+    fn into_command(self, _state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        Ok(RenderCommand::Gradient {
+            from: self.from,
+            to: self.to,
+            direction: self.direction,
+        })
+    }
+}
+
+impl TypeTextRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        let font = state.font_or_default(self.font);
+        require_font(state, &font)?;
+
+        Ok(RenderCommand::TypeText {
+            text: self.text,
+            font,
+            color: state.text_color_or_default(self.color),
+            char_delay_ms: self.char_delay_ms,
+        })
+    }
+}
+
+impl TimerRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        let font = state.font_or_default(self.font);
+        require_font(state, &font)?;
+
+        Ok(RenderCommand::Timer {
+            seconds: self.seconds,
+            font,
+            color: state.text_color_or_default(self.color),
+            on_finish: self.on_finish,
+            started_at: None,
+        })
+    }
+}
+
+impl MarqueeRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        let icon_path = validate_media_path(&state.media_dir, &self.icon)?;
+        let font = state.font_or_default(self.font);
+        require_font(state, &font)?;
+
+        Ok(RenderCommand::IconMarquee {
+            icon_path,
+            text: self.text,
+            font,
+            color: state.text_color_or_default(self.color),
+            speed: self.speed,
+        })
+    }
+}
+
+impl ColorWheelRequest {
+    fn into_command(self, _state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        Ok(RenderCommand::ColorWheel { speed: self.speed })
+    }
+}
+
+impl EffectRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        if self.speed == 0 {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "speed must be at least 1".to_string(),
+            ));
+        }
+        Ok(RenderCommand::Effect {
+            kind: self.kind,
+            speed: self.speed,
+            color: state.text_color_or_default(self.color),
+        })
+    }
+}
+
+impl ClearRequest {
+    fn into_command(self, _state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        Ok(RenderCommand::Clear {
+            color: self.color,
+            fade_ms: self.fade_ms,
+        })
+    }
+}
+
+impl StopRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        Ok(RenderCommand::Stop {
+            clear: self.clears(state.stop_clears),
+        })
+    }
+}
+
+impl BrightnessRequest {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        Ok(match state.brightness_mode {
+            BrightnessMode::Software => RenderCommand::SetBrightness(self.value),
+            BrightnessMode::Hardware => RenderCommand::SetHardwareBrightness(self.value),
+        })
+    }
+}
+
+impl InvertRequest {
+    fn into_command(self, _state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        Ok(RenderCommand::SetInvert(self.enabled))
+    }
+}
+
+impl LoopRequest {
+    fn into_command(self, _state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        Ok(RenderCommand::SetLoop(self.enabled))
+    }
+}
+
+// ── Router ───────────────────────────────────────────────────────────
+
+/// The OpenAPI spec for every endpoint this build serves, including
+/// feature-gated ones.
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    #[allow(unused_mut)]
+    let mut api = ApiDoc::openapi();
+    #[cfg(feature = "remote-images")]
+    api.merge(RemoteImagesApi::openapi());
+    api
+}
+
+/// The spec with the server's addresses as its `servers`.
+///
+/// A relative `/` comes first, so Swagger's "Try it out" calls whatever
+/// origin the docs page was loaded from; the hostname may not resolve from
+/// the browser, and another origin would make every request cross-origin.
+/// `scheme://<host>:port` for each of `hosts` follows for generated
+/// clients, unless `port` is 0 and so not known yet.
+pub fn openapi_served_at(scheme: &str, hosts: &[&str], port: u16) -> utoipa::openapi::OpenApi {
+    use utoipa::openapi::Server;
+
+    let mut api = openapi();
+    let absolute = hosts
+        .iter()
+        .filter(|_| port != 0)
+        .map(|host| Server::new(format!("{scheme}://{host}:{port}")));
+    api.servers = Some(std::iter::once(Server::new("/")).chain(absolute).collect());
+    api
+}
+
+/// Build the axum router with all API endpoints, documented as served on
+/// `port` over `scheme` (`http` or `https`).
+pub fn create_router(state: AppState, scheme: &str, port: u16) -> Router {
+    let max_body_bytes = state.max_body_bytes;
+    // Listening on every interface, so name the machine rather than 0.0.0.0
+    let hostname = read_hostname();
+    let hosts: Vec<&str> = hostname
+        .as_deref()
+        .into_iter()
+        .chain(["localhost"])
+        .collect();
+    let api = openapi_served_at(scheme, &hosts, port);
+
+    let router = Router::new()
+        .merge(
+            SwaggerUi::new("/docs")
+                .url("/api-docs/openapi.json", api)
+                .config(
+                    utoipa_swagger_ui::Config::new(["/api-docs/openapi.json"])
+                        .validator_url("none"),
+                ),
+        )
+        .route("/api/v1/status", get(get_status))
+        .route("/api/v1/diagnostics", get(get_diagnostics))
+        .route("/api/v1/config", get(get_config))
+        .route("/api/v1/status/ws", get(ws_status))
+        .route("/api/v1/control", get(ws_control))
+        .route("/api/v1/panels", get(get_panels))
+        .route("/api/v1/system/reset", post(post_system_reset))
+        .route("/api/v1/system/benchmark", get(get_system_benchmark))
+        .route("/api/v1/system/identify", post(post_system_identify))
+        .route("/api/v1/images", get(get_images))
+        .route("/api/v1/videos", get(get_videos))
+        .route("/api/v1/formats", get(get_formats))
+        .route("/api/v1/fonts", get(get_fonts))
+        .route("/api/v1/fonts/refresh", post(post_fonts_refresh))
+        .route("/api/v1/fonts/{name}/preview.png", get(get_font_preview))
+        .route("/api/v1/display/image", post(post_display_image))
+        .route("/api/v1/display/wipe", post(post_display_wipe))
+        .route("/api/v1/display/slideshow", post(post_display_slideshow))
+        .route("/api/v1/display/video", post(post_display_video))
+        .route("/api/v1/display/text", post(post_display_text))
+        .route("/api/v1/display/text/lines", post(post_display_text_lines))
+        .route("/api/v1/display/sysinfo", post(post_display_sysinfo))
+        .route("/api/v1/display/raw-text", post(post_display_raw_text))
+        .route("/api/v1/display/textfile", post(post_display_textfile))
+        .route("/api/v1/display/gauge", post(post_display_gauge))
+        .route("/api/v1/display/qr", post(post_display_qr))
+        .route("/api/v1/display/polygon", post(post_display_polygon))
+        .route("/api/v1/display/sprite", post(post_display_sprite))
+        .route("/api/v1/display/temporary", post(post_display_temporary))
+        .route("/api/v1/display/gradient", post(post_display_gradient))
+        .route(
+            "/api/v1/display/color-wheel",
+            post(post_display_color_wheel),
+        )
+        .route("/api/v1/display/effect", post(post_display_effect))
+        .route("/api/v1/display/marquee", post(post_display_marquee))
+        .route("/api/v1/display/type", post(post_display_type))
+        .route("/api/v1/display/timer", post(post_display_timer))
+        .route("/api/v1/display/frame", post(post_display_frame))
+        .route("/api/v1/display/frames", post(post_display_frames))
+        .route("/api/v1/display/stream", get(ws_display_stream))
+        .route("/api/v1/display/save", post(post_display_save))
+        .route("/api/v1/display/clear", post(post_display_clear))
+        .route("/api/v1/display/stop", post(post_display_stop))
+        .route("/api/v1/display/invert", post(post_display_invert))
+        .route("/api/v1/display/loop", post(post_display_loop))
+        .route("/api/v1/brightness", post(post_brightness))
+        .route(
+            "/api/v1/brightness/schedule/resume",
+            post(post_brightness_schedule_resume),
+        )
+        .route("/api/v1/presets", get(get_presets).post(post_presets))
+        .route(
+            "/api/v1/presets/{name}/activate",
+            post(post_preset_activate),
+        );
+
+    #[cfg(feature = "remote-images")]
+    let router = router.route("/api/v1/display/image/url", post(post_display_image_url));
+
+    // In appliance mode the changing routes answer 403 rather than being
+    // left out, so clients can tell them from a typo
+    let router = if state.appliance.is_some() {
+        router.route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            appliance_guard,
+        ))
+    } else {
+        router
+    };
+
+    router
+        // Bound every body before it's buffered or decoded, so a huge upload
+        // can't exhaust memory on the Pi
+        .layer(DefaultBodyLimit::max(max_body_bytes))
+        .layer(compression())
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
+                .on_response(DefaultOnResponse::new().level(Level::INFO)),
+        )
+        .layer(CorsLayer::permissive())
+        .with_state(state)
+}
+
+/// Compress responses for clients that send `Accept-Encoding` (gzip,
+/// deflate, or br), mainly for long media listings. Tiny responses and
+/// images such as snapshots and previews are sent as they are, and
+/// WebSocket upgrades have no body to compress.
+fn compression() -> CompressionLayer {
+    CompressionLayer::new()
+}
+
+/// GET routes that still change the display: the command and frame
+/// WebSockets, and the benchmark, which takes over the panel while it runs.
+const CHANGING_GET_ROUTES: &[&str] = &[
+    "/api/v1/control",
+    "/api/v1/display/stream",
+    "/api/v1/system/benchmark",
+];
+
+/// Refuse anything that would change the display in appliance mode, unless
+/// its path was allowed with `--appliance-allow`.
+async fn appliance_guard(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    let changing = !matches!(*request.method(), Method::GET | Method::HEAD)
+        || CHANGING_GET_ROUTES.contains(&path);
+    if changing && !state.appliance_allows(path) {
+        return (
+            StatusCode::FORBIDDEN,
+            format!("{path} is disabled in appliance mode"),
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+/// GET /api/v1/status — return current display state
+#[utoipa::path(
+    get,
+    path = "/api/v1/status",
+    tag = "system",
+    params(PanelQuery),
+    responses(
+        (status = 200, description = "Current display status", body = DisplayStatus),
+        (status = 404, description = "Unknown panel")
+    )
+)]
+async fn get_status(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+) -> Result<Json<DisplayStatus>, (StatusCode, String)> {
+    Ok(Json(state.display(target.panel)?.status()))
+}
+
+/// GET /api/v1/config — the settings the server is running with
+///
+/// Panel geometry, hardware options, directories and limits after defaults
+/// and `--settings-file` are applied, for checking what a deployment is
+/// actually using. The brightness and refresh caps are the current ones.
+#[utoipa::path(
+    get,
+    path = "/api/v1/config",
+    tag = "system",
+    responses(
+        (status = 200, description = "Effective configuration", body = EffectiveConfig)
+    )
+)]
+async fn get_config(State(state): State<AppState>) -> Json<EffectiveConfig> {
+    let limits = state.limits.get();
+    Json(EffectiveConfig {
+        max_brightness: limits.max_brightness,
+        max_refresh_fps: limits.max_refresh_fps,
+        ..(*state.config).clone()
+    })
+}
+
+/// GET /api/v1/diagnostics — recent commands and their timings
+///
+/// The last few hundred commands the panel ran, oldest first, with when
+/// each was queued, started, and finished, and any error. A long gap
+/// between `queued_at_ms` and `started_at_ms` means the render thread was
+/// busy with something else.
+#[utoipa::path(
+    get,
+    path = "/api/v1/diagnostics",
+    tag = "system",
+    params(PanelQuery),
+    responses(
+        (status = 200, description = "Recent commands, oldest first", body = Vec<CommandRecord>),
+        (status = 404, description = "Unknown panel")
+    )
+)]
+async fn get_diagnostics(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+) -> Result<Json<Vec<CommandRecord>>, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let records = display
+        .diagnostics
+        .lock()
+        .unwrap()
+        .records()
+        .cloned()
+        .collect();
+    Ok(Json(records))
+}
+
+/// GET /api/v1/panels — list configured panels and their status
+#[utoipa::path(
+    get,
+    path = "/api/v1/panels",
+    tag = "system",
+    responses(
+        (status = 200, description = "Every panel, in id order", body = Vec<PanelStatus>)
+    )
+)]
+async fn get_panels(State(state): State<AppState>) -> Json<Vec<PanelStatus>> {
+    Json(
+        state
+            .displays
+            .iter()
+            .map(|(&id, display)| PanelStatus {
+                id,
+                status: display.status(),
+            })
+            .collect(),
+    )
+}
+
+/// POST /api/v1/system/reset — re-create the LED matrix
+///
+/// Recovers from corrupted panel output without restarting the process:
+/// the render thread drops the matrix, creates a fresh one, and redraws the
+/// last frame. Playback is stopped. If the matrix can't be re-created the
+/// render thread exits and the server has to be restarted.
+#[utoipa::path(
+    post,
+    path = "/api/v1/system/reset",
+    tag = "system",
+    params(PanelQuery),
+    responses(
+        (status = 200, description = "Matrix re-created"),
+        (status = 404, description = "Unknown panel"),
+        (status = 500, description = "Matrix could not be re-created")
+    )
+)]
+async fn post_system_reset(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+
+    let (reply, done) = oneshot::channel();
+    display
+        .command_tx
+        .send(RenderCommand::ResetMatrix { reply })
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Render thread gone".to_string(),
+            )
+        })?;
+
+    done.await
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Render thread gone".to_string(),
+            )
+        })?
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    Ok(StatusCode::OK)
+}
+
+/// GET /api/v1/system/benchmark — measure the panel's sustainable fps
+///
+/// The render thread draws and swaps generated frames as fast as it can for
+/// about a second, then redraws the last frame and goes idle. Use the result
+/// to pick realistic video and stream frame rates for this hardware.
+/// Playback is stopped.
+#[utoipa::path(
+    get,
+    path = "/api/v1/system/benchmark",
+    tag = "system",
+    params(PanelQuery),
+    responses(
+        (status = 200, description = "Measured throughput", body = BenchmarkResult),
+        (status = 404, description = "Unknown panel")
+    )
+)]
+async fn get_system_benchmark(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+) -> Result<Json<BenchmarkResult>, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+
+    let (reply, done) = oneshot::channel();
+    display
+        .command_tx
+        .send(RenderCommand::Benchmark { reply })
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Render thread gone".to_string(),
+            )
+        })?;
+
+    let result = done.await.map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(Json(result))
+}
+
+/// POST /api/v1/system/identify — flash the panel to find it physically
+///
+/// Flashes the device's hostname and the panel id for about three seconds,
+/// then goes back to whatever was showing, including videos and scrolling
+/// text.
+#[utoipa::path(
+    post,
+    path = "/api/v1/system/identify",
+    tag = "system",
+    params(PanelQuery),
+    responses(
+        (status = 200, description = "Panel is flashing"),
+        (status = 404, description = "Unknown panel")
+    )
+)]
+async fn post_system_identify(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let label = format!("{} #{}", hostname(), target.panel);
+
+    display
+        .command_tx
+        .send(RenderCommand::Identify { label })
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Render thread gone".to_string(),
+            )
+        })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// This machine's hostname, or `led-matrix` if it can't be read.
+fn hostname() -> String {
+    read_hostname().unwrap_or_else(|| "led-matrix".to_string())
+}
+
+fn read_hostname() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .map(|name| name.trim().to_string())
+        .ok()
+        .filter(|name| !name.is_empty())
+}
+
+/// GET /api/v1/images — list available images
+#[utoipa::path(
+    get,
+    path = "/api/v1/images",
+    tag = "media",
+    params(ListQuery),
+    responses(
+        (status = 200, description = "List of available images", body = Vec<MediaEntry>,
+            headers(("x-total-count" = usize, description = "Matching images before paging")))
+    )
+)]
+async fn get_images(
+    State(state): State<AppState>,
+    Query(query): Query<ListQuery>,
+) -> impl IntoResponse {
+    let images = media::list_images(&state.media_dir);
+    query.apply(images)
+}
+
+/// GET /api/v1/videos — list available video directories
+#[utoipa::path(
+    get,
+    path = "/api/v1/videos",
+    tag = "media",
+    params(ListQuery),
+    responses(
+        (status = 200, description = "List of available videos", body = Vec<VideoEntry>,
+            headers(("x-total-count" = usize, description = "Matching videos before paging")))
+    )
+)]
+async fn get_videos(
+    State(state): State<AppState>,
+    Query(query): Query<ListQuery>,
+) -> impl IntoResponse {
+    let videos = media::list_videos(&state.media_dir);
+    query.apply(videos)
+}
+
+/// GET /api/v1/formats — file extensions the server accepts
+///
+/// For upload UIs: files with any other extension are left out of the
+/// listings and can't be displayed.
+#[utoipa::path(
+    get,
+    path = "/api/v1/formats",
+    tag = "media",
+    responses(
+        (status = 200, description = "Supported extensions", body = FormatsResponse)
+    )
+)]
+async fn get_formats() -> Json<FormatsResponse> {
+    Json(FormatsResponse {
+        images: media::IMAGE_EXTENSIONS.to_vec(),
+        videos: media::VIDEO_FRAME_EXTENSIONS.to_vec(),
+    })
+}
+
+/// GET /api/v1/fonts — list available BDF fonts
+///
+/// Fonts in the fonts directory plus the ones built into the server. When
+/// the directory has none, the `x-fonts-dir` header says where it looked.
+#[utoipa::path(
+    get,
+    path = "/api/v1/fonts",
+    tag = "media",
+    responses(
+        (status = 200, description = "List of available font names", body = Vec<String>,
+            headers(("x-fonts-dir" = String, description = "Fonts directory searched, when it has no fonts")))
+    )
+)]
+async fn get_fonts(State(state): State<AppState>) -> impl IntoResponse {
+    let mut headers = header::HeaderMap::new();
+    if media::list_bdf_fonts(&state.fonts_dir).is_empty()
+        && let Ok(dir) = header::HeaderValue::from_str(&state.fonts_dir.display().to_string())
+    {
+        headers.insert("x-fonts-dir", dir);
+    }
+    (headers, Json(media::list_available_fonts(&state.fonts_dir)))
+}
+
+/// POST /api/v1/fonts/refresh — re-scan the fonts directory and validate each font
+///
+/// Every BDF file is parsed with the same loader the render thread uses, so
+/// broken fonts show up here instead of failing silently at first use.
+#[utoipa::path(
+    post,
+    path = "/api/v1/fonts/refresh",
+    tag = "media",
+    responses(
+        (status = 200, description = "Fonts re-scanned", body = FontRefreshResponse)
+    )
+)]
+async fn post_fonts_refresh(
+    State(state): State<AppState>,
+) -> Result<Json<FontRefreshResponse>, (StatusCode, String)> {
+    let fonts_dir = state.fonts_dir.clone();
+
+    // Font parsing is blocking file I/O, so keep it off the async workers
+    let response = tokio::task::spawn_blocking(move || {
+        let mut fonts = Vec::new();
+        let mut failed = Vec::new();
+
+        for name in media::list_bdf_fonts(&fonts_dir) {
+            let path = fonts_dir.join(format!("{name}.bdf"));
+            match LedFont::new(&path) {
+                Ok(_) => fonts.push(name),
+                Err(e) => {
+                    tracing::warn!("Font {} failed to parse: {}", path.display(), e);
+                    failed.push(FontError {
+                        name,
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        FontRefreshResponse { fonts, failed }
+    })
+    .await
+    .map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Font scan failed".to_string(),
+        )
+    })?;
+
+    Ok(Json(response))
+}
+
+/// GET /api/v1/fonts/{name}/preview.png — render sample text in a font
+///
+/// Returns a PNG of the text drawn white-on-black at the font's native
+/// pixel size, so a UI can show real previews in a font picker.
+#[utoipa::path(
+    get,
+    path = "/api/v1/fonts/{name}/preview.png",
+    tag = "media",
+    params(
+        ("name" = String, Path, description = "Font name (as listed by GET /api/v1/fonts)"),
+        FontPreviewQuery
+    ),
+    responses(
+        (status = 200, description = "PNG preview", content_type = "image/png"),
+        (status = 404, description = "Font not found"),
+        (status = 422, description = "Font failed to parse")
+    )
+)]
+async fn get_font_preview(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<FontPreviewQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    // Only accept names that exist in the fonts dir or are built in — never
+    // build a path from arbitrary input
+    require_font(&state, &name)?;
+
+    let fonts_dir = state.fonts_dir.clone();
+    let font_name = name.clone();
+    let text: String = query.text.chars().take(MAX_PREVIEW_CHARS).collect();
+
+    let png = tokio::task::spawn_blocking(move || -> Result<Vec<u8>, String> {
+        let font = BdfFont::load_named(&fonts_dir, &font_name).map_err(|e| e.to_string())?;
+        let img = font.render(&text, image::Rgb([255, 255, 255]), image::Rgb([0, 0, 0]), 1);
+        let mut png = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|e| e.to_string())?;
+        Ok(png)
+    })
+    .await
+    .map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Font preview failed".to_string(),
+        )
+    })?
+    .map_err(|e| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Failed to render {name}: {e}"),
+        )
+    })?;
+
+    Ok(([(header::CONTENT_TYPE, "image/png")], png))
+}
+
+/// POST /api/v1/display/image — display a static image
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/image",
+    tag = "display",
+    params(PanelQuery),
+    request_body = ImageRequest,
+    responses(
+        (status = 200, description = "Image displayed successfully"),
+        (status = 404, description = "Image not found"),
+        (status = 400, description = "Invalid path"),
+        (status = 422, description = "Image failed to decode")
+    )
+)]
+async fn post_display_image(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<ImageRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = run_blocking({
+        let state = state.clone();
+        move || req.into_command(&state)
+    })
+    .await?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// Longest a URL image fetch may take, including connecting.
+#[cfg(feature = "remote-images")]
+const IMAGE_URL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[cfg(feature = "remote-images")]
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ImageUrlRequest {
+    /// http or https URL of a PNG or JPEG image
+    #[schema(example = "https://example.com/weather.png")]
+    url: String,
+    /// How to scale the image when its aspect ratio differs from the panel: stretch, contain, or cover
+    #[serde(default)]
+    #[schema(example = "contain", default = "stretch")]
+    fit: FitMode,
+    /// Color of the bars left by fit=contain or center
+    #[serde(default)]
+    #[schema(example = "[10, 0, 20]", default = "[0, 0, 0]")]
+    background: Color,
+}
+
+/// POST /api/v1/display/image/url — fetch an image from a URL and display it
+///
+/// The download is limited to `--max-body-bytes` and 10 seconds, redirects
+/// aren't followed, and hosts resolving to loopback or private addresses
+/// are refused unless listed with `--image-url-host`.
+#[cfg(feature = "remote-images")]
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/image/url",
+    tag = "display",
+    params(PanelQuery),
+    request_body = ImageUrlRequest,
+    responses(
+        (status = 200, description = "Image fetched and sent to the display"),
+        (status = 400, description = "Invalid URL"),
+        (status = 403, description = "Host not allowed"),
+        (status = 413, description = "Image larger than the size limit"),
+        (status = 422, description = "Image failed to decode"),
+        (status = 502, description = "Fetch failed")
+    )
+)]
+async fn post_display_image_url(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<ImageUrlRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let data = fetch_image(&state, &req.url).await?;
+
+    let (panel, fit) = (state.panel, req.fit);
+    let background = req.background;
+    let img = run_blocking(move || {
+        let img = image::load_from_memory(&data).map_err(|e| {
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Failed to decode image: {e}"),
+            )
+        })?;
+        Ok(layout::fit_image(
+            &img.to_rgb8(),
+            panel.cols,
+            panel.rows,
+            fit,
+            background.into(),
+        ))
+    })
+    .await?;
+
+    display
+        .command_tx
+        .send(RenderCommand::ShowImage {
+            img,
+            source: req.url,
+            watch: None,
+        })
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Render thread gone".to_string(),
+            )
+        })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// Download `url` after checking it against the SSRF rules in `remote`.
+///
+/// The host is resolved once here and the client is pinned to those
+/// addresses, so a second DNS answer can't point the fetch elsewhere.
+#[cfg(feature = "remote-images")]
+async fn fetch_image(state: &AppState, url: &str) -> Result<Vec<u8>, (StatusCode, String)> {
+    let bad_gateway = |e: reqwest::Error| (StatusCode::BAD_GATEWAY, format!("Fetch failed: {e}"));
+
+    let url = reqwest::Url::parse(url)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid URL: {e}")))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Only http and https URLs are allowed".to_string(),
+        ));
+    }
+    let host = url
+        .host_str()
+        .ok_or((StatusCode::BAD_REQUEST, "URL has no host".to_string()))?;
+    let port = url.port_or_known_default().unwrap_or(80);
+
+    let ip_literal = host
+        .trim_matches(['[', ']'])
+        .parse::<std::net::IpAddr>()
+        .ok();
+    let addrs: Vec<std::net::SocketAddr> = match ip_literal {
+        Some(ip) => vec![std::net::SocketAddr::new(ip, port)],
+        None => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::BAD_GATEWAY,
+                    format!("Can't resolve {host}: {e}"),
+                )
+            })?
+            .collect(),
+    };
+    crate::remote::check_fetch_target(host, addrs.iter().map(|a| a.ip()), &state.image_url_hosts)
+        .map_err(|e| (StatusCode::FORBIDDEN, e))?;
+
+    let mut builder = reqwest::Client::builder()
+        .timeout(IMAGE_URL_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none());
+    if ip_literal.is_none() {
+        builder = builder.resolve_to_addrs(host, &addrs);
+    }
+    let client = builder.build().map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("HTTP client: {e}"),
+        )
+    })?;
+
+    let mut response = client.get(url.clone()).send().await.map_err(bad_gateway)?;
+    if !response.status().is_success() {
+        return Err((
+            StatusCode::BAD_GATEWAY,
+            format!("Fetch failed: {} returned {}", url, response.status()),
+        ));
+    }
+
+    let limit = state.max_body_bytes;
+    let too_large = || {
+        (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("Image is larger than {limit} bytes"),
+        )
+    };
+    if response
+        .content_length()
+        .is_some_and(|len| len > limit as u64)
+    {
+        return Err(too_large());
+    }
+    let mut data = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(bad_gateway)? {
+        data.extend_from_slice(&chunk);
+        if data.len() > limit {
+            return Err(too_large());
+        }
+    }
+
+    Ok(data)
+}
+
+/// POST /api/v1/display/wipe — reveal an image with a wipe transition
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/wipe",
+    tag = "display",
+    params(PanelQuery),
+    request_body = WipeRequest,
+    responses(
+        (status = 200, description = "Wipe transition started"),
+        (status = 404, description = "Image not found"),
+        (status = 400, description = "Invalid path")
+    )
+)]
+async fn post_display_wipe(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<WipeRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/slideshow — cycle through a directory of images
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/slideshow",
+    tag = "display",
+    params(PanelQuery),
+    request_body = SlideshowRequest,
+    responses(
+        (status = 200, description = "Slideshow started"),
+        (status = 404, description = "Directory not found or has no images"),
+        (status = 400, description = "Invalid path or interval")
+    )
+)]
+async fn post_display_slideshow(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<SlideshowRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/video — play a video (directory of frame images)
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/video",
+    tag = "display",
+    params(PanelQuery),
+    request_body = VideoRequest,
+    responses(
+        (status = 200, description = "Video playback started"),
+        (status = 404, description = "Video directory not found"),
+        (status = 400, description = "Invalid path, or no frame images in the directory")
+    )
+)]
+async fn post_display_video(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<VideoRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/text — scroll text across the display
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/text",
+    tag = "display",
+    params(PanelQuery),
+    request_body = TextRequest,
+    responses(
+        (status = 200, description = "Text scrolling started"),
+        (status = 400, description = "Invalid position or repeat count"),
+        (status = 404, description = "Font not found")
+    )
+)]
+async fn post_display_text(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<TextRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/text/lines — several independently styled lines at once
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/text/lines",
+    tag = "display",
+    params(PanelQuery),
+    request_body = TextLinesRequest,
+    responses(
+        (status = 200, description = "Lines displayed"),
+        (status = 400, description = "No lines given"),
+        (status = 404, description = "Font not found")
+    )
+)]
+async fn post_display_text_lines(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<TextLinesRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/sysinfo — dashboard of the Pi's own stats
+///
+/// Shows CPU temperature, load, free memory, and IP address, refreshed
+/// every few seconds until another command replaces it. The body is
+/// optional.
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/sysinfo",
+    tag = "display",
+    params(PanelQuery),
+    request_body(content = Option<SysInfoRequest>),
+    responses(
+        (status = 200, description = "Dashboard displayed"),
+        (status = 404, description = "Font not found")
+    )
+)]
+async fn post_display_sysinfo(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    req: Option<Json<SysInfoRequest>>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/raw-text — scroll a client-rendered bitmap
+///
+/// For typography the BDF fonts can't do (custom fonts, emoji): the client
+/// rasterizes the text itself and the server colorizes and scrolls it.
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/raw-text",
+    tag = "display",
+    params(PanelQuery),
+    request_body = RawTextRequest,
+    responses(
+        (status = 200, description = "Bitmap scrolling started"),
+        (status = 400, description = "Invalid base64 or bitmap size")
+    )
+)]
+async fn post_display_raw_text(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<RawTextRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/textfile — show a text file or ANSI art
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/textfile",
+    tag = "display",
+    params(PanelQuery),
+    request_body = TextFileRequest,
+    responses(
+        (status = 200, description = "Text file displayed"),
+        (status = 400, description = "Invalid path"),
+        (status = 404, description = "File or font not found")
+    )
+)]
+async fn post_display_textfile(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<TextFileRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/gauge — draw a progress bar or ring gauge
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/gauge",
+    tag = "display",
+    params(PanelQuery),
+    request_body = GaugeRequest,
+    responses(
+        (status = 200, description = "Gauge drawn"),
+    )
+)]
+async fn post_display_gauge(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<GaugeRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/qr — show a QR code
+///
+/// Most phone cameras expect dark modules on a light background, which is
+/// the default. Keep brightness moderate so the panel doesn't bloom.
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/qr",
+    tag = "display",
+    params(PanelQuery),
+    request_body = QrRequest,
+    responses(
+        (status = 200, description = "QR code displayed"),
+        (status = 400, description = "Data can't be encoded or doesn't fit the panel")
+    )
+)]
+async fn post_display_qr(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<QrRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/polygon — draw a filled or outlined polygon
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/polygon",
+    tag = "display",
+    params(PanelQuery),
+    request_body = PolygonRequest,
+    responses(
+        (status = 200, description = "Polygon displayed"),
+        (status = 400, description = "Too few points, or a point too far off the panel")
+    )
+)]
+async fn post_display_polygon(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<PolygonRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/sprite — draw a built-in icon
+///
+/// Draws over whatever is shown unless `clear` is set, so icons can be
+/// added next to text or on top of an image.
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/sprite",
+    tag = "display",
+    params(PanelQuery),
+    request_body = SpriteRequest,
+    responses(
+        (status = 200, description = "Sprite displayed"),
+        (status = 400, description = "Scale out of range"),
+        (status = 404, description = "Unknown sprite")
+    )
+)]
+async fn post_display_sprite(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<SpriteRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/gradient — fill the panel with a color gradient
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/gradient",
+    tag = "display",
+    params(PanelQuery),
+    request_body = GradientRequest,
+    responses(
+        (status = 200, description = "Gradient displayed")
+    )
+)]
+async fn post_display_gradient(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<GradientRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/marquee — static icon with text scrolling beside it
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/marquee",
+    tag = "display",
+    params(PanelQuery),
+    request_body = MarqueeRequest,
+    responses(
+        (status = 200, description = "Marquee started"),
+        (status = 404, description = "Icon or font not found"),
+        (status = 400, description = "Invalid path")
+    )
+)]
+async fn post_display_marquee(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<MarqueeRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/type — reveal text one character at a time
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/type",
+    tag = "display",
+    params(PanelQuery),
+    request_body = TypeTextRequest,
+    responses(
+        (status = 200, description = "Typing started"),
+        (status = 404, description = "Font not found")
+    )
+)]
+async fn post_display_type(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<TypeTextRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/timer — count down as `mm:ss`
+///
+/// The readout ticks down once a second until zero, then the panel
+/// flashes, clears, or shows a message. Any other command cancels it.
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/timer",
+    tag = "display",
+    params(PanelQuery),
+    request_body = TimerRequest,
+    responses(
+        (status = 200, description = "Timer started"),
+        (status = 404, description = "Font not found")
+    )
+)]
+async fn post_display_timer(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<TimerRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/color-wheel — rotating radial rainbow
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/color-wheel",
+    tag = "display",
+    params(PanelQuery),
+    request_body = ColorWheelRequest,
+    responses(
+        (status = 200, description = "Color wheel started"),
+    )
+)]
+async fn post_display_color_wheel(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<ColorWheelRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/effect — run a simulation on the panel
+///
+/// The simulation is stepped on the render thread until another command
+/// replaces it, so it keeps going without a client.
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/effect",
+    tag = "display",
+    params(PanelQuery),
+    request_body = EffectRequest,
+    responses(
+        (status = 200, description = "Effect started"),
+        (status = 400, description = "Speed of 0"),
+    )
+)]
+async fn post_display_effect(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<EffectRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/frames — play a short animation sent in the request
+///
+/// Frames are held in memory, so no files are written. Each frame can have
+/// its own delay for GIF-style timing.
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/frames",
+    tag = "display",
+    params(PanelQuery),
+    request_body = FramesRequest,
+    responses(
+        (status = 200, description = "Animation started"),
+        (status = 400, description = "Bad frame data or delay count")
+    )
+)]
+async fn post_display_frames(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<FramesRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/frame — push a raw RGB frame
+///
+/// Expects `application/octet-stream` body with exactly rows*cols*3 bytes,
+/// or rows*cols*4 with `?format=rgba`. With `?checksum=crc32`, the body is
+/// followed by a 4-byte CRC32 of it and frames that don't match are
+/// rejected.
+async fn post_display_frame(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Query(query): Query<FrameQuery>,
+    body: Bytes,
+) -> Result<StatusCode, ApiError> {
+    let display = state.display(target.panel)?;
+    let body = query.checksum.verify(&body).map_err(|e| {
+        display.status.lock().unwrap().checksum_failures += 1;
+        (StatusCode::BAD_REQUEST, e)
+    })?;
+
+    let format = query.format;
+    let expected = state.panel.pixel_count() as usize * format.bytes_per_pixel();
+    if body.len() != expected {
+        let message = format!(
+            "Expected {} bytes ({}x{}x{} {}), got {} bytes{}",
+            expected,
+            state.panel.cols,
+            state.panel.rows,
+            format.bytes_per_pixel(),
+            format.label(),
+            body.len(),
+            format.size_hint(body.len())
+        );
+        tracing::warn!("Raw frame rejected: {message}");
+        return Err(ApiError::FrameSize(FrameSizeError {
+            error: message,
+            expected_bytes: expected,
+            got_bytes: body.len(),
+            rows: state.panel.rows,
+            cols: state.panel.cols,
+        }));
+    }
+
+    display
+        .command_tx
+        .send(RenderCommand::ShowFrame(format.to_rgb(body)))
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Render thread gone".to_string(),
+            )
+        })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/save — save the current frame as a PNG
+///
+/// Writes the last full frame the render thread drew (images, video frames,
+/// scrolling text, raw/streamed frames, and generated graphics) into the
+/// media directory so it can be displayed again later, and returns its
+/// media entry. The frame is in the logical orientation clients draw in,
+/// before any `--pixel-map` remapping.
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/save",
+    tag = "display",
+    params(PanelQuery),
+    request_body = SaveRequest,
+    responses(
+        (status = 200, description = "Snapshot saved", body = MediaEntry),
+        (status = 400, description = "Invalid path"),
+        (status = 404, description = "Destination directory not found"),
+        (status = 500, description = "Failed to write the file")
+    )
+)]
+async fn post_display_save(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<SaveRequest>,
+) -> Result<Json<MediaEntry>, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let full_path = validate_media_write_path(&state.media_dir, &req.path, "png")?;
+    // As listed, e.g. `images/capture.png` for `./images/capture.png`
+    let rel_path = state
+        .media_dir
+        .canonicalize()
+        .ok()
+        .and_then(|media_dir| full_path.strip_prefix(media_dir).ok().map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from(&req.path))
+        .to_string_lossy()
+        .into_owned();
+
+    let (reply, done) = oneshot::channel();
+    display
+        .command_tx
+        .send(RenderCommand::SaveSnapshot {
+            path: full_path.clone(),
+            reply,
+        })
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Render thread gone".to_string(),
+            )
+        })?;
+
+    done.await
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Render thread gone".to_string(),
+            )
+        })?
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    Ok(Json(MediaEntry::for_file(&full_path, rel_path)))
+}
+
+/// POST /api/v1/display/clear — clear the display
+///
+/// Blanks the panel, or fills it with `{"color": [r, g, b]}`. The body is
+/// optional.
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/clear",
+    tag = "display",
+    params(PanelQuery),
+    request_body(content = Option<ClearRequest>),
+    responses(
+        (status = 200, description = "Display cleared"),
+    )
+)]
+async fn post_display_clear(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    req: Option<Json<ClearRequest>>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/stop — stop current playback
+///
+/// Stops videos and animations where they are. By default the last frame
+/// stays on the panel; use `/api/v1/display/clear` to blank it, send
+/// `{"clear": true}`, or start the server with `--stop-clears`. The body is
+/// optional.
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/stop",
+    tag = "display",
+    params(PanelQuery),
+    request_body(content = Option<StopRequest>),
+    responses(
+        (status = 200, description = "Playback stopped"),
+    )
+)]
+async fn post_display_stop(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    req: Option<Json<StopRequest>>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/brightness — set display brightness (0-100)
+///
+/// Sets software (per-pixel) brightness, or the matrix's hardware brightness
+/// when the server runs with `--brightness-mode hardware`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/brightness",
+    tag = "display",
+    params(PanelQuery),
+    request_body = BrightnessRequest,
+    responses(
+        (status = 200, description = "Brightness updated"),
+    )
+)]
+async fn post_brightness(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<BrightnessRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+    display.schedule_override.store(true, Ordering::Relaxed);
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/invert — turn color inversion on or off
+///
+/// Inversion is applied at draw time on top of brightness, to whatever is
+/// showing now and everything drawn later, until it's turned off.
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/invert",
+    tag = "display",
+    params(PanelQuery),
+    request_body = InvertRequest,
+    responses(
+        (status = 200, description = "Inversion updated"),
+    )
+)]
+async fn post_display_invert(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<InvertRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/loop — turn looping of the playing video on or off
+///
+/// The video keeps playing either way: turned off, it stops at the end of
+/// the current pass (then does its `on_finish`); turned on, it loops until
+/// another command replaces it. Does nothing unless a video is playing.
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/loop",
+    tag = "display",
+    params(PanelQuery),
+    request_body = LoopRequest,
+    responses(
+        (status = 200, description = "Looping updated"),
+    )
+)]
+async fn post_display_loop(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<LoopRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let cmd = req.into_command(&state)?;
+
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/brightness/schedule/resume — follow the brightness schedule again
+///
+/// Undoes the override from an explicit brightness command and applies the
+/// brightness the schedule gives for the current local time. With a light
+/// sensor instead, its next reading sets the brightness.
+#[utoipa::path(
+    post,
+    path = "/api/v1/brightness/schedule/resume",
+    tag = "display",
+    params(PanelQuery),
+    responses(
+        (status = 200, description = "Brightness schedule resumed"),
+        (status = 404, description = "No brightness schedule or light sensor configured")
+    )
+)]
+async fn post_brightness_schedule_resume(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let Some(schedule) = state.brightness_schedule.as_ref() else {
+        if !state.light_sensor {
+            return Err((
+                StatusCode::NOT_FOUND,
+                "No brightness schedule or light sensor configured".to_string(),
+            ));
+        }
+        display.schedule_override.store(false, Ordering::Relaxed);
+        return Ok(StatusCode::OK);
+    };
+
+    let value = scheduled_brightness(schedule);
+    let cmd = BrightnessRequest { value }.into_command(&state)?;
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+    display.schedule_override.store(false, Ordering::Relaxed);
+
+    Ok(StatusCode::OK)
+}
+
+/// Brightness the schedule gives for the current local time.
+fn scheduled_brightness(schedule: &BrightnessSchedule) -> u8 {
+    schedule.brightness_at(chrono::Local::now().num_seconds_from_midnight())
+}
+
+/// How often the brightness schedule checks the clock.
+const SCHEDULE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Start the background task that applies the brightness schedule.
+///
+/// Every `SCHEDULE_INTERVAL` it works out the scheduled brightness and, when
+/// it has changed, sends it to every panel that isn't overridden. Does
+/// nothing if no schedule is configured.
+pub fn spawn_brightness_schedule(state: AppState) {
+    let Some(schedule) = state.brightness_schedule.clone() else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SCHEDULE_INTERVAL);
+        let mut last = None;
+        loop {
+            interval.tick().await;
+
+            let value = scheduled_brightness(&schedule);
+            if last == Some(value) {
+                continue;
+            }
+            last = Some(value);
+            tracing::info!("Scheduled brightness: {value}");
+
+            for display in state.displays.values() {
+                if display.schedule_override.load(Ordering::Relaxed) {
+                    continue;
+                }
+                if let Ok(cmd) = (BrightnessRequest { value }).into_command(&state) {
+                    let _ = display.command_tx.send(cmd);
+                }
+            }
+        }
+    });
+}
+
+/// Start the task that re-reads `path` on SIGHUP and applies it to the
+/// state's `limits`.
+///
+/// Logs each setting that changed and each key that only a restart can
+/// change. A file that fails to load is logged and leaves the current
+/// settings alone. Lowering `max_brightness` below a panel's brightness
+/// brings that panel down to the new cap.
+pub fn spawn_settings_reload(state: AppState, path: PathBuf, flags: RuntimeSettings) {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(e) => {
+            tracing::warn!("Can't listen for SIGHUP, settings won't reload: {e}");
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            let (file, restart_only) = match SettingsFile::load(&path) {
+                Ok(loaded) => loaded,
+                Err(e) => {
+                    tracing::warn!("Settings not reloaded: {e}");
+                    continue;
+                }
+            };
+            for key in &restart_only {
+                tracing::warn!("Settings reload: {key} needs a restart; ignored");
+            }
+            let limits = &state.limits;
+            let old = limits.get();
+            let new = file.resolve(flags);
+            let changes = old.changes(&new);
+            if changes.is_empty() {
+                tracing::info!("Settings reloaded from {}: no changes", path.display());
+                continue;
+            }
+            limits.set(new);
+            tracing::info!(
+                "Settings reloaded from {}: {}",
+                path.display(),
+                changes.join(", ")
+            );
+
+            for display in state.displays.values() {
+                let value = {
+                    let mut status = display.status.lock().unwrap();
+                    status.max_brightness = new.max_brightness;
+                    match state.brightness_mode {
+                        BrightnessMode::Software => status.brightness,
+                        BrightnessMode::Hardware => status.hardware_brightness,
+                    }
+                };
+                if value > new.max_brightness
+                    && let Ok(cmd) = (BrightnessRequest { value }).into_command(&state)
+                {
+                    let _ = display.command_tx.send(cmd);
+                }
+            }
+        }
+    });
+}
+
+/// How often the light sensor is read.
+#[cfg(feature = "light-sensor")]
+const LIGHT_SENSOR_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Start the background thread that sets brightness from a light sensor.
+///
+/// Every `LIGHT_SENSOR_INTERVAL` it reads the sensor, maps the reading
+/// through `curve`, and sends the brightness to every panel that isn't
+/// overridden and isn't already at that value. Failed readings are logged
+/// and skipped.
+///
+/// It's always the software brightness, even with `--brightness-mode
+/// hardware`: nearly every reading gives a new value, and each hardware
+/// change re-creates the matrix, blanking the panel and stopping playback.
+#[cfg(feature = "light-sensor")]
+pub fn spawn_light_sensor(
+    state: AppState,
+    mut sensor: crate::light::Bh1750,
+    curve: crate::light::LuxCurve,
+) {
+    // The sensor does blocking I2C reads, so it gets a thread rather than
+    // a tokio task
+    std::thread::spawn(move || {
+        // Last brightness sent to each panel; forgotten while a panel is
+        // overridden, so resuming applies the sensor's value again
+        let mut sent: BTreeMap<u32, u8> = BTreeMap::new();
+        let mut last = None;
+        loop {
+            let lux = match sensor.read_lux() {
+                Ok(lux) => lux,
+                Err(e) => {
+                    tracing::warn!("Light sensor read failed: {e}");
+                    std::thread::sleep(LIGHT_SENSOR_INTERVAL);
+                    continue;
+                }
+            };
+
+            let value = curve.brightness_at(lux);
+            if last != Some(value) {
+                last = Some(value);
+                tracing::info!("Light sensor: {lux:.0} lux, brightness {value}");
+            }
+
+            for (&id, display) in state.displays.iter() {
+                if display.schedule_override.load(Ordering::Relaxed) {
+                    sent.remove(&id);
+                    continue;
+                }
+                if sent.get(&id) == Some(&value) {
+                    continue;
+                }
+                if display
+                    .command_tx
+                    .send(RenderCommand::SetBrightness(value))
+                    .is_ok()
+                {
+                    sent.insert(id, value);
+                }
+            }
+
+            std::thread::sleep(LIGHT_SENSOR_INTERVAL);
+        }
+    });
+}
+
+// ── Presets ──────────────────────────────────────────────────────────
+
+impl PresetCommand {
+    fn into_command(self, state: &AppState) -> Result<RenderCommand, (StatusCode, String)> {
+        match self {
+            Self::Image(req) => req.into_command(state),
+            Self::Wipe(req) => req.into_command(state),
+            Self::Slideshow(req) => req.into_command(state),
+            Self::Video(req) => req.into_command(state),
+            Self::Text(req) => req.into_command(state),
+            Self::TextLines(req) => req.into_command(state),
+            Self::Sysinfo(req) => req.into_command(state),
+            Self::RawText(req) => req.into_command(state),
+            Self::TextFile(req) => req.into_command(state),
+            Self::Gauge(req) => req.into_command(state),
+            Self::Qr(req) => req.into_command(state),
+            Self::Polygon(req) => req.into_command(state),
+            Self::Sprite(req) => req.into_command(state),
+            Self::Gradient(req) => req.into_command(state),
+            Self::ColorWheel(req) => req.into_command(state),
+            Self::Effect(req) => req.into_command(state),
+            Self::Marquee(req) => req.into_command(state),
+            Self::TypeText(req) => req.into_command(state),
+            Self::Timer(req) => req.into_command(state),
+            Self::Clear(req) => req.into_command(state),
+            Self::Stop(req) => req.into_command(state),
+            Self::Brightness(req) => req.into_command(state),
+            Self::Invert(req) => req.into_command(state),
+        }
+    }
+}
+
+impl Preset {
+    /// Whether activating this preset changes brightness, which overrides
+    /// the brightness schedule.
+    fn sets_brightness(&self) -> bool {
+        self.brightness.is_some() || matches!(self.command, PresetCommand::Brightness(_))
+    }
+
+    /// Commands to send, in order: brightness first, then the command.
+    fn into_commands(self, state: &AppState) -> Result<Vec<RenderCommand>, (StatusCode, String)> {
+        let mut commands = Vec::new();
+        if let Some(value) = self.brightness {
+            commands.push(BrightnessRequest { value }.into_command(state)?);
+        }
+        commands.push(self.command.into_command(state)?);
+        Ok(commands)
+    }
+}
+
+/// Activate preset `name` on every panel, for the content a display starts
+/// up with (`--startup-preset`).
+pub fn activate_startup_preset(state: &AppState, name: &str) -> Result<(), String> {
+    let preset = state
+        .presets
+        .lock()
+        .unwrap()
+        .get(name)
+        .cloned()
+        .ok_or_else(|| format!("Preset not found: {name}"))?;
+
+    for display in state.displays.values() {
+        let commands = preset.clone().into_commands(state).map_err(|(_, e)| e)?;
+        for cmd in commands {
+            display
+                .command_tx
+                .send(cmd)
+                .map_err(|_| "Render thread gone".to_string())?;
+        }
+        if preset.sets_brightness() {
+            display.schedule_override.store(true, Ordering::Relaxed);
+        }
+    }
+    Ok(())
+}
+
+/// GET /api/v1/presets — list saved presets
+#[utoipa::path(
+    get,
+    path = "/api/v1/presets",
+    tag = "presets",
+    responses(
+        (status = 200, description = "Saved presets in name order", body = Vec<PresetEntry>)
+    )
+)]
+async fn get_presets(State(state): State<AppState>) -> Json<Vec<PresetEntry>> {
+    let store = state.presets.lock().unwrap();
+    Json(
+        store
+            .iter()
+            .map(|(name, preset)| PresetEntry {
+                name: name.clone(),
+                preset: preset.clone(),
+            })
+            .collect(),
+    )
+}
+
+/// POST /api/v1/presets — save a command under a name
+///
+/// Replaces any preset with the same name. The command is checked the same
+/// way its display endpoint would check it, but not run.
+#[utoipa::path(
+    post,
+    path = "/api/v1/presets",
+    tag = "presets",
+    request_body = PresetRequest,
+    responses(
+        (status = 200, description = "Preset saved"),
+        (status = 400, description = "Invalid name or command"),
+        (status = 404, description = "Media file or font not found"),
+        (status = 422, description = "Image failed to decode"),
+        (status = 500, description = "Failed to write the presets file")
+    )
+)]
+async fn post_presets(
+    State(state): State<AppState>,
+    Json(req): Json<PresetRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    presets::validate_name(&req.name).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    run_blocking({
+        let (state, preset) = (state.clone(), req.preset.clone());
+        move || preset.into_commands(&state)
+    })
+    .await?;
+
+    state
+        .presets
+        .lock()
+        .unwrap()
+        .insert(req.name, req.preset)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to save preset: {e}"),
+            )
+        })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/presets/{name}/activate — replay a saved preset
+#[utoipa::path(
+    post,
+    path = "/api/v1/presets/{name}/activate",
+    tag = "presets",
+    params(
+        ("name" = String, Path, description = "Preset name"),
+        PanelQuery,
+    ),
+    responses(
+        (status = 200, description = "Preset activated"),
+        (status = 404, description = "Preset, media file, or font not found"),
+        (status = 422, description = "Image failed to decode")
+    )
+)]
+async fn post_preset_activate(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let display = state.display(target.panel)?;
+    let preset = state
+        .presets
+        .lock()
+        .unwrap()
+        .get(&name)
+        .cloned()
+        .ok_or((StatusCode::NOT_FOUND, format!("Preset not found: {name}")))?;
+
+    let sets_brightness = preset.sets_brightness();
+    let commands = run_blocking({
+        let state = state.clone();
+        move || preset.into_commands(&state)
+    })
+    .await?;
+    for cmd in commands {
+        display.command_tx.send(cmd).map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Render thread gone".to_string(),
+            )
+        })?;
+    }
+    if sets_brightness {
+        display.schedule_override.store(true, Ordering::Relaxed);
+    }
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/v1/display/temporary — show a command, then go back
+///
+/// Runs `command` for `restore_after_ms`, then returns to what was showing
+/// before: a video or animation is restarted, and anything else is redrawn
+/// as it was. Any other command sent in the meantime cancels the return;
+/// another temporary one extends it.
+#[utoipa::path(
+    post,
+    path = "/api/v1/display/temporary",
+    tag = "display",
+    params(PanelQuery),
+    request_body = TemporaryRequest,
+    responses(
+        (status = 200, description = "Command shown"),
+        (status = 400, description = "Invalid command, or one that adjusts the panel rather than showing something"),
+        (status = 404, description = "Media file or font not found")
+    )
+)]
+async fn post_display_temporary(
     State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    Json(req): Json<TemporaryRequest>,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    state.command_tx.send(RenderCommand::Stop).map_err(|_| {
+    let display = state.display(target.panel)?;
+    if matches!(
+        req.command,
+        PresetCommand::Brightness(_) | PresetCommand::Invert(_)
+    ) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Brightness and invert can't be temporary".to_string(),
+        ));
+    }
+
+    let cmd = run_blocking({
+        let state = state.clone();
+        move || req.command.into_command(&state)
+    })
+    .await?;
+    let cmd = RenderCommand::Temporary {
+        cmd: Box::new(cmd),
+        restore_after: std::time::Duration::from_millis(req.restore_after_ms as u64),
+    };
+
+    display.command_tx.send(cmd).map_err(|_| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             "Render thread gone".to_string(),
@@ -422,31 +3700,179 @@ async fn post_display_stop(
     Ok(StatusCode::OK)
 }
 
-/// POST /api/v1/brightness — set display brightness (0-100)
-#[utoipa::path(
-    post,
-    path = "/api/v1/brightness",
-    tag = "display",
-    request_body = BrightnessRequest,
-    responses(
-        (status = 200, description = "Brightness updated"),
-    )
-)]
-async fn post_brightness(
+// ── WebSocket status ────────────────────────────────────────────────
+
+/// GET /api/v1/status/ws — WebSocket that pushes status updates.
+///
+/// Sends the panel's `DisplayStatus` as JSON on connect and again whenever
+/// it changes (checked every `STATUS_POLL_INTERVAL`). Text messages from the
+/// client are run as commands, in the same format as a preset's `command`
+/// (e.g. `{"type": "clear"}`); a command that fails is answered with
+/// `{"error": "..."}`.
+async fn ws_status(
     State(state): State<AppState>,
-    Json(req): Json<BrightnessRequest>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    state
-        .command_tx
-        .send(RenderCommand::SetBrightness(req.value))
-        .map_err(|_| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Render thread gone".to_string(),
-            )
-        })?;
+    Query(target): Query<PanelQuery>,
+    ws: WebSocketUpgrade,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let display = state.display(target.panel)?.clone();
+    Ok(ws.on_upgrade(move |socket| handle_status_socket(socket, state, display)))
+}
 
-    Ok(StatusCode::OK)
+async fn handle_status_socket(mut socket: WebSocket, state: AppState, display: Display) {
+    tracing::info!("WebSocket status client connected");
+
+    let mut interval = tokio::time::interval(STATUS_POLL_INTERVAL);
+    let mut last_sent = String::new();
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let json = match serde_json::to_string(&display.status()) {
+                    Ok(json) => json,
+                    Err(e) => {
+                        tracing::error!("Failed to serialize status: {}", e);
+                        break;
+                    }
+                };
+                if json == last_sent {
+                    continue;
+                }
+                if socket.send(Message::Text(json.clone().into())).await.is_err() {
+                    break;
+                }
+                last_sent = json;
+            }
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Text(text))) => {
+                    if let Err((_, e)) = run_socket_command(&state, &display, &text).await {
+                        let reply = serde_json::json!({ "error": e }).to_string();
+                        if socket.send(Message::Text(reply.into())).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Err(e)) => {
+                    tracing::warn!("WebSocket receive error: {}", e);
+                    break;
+                }
+                Some(Ok(_)) => {} // Ignore binary, ping/pong handled by axum
+            }
+        }
+    }
+
+    tracing::info!("WebSocket status client disconnected");
+}
+
+/// Parse and send one command received on the status WebSocket.
+async fn run_socket_command(
+    state: &AppState,
+    display: &Display,
+    text: &str,
+) -> Result<(), (StatusCode, String)> {
+    if !state.appliance_allows("/api/v1/status/ws") {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Commands are disabled in appliance mode".to_string(),
+        ));
+    }
+    let command: PresetCommand = serde_json::from_str(text)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid command: {e}")))?;
+    send_preset_command(state, display, command).await
+}
+
+/// Build `command` off the async runtime and send it to the render thread.
+async fn send_preset_command(
+    state: &AppState,
+    display: &Display,
+    command: PresetCommand,
+) -> Result<(), (StatusCode, String)> {
+    let sets_brightness = matches!(command, PresetCommand::Brightness(_));
+    let cmd = run_blocking({
+        let state = state.clone();
+        move || command.into_command(&state)
+    })
+    .await?;
+    display.command_tx.send(cmd).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Render thread gone".to_string(),
+        )
+    })?;
+    if sets_brightness {
+        display.schedule_override.store(true, Ordering::Relaxed);
+    }
+
+    Ok(())
+}
+
+// ── WebSocket control ───────────────────────────────────────────────
+
+/// GET /api/v1/control — WebSocket for sending commands with acks.
+///
+/// Each text message is `{"id": .., "command": {..}, "dwell_ms": ..}`,
+/// where `command` is written like a preset's command and `id` (optional,
+/// any JSON value) is echoed back. Every message gets exactly one reply,
+/// `{"id": .., "ok": true}` once the command is queued or
+/// `{"id": .., "ok": false, "error": ".."}`. Messages run in order; with
+/// `dwell_ms`, the next one waits that long, so a client can send a whole
+/// timed sequence at once.
+async fn ws_control(
+    State(state): State<AppState>,
+    Query(target): Query<PanelQuery>,
+    ws: WebSocketUpgrade,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let display = state.display(target.panel)?.clone();
+    Ok(ws.on_upgrade(move |socket| handle_control_socket(socket, state, display)))
+}
+
+async fn handle_control_socket(mut socket: WebSocket, state: AppState, display: Display) {
+    tracing::info!("WebSocket control client connected");
+
+    loop {
+        let text = match socket.recv().await {
+            Some(Ok(Message::Text(text))) => text,
+            Some(Ok(Message::Close(_))) | None => break,
+            Some(Err(e)) => {
+                tracing::warn!("WebSocket receive error: {}", e);
+                break;
+            }
+            Some(Ok(_)) => continue, // Ignore binary, ping/pong handled by axum
+        };
+
+        // Read the id on its own so even a malformed command can be acked
+        let message: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+        let id = message.get("id").cloned().unwrap_or_default();
+        let (reply, dwell_ms) = match run_control_message(&state, &display, message).await {
+            Ok(dwell_ms) => (serde_json::json!({ "id": id, "ok": true }), dwell_ms),
+            Err((_, e)) => (serde_json::json!({ "id": id, "ok": false, "error": e }), 0),
+        };
+        if socket
+            .send(Message::Text(reply.to_string().into()))
+            .await
+            .is_err()
+        {
+            break;
+        }
+
+        if dwell_ms > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(dwell_ms as u64)).await;
+        }
+    }
+
+    tracing::info!("WebSocket control client disconnected");
+}
+
+/// Parse and send one control message, returning its dwell time.
+async fn run_control_message(
+    state: &AppState,
+    display: &Display,
+    message: serde_json::Value,
+) -> Result<u32, (StatusCode, String)> {
+    let message = ControlMessage::<PresetCommand>::parse(message)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    send_preset_command(state, display, message.command).await?;
+    Ok(message.dwell_ms)
 }
 
 // ── WebSocket streaming ─────────────────────────────────────────────
@@ -455,27 +3881,122 @@ async fn post_brightness(
 ///
 /// Connect with a WebSocket client and send binary messages of exactly
 /// rows*cols*3 bytes (RGB24). Each message is rendered as one frame.
+/// A client with a different resolution can declare it with `?w=&h=`;
+/// its frames (w*h*3 bytes) are then scaled to the panel using `filter`.
+/// With `?gamma=&brightness=`, the server corrects each frame through a
+/// lookup table so the client can send raw linear pixels.
 /// Text messages are ignored. The connection sets status to `Streaming`
-/// on connect and back to `Idle` on disconnect.
+/// on connect and back to `Idle` once the last client disconnects.
+/// Clients beyond `--max-stream-clients` on a panel are closed right away
+/// with code 1013 (try again later) and the reason.
+///
+/// Lifetime counters (`total_stream_frames`, `total_stream_bytes`,
+/// `active_stream_clients`) are kept in the shared status, so streaming
+/// usage and leaked connections are visible via `GET /api/v1/status`.
 async fn ws_display_stream(
     State(state): State<AppState>,
+    Query(query): Query<StreamQuery>,
     ws: WebSocketUpgrade,
-) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_stream_socket(socket, state))
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let display = state.display(query.panel)?.clone();
+    let panel_size = (state.panel.cols, state.panel.rows);
+    let source = match (query.w, query.h) {
+        (None, None) => panel_size,
+        (Some(w), Some(h))
+            if (1..=MAX_STREAM_SIDE).contains(&w) && (1..=MAX_STREAM_SIDE).contains(&h) =>
+        {
+            (w, h)
+        }
+        (Some(_), Some(_)) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Stream w and h must be 1-{MAX_STREAM_SIDE}"),
+            ));
+        }
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Stream w and h must be given together".to_string(),
+            ));
+        }
+    };
+
+    let correction = match (query.gamma, query.brightness) {
+        (None, None) => None,
+        (gamma, brightness) => Some(
+            ColorLut::new(gamma.unwrap_or(1.0), brightness.unwrap_or(100))
+                .map_err(|e| (StatusCode::BAD_REQUEST, e))?,
+        ),
+    };
+
+    Ok(ws.on_upgrade(move |socket| {
+        handle_stream_socket(
+            socket,
+            state,
+            display,
+            source,
+            query.filter,
+            query.checksum,
+            correction,
+        )
+    }))
 }
 
-async fn handle_stream_socket(mut socket: WebSocket, state: AppState) {
-    tracing::info!("WebSocket stream client connected");
+async fn handle_stream_socket(
+    mut socket: WebSocket,
+    state: AppState,
+    display: Display,
+    source: (u32, u32),
+    filter: ScaleFilter,
+    checksum: Checksum,
+    correction: Option<ColorLut>,
+) {
+    // Check and claim a slot under one lock, so two clients connecting at
+    // once can't both take the last one
+    let max_clients = state.max_stream_clients;
+    let rejected = {
+        let mut s = display.status.lock().unwrap();
+        if s.active_stream_clients >= max_clients {
+            true
+        } else {
+            s.state = DisplayState::Streaming;
+            s.current_media = Some("websocket".to_string());
+            s.frame = None;
+            s.total_frames = None;
+            s.active_stream_clients += 1;
+            false
+        }
+    };
+    if rejected {
+        tracing::warn!("WebSocket stream client rejected: {max_clients} already connected");
+        let reason = format!(
+            "Panel already has {max_clients} stream client(s); the limit is --max-stream-clients"
+        );
+        let _ = socket
+            .send(Message::Close(Some(CloseFrame {
+                code: close_code::AGAIN,
+                reason: reason.into(),
+            })))
+            .await;
+        return;
+    }
 
-    {
-        let mut s = state.status.lock().unwrap();
-        s.state = DisplayState::Streaming;
-        s.current_media = Some("websocket".to_string());
-        s.frame = None;
-        s.total_frames = None;
+    let panel_size = (state.panel.cols, state.panel.rows);
+    if source == panel_size {
+        tracing::info!("WebSocket stream client connected");
+    } else {
+        tracing::info!(
+            "WebSocket stream client connected ({}x{} frames, scaled to {}x{} with {:?})",
+            source.0,
+            source.1,
+            panel_size.0,
+            panel_size.1,
+            filter
+        );
     }
 
     let mut frame_count: u64 = 0;
+    let mut byte_count: u64 = 0;
 
     while let Some(msg) = socket.recv().await {
         let msg = match msg {
@@ -488,19 +4009,33 @@ async fn handle_stream_socket(mut socket: WebSocket, state: AppState) {
 
         match msg {
             Message::Binary(data) => {
-                let expected = state.panel.frame_byte_count();
-                if data.len() != expected {
-                    tracing::warn!(
-                        "WebSocket frame: expected {} bytes, got {}",
-                        expected,
-                        data.len()
-                    );
-                    continue;
+                let frame = match checksum.verify(&data) {
+                    Ok(frame) => frame,
+                    Err(e) => {
+                        tracing::warn!("WebSocket frame dropped: {}", e);
+                        display.status.lock().unwrap().checksum_failures += 1;
+                        continue;
+                    }
+                };
+                let mut frame =
+                    match layout::resize_raw_frame(frame.to_vec(), source, panel_size, filter) {
+                        Ok(frame) => frame,
+                        Err(e) => {
+                            tracing::warn!(
+                                "WebSocket frame: {}{}",
+                                e,
+                                frame::size_hint(frame.len())
+                            );
+                            continue;
+                        }
+                    };
+                if let Some(lut) = &correction {
+                    lut.apply(&mut frame);
                 }
 
-                if state
+                if display
                     .command_tx
-                    .send(RenderCommand::ShowFrame(data.to_vec()))
+                    .send(RenderCommand::ShowFrame(frame))
                     .is_err()
                 {
                     tracing::error!("Render thread gone, closing WebSocket");
@@ -508,6 +4043,11 @@ async fn handle_stream_socket(mut socket: WebSocket, state: AppState) {
                 }
 
                 frame_count += 1;
+                byte_count += data.len() as u64;
+
+                let mut s = display.status.lock().unwrap();
+                s.total_stream_frames += 1;
+                s.total_stream_bytes += data.len() as u64;
             }
             Message::Close(_) => break,
             _ => {} // Ignore text, ping/pong handled by axum
@@ -515,10 +4055,16 @@ async fn handle_stream_socket(mut socket: WebSocket, state: AppState) {
     }
 
     tracing::info!(
-        "WebSocket stream client disconnected ({} frames received)",
-        frame_count
+        "WebSocket stream client disconnected ({} frames, {} bytes received)",
+        frame_count,
+        byte_count
     );
-    state.status.lock().unwrap().set_idle();
+
+    let mut s = display.status.lock().unwrap();
+    s.active_stream_clients = s.active_stream_clients.saturating_sub(1);
+    if s.active_stream_clients == 0 {
+        s.set_idle();
+    }
 }
 
 // ── Path validation ──────────────────────────────────────────────────
@@ -529,7 +4075,7 @@ async fn handle_stream_socket(mut socket: WebSocket, state: AppState) {
 /// We canonicalize both paths and check that the requested path starts
 /// with the media directory prefix.
 fn validate_media_path(
-    media_dir: &PathBuf,
+    media_dir: &std::path::Path,
     relative_path: &str,
 ) -> Result<PathBuf, (StatusCode, String)> {
     let full_path = media_dir.join(relative_path);
@@ -558,3 +4104,754 @@ fn validate_media_path(
 
     Ok(canonical)
 }
+
+/// Validate a destination path for a file the server will write.
+///
+/// Unlike `validate_media_path`, the file doesn't need to exist yet. The
+/// path must be relative with no `..` components, end in `extension`, and
+/// its parent directory must resolve to somewhere inside the media directory.
+/// An existing symlink at the destination is rejected so a write can't be
+/// redirected outside the media directory.
+fn validate_media_write_path(
+    media_dir: &std::path::Path,
+    relative_path: &str,
+    extension: &str,
+) -> Result<PathBuf, (StatusCode, String)> {
+    let relative = std::path::Path::new(relative_path);
+
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Path must be relative to the media directory without '..'".to_string(),
+        ));
+    }
+
+    let has_extension = relative
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case(extension));
+    let file_name = relative.file_name().filter(|_| has_extension).ok_or((
+        StatusCode::BAD_REQUEST,
+        format!("Path must name a .{extension} file"),
+    ))?;
+
+    let parent = media_dir.join(relative.parent().unwrap_or(std::path::Path::new("")));
+    let canonical_parent = parent.canonicalize().map_err(|_| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("Directory not found for: {relative_path}"),
+        )
+    })?;
+
+    let canonical_media = media_dir.canonicalize().map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Media directory not found".to_string(),
+        )
+    })?;
+
+    if !canonical_parent.starts_with(&canonical_media) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Path is outside the media directory".to_string(),
+        ));
+    }
+
+    let destination = canonical_parent.join(file_name);
+    if destination
+        .symlink_metadata()
+        .is_ok_and(|m| m.file_type().is_symlink())
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Destination is a symlink".to_string(),
+        ));
+    }
+
+    Ok(destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::queue::{self, CountingReceiver};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use tower::ServiceExt;
+
+    /// State for one panel serving `media_dir`, and that panel's command
+    /// queue.
+    fn test_state(media_dir: &std::path::Path) -> (AppState, CountingReceiver<RenderCommand>) {
+        let (command_tx, rx) = queue::counting_channel();
+        let display = Display {
+            command_tx,
+            status: Arc::default(),
+            schedule_override: Arc::default(),
+            diagnostics: Arc::default(),
+            started_at: std::time::Instant::now(),
+        };
+        let presets = PresetStore::load(media_dir.join("presets.json")).unwrap();
+        let settings = RuntimeSettings {
+            max_brightness: 100,
+            max_refresh_fps: None,
+        };
+        let config = EffectiveConfig {
+            rows: 32,
+            cols: 64,
+            safe_inset: 0,
+            hardware_mappings: vec![crate::DEFAULT_HARDWARE_MAPPING.to_string()],
+            pwm_bits: crate::PWM_BITS,
+            gpio_slowdown: crate::GPIO_SLOWDOWN,
+            canvas: "double-buffered".to_string(),
+            color_order: "rgb".to_string(),
+            symmetry: "none".to_string(),
+            pixel_map: None,
+            hardware_brightness: 100,
+            brightness_mode: "software".to_string(),
+            max_brightness: settings.max_brightness,
+            max_refresh_fps: settings.max_refresh_fps,
+            brightness_schedule_points: 0,
+            light_sensor: false,
+            media_dir: media_dir.display().to_string(),
+            fonts_dir: media_dir.join("fonts/bdf").display().to_string(),
+            presets_file: media_dir.join("presets.json").display().to_string(),
+            settings_file: None,
+            default_font: crate::bdf::FALLBACK_FONT.to_string(),
+            default_text_color: Color::new(255, 255, 255),
+            power_off_after_secs: None,
+            min_display_ms: 0,
+            skip_duplicate_frames: false,
+            stop_clears: false,
+            video_finish_action: VideoFinish::default(),
+            max_stream_clients: 1,
+            max_body_bytes: 1 << 20,
+            appliance: false,
+            tls: false,
+            port: 8080,
+        };
+        let state = AppState {
+            displays: Arc::new(BTreeMap::from([(0, display)])),
+            media_dir: media_dir.to_path_buf(),
+            fonts_dir: media_dir.join("fonts/bdf"),
+            panel: PanelConfig::new(32, 64),
+            brightness_mode: BrightnessMode::default(),
+            max_body_bytes: 1 << 20,
+            stop_clears: false,
+            video_finish: VideoFinish::default(),
+            max_stream_clients: 1,
+            presets: Arc::new(Mutex::new(presets)),
+            brightness_schedule: None,
+            light_sensor: false,
+            default_font: crate::bdf::FALLBACK_FONT.to_string(),
+            default_text_color: Color::new(255, 255, 255),
+            #[cfg(feature = "remote-images")]
+            image_url_hosts: Vec::new(),
+            appliance: None,
+            limits: Arc::new(Limits::new(settings)),
+            config: Arc::new(config),
+        };
+        (state, rx)
+    }
+
+    /// Serve the full router for `state` on a free local port.
+    async fn serve(state: AppState) -> std::net::SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = create_router(state, "http", 0);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        addr
+    }
+
+    /// Wait up to a second for `done` to hold for `state`'s panel status.
+    async fn wait_for_status(state: &AppState, done: impl Fn(&DisplayStatus) -> bool) {
+        for _ in 0..100 {
+            if done(&state.displays[&0].status.lock().unwrap()) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("status never changed: {:?}", state.displays[&0].status());
+    }
+
+    /// Body size of a long `GET /api/v1/images` listing, requested with
+    /// `accept_encoding`.
+    async fn listing_size(accept_encoding: Option<&str>) -> (usize, Option<String>) {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir(tmp.path().join("images")).unwrap();
+        for i in 0..1000 {
+            std::fs::write(tmp.path().join(format!("images/photo_{i:04}.png")), b"").unwrap();
+        }
+        let (state, _rx) = test_state(tmp.path());
+        let app = create_router(state, "http", 0);
+
+        let mut request = Request::builder().uri("/api/v1/images");
+        if let Some(encoding) = accept_encoding {
+            request = request.header(header::ACCEPT_ENCODING, encoding);
+        }
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let encoding = response
+            .headers()
+            .get(header::CONTENT_ENCODING)
+            .map(|value| value.to_str().unwrap().to_string());
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (body.len(), encoding)
+    }
+
+    #[test]
+    fn spec_lists_the_served_address() {
+        let api = openapi_served_at("http", &["pi", "localhost"], 8123);
+        let urls: Vec<_> = api
+            .servers
+            .unwrap()
+            .into_iter()
+            .map(|server| server.url)
+            .collect();
+        assert_eq!(urls, ["/", "http://pi:8123", "http://localhost:8123"]);
+
+        // Only the page's own origin when the port isn't known
+        let api = openapi_served_at("http", &["pi"], 0);
+        let urls: Vec<_> = api
+            .servers
+            .unwrap()
+            .into_iter()
+            .map(|server| server.url)
+            .collect();
+        assert_eq!(urls, ["/"]);
+    }
+
+    #[tokio::test]
+    async fn video_without_frames_is_a_bad_request() {
+        let media = TempDir::new().unwrap();
+        std::fs::create_dir_all(media.path().join("videos/empty")).unwrap();
+        std::fs::write(media.path().join("videos/empty/notes.txt"), "").unwrap();
+        let (state, rx) = test_state(media.path());
+
+        let (status, message) = post_display_video(
+            State(state),
+            Query(PanelQuery { panel: 0 }),
+            Json(VideoRequest::new("videos/empty")),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(
+            message.contains("videos/empty has no video frames"),
+            "{message}"
+        );
+        assert!(
+            rx.try_recv().is_err(),
+            "nothing should reach the render thread"
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_sized_frame_is_a_json_error() {
+        let media = TempDir::new().unwrap();
+        let (state, rx) = test_state(media.path());
+
+        let response = post_display_frame(
+            State(state),
+            Query(PanelQuery { panel: 0 }),
+            Query(FrameQuery {
+                checksum: Checksum::None,
+                format: PixelFormat::Rgb,
+            }),
+            Bytes::from(vec![0; 100]),
+        )
+        .await
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: FrameSizeError = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            (
+                error.expected_bytes,
+                error.got_bytes,
+                error.rows,
+                error.cols
+            ),
+            (32 * 64 * 3, 100, 32, 64)
+        );
+        assert!(
+            error.error.starts_with("Expected 6144 bytes"),
+            "{}",
+            error.error
+        );
+        assert!(
+            rx.try_recv().is_err(),
+            "nothing should reach the render thread"
+        );
+    }
+
+    #[tokio::test]
+    async fn rgba_frames_are_sized_and_converted_to_rgb() {
+        let media = TempDir::new().unwrap();
+        let (state, rx) = test_state(media.path());
+        let rgba = |len| Bytes::from([255, 255, 255, 0].repeat(len));
+        let query = || {
+            Query(FrameQuery {
+                checksum: Checksum::None,
+                format: PixelFormat::Rgba,
+            })
+        };
+
+        // An RGB-sized body is too short for RGBA
+        let rgb_sized = Bytes::from(vec![0; 32 * 64 * 3]);
+        let err = post_display_frame(
+            State(state.clone()),
+            Query(PanelQuery { panel: 0 }),
+            query(),
+            rgb_sized,
+        )
+        .await
+        .unwrap_err();
+        let ApiError::FrameSize(err) = err else {
+            panic!("expected a frame size error, got {err:?}");
+        };
+        assert!(
+            err.error
+                .starts_with("Expected 8192 bytes (64x32x4 RGBA), got 6144 bytes"),
+            "{}",
+            err.error
+        );
+
+        post_display_frame(
+            State(state),
+            Query(PanelQuery { panel: 0 }),
+            query(),
+            rgba(32 * 64),
+        )
+        .await
+        .unwrap();
+        // Transparent white over black is black
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            RenderCommand::ShowFrame(data) if data == vec![0; 32 * 64 * 3]
+        ));
+    }
+
+    #[tokio::test]
+    async fn status_counts_dispatched_commands() {
+        let media = TempDir::new().unwrap();
+        let (state, rx) = test_state(media.path());
+        for enabled in [true, false] {
+            post_display_invert(
+                State(state.clone()),
+                Query(PanelQuery { panel: 0 }),
+                Json(InvertRequest { enabled }),
+            )
+            .await
+            .unwrap();
+        }
+
+        let Json(status) = get_status(State(state.clone()), Query(PanelQuery { panel: 0 }))
+            .await
+            .unwrap();
+        assert_eq!((status.commands_sent, status.commands_processed), (2, 0));
+
+        // What the render thread does for each command it takes
+        rx.recv().unwrap();
+        rx.recv().unwrap();
+        let Json(status) = get_status(State(state), Query(PanelQuery { panel: 0 }))
+            .await
+            .unwrap();
+        assert_eq!((status.commands_sent, status.commands_processed), (2, 2));
+        assert_eq!(status.uptime_secs, 0);
+    }
+
+    #[tokio::test]
+    async fn config_reports_the_panel_and_current_caps() {
+        let media = TempDir::new().unwrap();
+        let (state, _rx) = test_state(media.path());
+        state.limits.set(RuntimeSettings {
+            max_brightness: 60,
+            max_refresh_fps: Some(30),
+        });
+
+        let Json(config) = get_config(State(state.clone())).await;
+        assert_eq!(
+            (config.rows, config.cols),
+            (state.panel.rows, state.panel.cols)
+        );
+        assert_eq!(
+            (config.pwm_bits, config.gpio_slowdown),
+            (crate::PWM_BITS, crate::GPIO_SLOWDOWN)
+        );
+        // A reloaded settings file shows up without a restart
+        assert_eq!(
+            (config.max_brightness, config.max_refresh_fps),
+            (60, Some(30))
+        );
+
+        // Nothing secret, such as the TLS key, is reported
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(
+            !json
+                .as_object()
+                .unwrap()
+                .keys()
+                .any(|key| key.contains("key")),
+            "{json}"
+        );
+    }
+
+    #[rstest::rstest]
+    #[case::parent_dir("../capture.png")]
+    #[case::nested_parent_dir("images/../../capture.png")]
+    #[case::absolute("/tmp/capture.png")]
+    fn snapshot_path_must_stay_in_the_media_dir(#[case] path: &str) {
+        let media = TempDir::new().unwrap();
+        std::fs::create_dir(media.path().join("images")).unwrap();
+        let (status, _) = validate_media_write_path(media.path(), path, "png").unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn save_returns_the_new_media_entry() {
+        let media = TempDir::new().unwrap();
+        std::fs::create_dir(media.path().join("images")).unwrap();
+        let (state, rx) = test_state(media.path());
+        // What the render thread does with the command
+        let render = std::thread::spawn(move || match rx.recv().unwrap() {
+            RenderCommand::SaveSnapshot { path, reply } => {
+                RgbImage::new(4, 2).save(&path).unwrap();
+                reply.send(Ok(())).unwrap();
+            }
+            other => panic!("unexpected {}", other.name()),
+        });
+
+        let Json(entry) = post_display_save(
+            State(state),
+            Query(PanelQuery { panel: 0 }),
+            Json(SaveRequest {
+                path: "./images/capture.png".to_string(),
+            }),
+        )
+        .await
+        .unwrap();
+        render.join().unwrap();
+        assert_eq!(entry.name, "capture.png");
+        assert_eq!(entry.path, "images/capture.png");
+        assert!(entry.size > 0);
+    }
+
+    #[rstest::rstest]
+    #[case::past_the_left_edge(Some(-1000), None)]
+    #[case::below_the_panel(None, Some(32))]
+    #[case::above_the_panel(Some(0), Some(-100))]
+    fn text_that_would_never_show_is_rejected(#[case] x: Option<i32>, #[case] y: Option<i32>) {
+        let media = TempDir::new().unwrap();
+        let (state, _rx) = test_state(media.path());
+        let req = TextRequest {
+            x,
+            y,
+            ..TextRequest::new("Hello!")
+        };
+        let Err((status, message)) = req.into_command(&state) else {
+            panic!("accepted text that can't be seen");
+        };
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(message.contains("never be visible"), "{message}");
+    }
+
+    #[rstest::rstest]
+    #[case::unknown("no-such-font")]
+    #[case::outside_the_fonts_dir("../secrets")]
+    fn text_in_a_missing_font_is_refused(#[case] font: &str) {
+        let media = TempDir::new().unwrap();
+        let (state, _rx) = test_state(media.path());
+        let req = TextRequest {
+            font: Some(font.to_string()),
+            x: Some(0),
+            ..TextRequest::new("Hello!")
+        };
+        let Err((status, message)) = req.into_command(&state) else {
+            panic!("accepted a font that doesn't exist");
+        };
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(message.contains(font), "{message}");
+    }
+
+    #[test]
+    fn text_partly_off_the_edges_is_accepted() {
+        let media = TempDir::new().unwrap();
+        let (state, _rx) = test_state(media.path());
+        let req = TextRequest {
+            x: Some(-4),
+            y: Some(-2),
+            ..TextRequest::new("Hello!")
+        };
+        assert!(req.into_command(&state).is_ok());
+    }
+
+    #[tokio::test]
+    async fn loop_sends_an_in_place_adjustment() {
+        let media = TempDir::new().unwrap();
+        let (state, rx) = test_state(media.path());
+
+        let status = post_display_loop(
+            State(state),
+            Query(PanelQuery { panel: 0 }),
+            Json(LoopRequest { enabled: false }),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(status, StatusCode::OK);
+        let cmd = rx.try_recv().unwrap();
+        assert!(matches!(cmd, RenderCommand::SetLoop(false)));
+        // Handled in place, so a playing video isn't interrupted
+        assert!(cmd.is_adjustment());
+    }
+
+    #[tokio::test]
+    async fn formats_lists_the_media_extensions() {
+        let Json(formats) = get_formats().await;
+        assert_eq!(
+            serde_json::to_value(formats).unwrap(),
+            serde_json::json!({
+                "images": media::IMAGE_EXTENSIONS,
+                "videos": media::VIDEO_FRAME_EXTENSIONS,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn json_listings_are_compressed_when_the_client_accepts_it() {
+        let (plain, plain_encoding) = listing_size(None).await;
+        let (gzipped, encoding) = listing_size(Some("gzip")).await;
+
+        assert_eq!(plain_encoding, None);
+        assert_eq!(encoding.as_deref(), Some("gzip"));
+        assert!(
+            gzipped * 4 < plain,
+            "{gzipped} bytes gzipped vs {plain} plain"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn frames_and_websockets_are_not_compressed() {
+        use futures_util::SinkExt;
+        use tokio_tungstenite::tungstenite::{self, client::IntoClientRequest};
+
+        let tmp = TempDir::new().unwrap();
+        let (state, rx) = test_state(tmp.path());
+        let frame: Vec<u8> = (0..64 * 32 * 3).map(|i| (i % 251) as u8).collect();
+
+        // An uploaded frame reaches the render thread as sent
+        let response = create_router(state.clone(), "http", 0)
+            .oneshot(
+                Request::post("/api/v1/display/frame")
+                    .header(header::CONTENT_TYPE, "application/octet-stream")
+                    .header(header::ACCEPT_ENCODING, "gzip")
+                    .body(Body::from(frame.clone()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+        match rx.recv_timeout(Duration::from_secs(1)).unwrap() {
+            RenderCommand::ShowFrame(shown) => assert_eq!(shown, frame),
+            other => panic!("expected a frame, got {}", other.name()),
+        }
+
+        // The stream upgrade isn't compressed, and frames still get through
+        let addr = serve(state).await;
+        let mut request = format!("ws://{addr}/api/v1/display/stream")
+            .into_client_request()
+            .unwrap();
+        request
+            .headers_mut()
+            .insert(header::ACCEPT_ENCODING, "gzip".parse().unwrap());
+        let (mut ws, response) = tokio_tungstenite::connect_async(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+        ws.send(tungstenite::Message::binary(frame.clone()))
+            .await
+            .unwrap();
+        match rx.recv_timeout(Duration::from_secs(1)).unwrap() {
+            RenderCommand::ShowFrame(shown) => assert_eq!(shown, frame),
+            other => panic!("expected a frame, got {}", other.name()),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn stream_counters_follow_the_websocket() {
+        use futures_util::SinkExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let tmp = TempDir::new().unwrap();
+        let (state, rx) = test_state(tmp.path());
+        let addr = serve(state.clone()).await;
+        let frame = vec![7u8; 64 * 32 * 3];
+
+        let (mut ws, _) =
+            tokio_tungstenite::connect_async(format!("ws://{addr}/api/v1/display/stream"))
+                .await
+                .unwrap();
+        wait_for_status(&state, |s| s.active_stream_clients == 1).await;
+        assert_eq!(state.displays[&0].status().state, DisplayState::Streaming);
+
+        ws.send(Message::binary(frame.clone())).await.unwrap();
+        ws.send(Message::binary(frame.clone())).await.unwrap();
+        // Ignored, and not counted
+        ws.send(Message::text("hello")).await.unwrap();
+        wait_for_status(&state, |s| s.total_stream_frames == 2).await;
+        assert_eq!(
+            state.displays[&0].status().total_stream_bytes,
+            2 * frame.len() as u64
+        );
+        assert!(rx.try_recv().is_ok() && rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_err());
+
+        ws.close(None).await.unwrap();
+        wait_for_status(&state, |s| s.active_stream_clients == 0).await;
+        let status = state.displays[&0].status();
+        assert_eq!(status.state, DisplayState::Idle);
+        assert_eq!(status.total_stream_frames, 2);
+    }
+
+    #[tokio::test]
+    async fn bodies_over_the_limit_are_refused() {
+        let tmp = TempDir::new().unwrap();
+        let (mut state, rx) = test_state(tmp.path());
+        state.max_body_bytes = 1024;
+        let app = create_router(state, "http", 0);
+
+        let text = serde_json::json!({ "text": "x".repeat(2000) }).to_string();
+        for (uri, content_type, body) in [
+            (
+                "/api/v1/display/frame",
+                "application/octet-stream",
+                vec![0; 2048],
+            ),
+            (
+                "/api/v1/display/text",
+                "application/json",
+                text.into_bytes(),
+            ),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::post(uri)
+                        .header(header::CONTENT_TYPE, content_type)
+                        .body(Body::from(body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE, "{uri}");
+        }
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[rstest::rstest]
+    #[case(false, None, false)]
+    #[case(true, None, true)]
+    #[case(true, Some(false), false)]
+    #[case(false, Some(true), true)]
+    fn stop_clears_unless_the_request_says(
+        #[case] stop_clears: bool,
+        #[case] clear: Option<bool>,
+        #[case] expected: bool,
+    ) {
+        let tmp = TempDir::new().unwrap();
+        let (mut state, _rx) = test_state(tmp.path());
+        state.stop_clears = stop_clears;
+        match (StopRequest { clear }).into_command(&state).unwrap() {
+            RenderCommand::Stop { clear } => assert_eq!(clear, expected),
+            other => panic!("expected stop, got {}", other.name()),
+        }
+    }
+
+    #[tokio::test]
+    async fn tracked_commands_resolve_once_run_or_dropped() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let tmp = TempDir::new().unwrap();
+        let (state, rx) = test_state(tmp.path());
+        let ran = Arc::new(AtomicBool::new(false));
+        // What the render thread does: run the first, fail the second
+        let render = std::thread::spawn({
+            let ran = ran.clone();
+            move || {
+                for run in [true, false] {
+                    match rx.recv().unwrap() {
+                        RenderCommand::Tracked { cmd, done } => {
+                            assert_eq!(cmd.name(), "identify");
+                            if run {
+                                std::thread::sleep(Duration::from_millis(50));
+                                ran.store(true, Ordering::SeqCst);
+                                done.send(()).unwrap();
+                            }
+                        }
+                        other => panic!("unexpected {}", other.name()),
+                    }
+                }
+            }
+        });
+
+        let display = &state.displays[&0];
+        display.flash("A").await.unwrap();
+        assert!(ran.load(Ordering::SeqCst));
+        assert!(display.flash("B").await.is_err());
+        render.join().unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn control_socket_acks_each_message_in_order_after_its_dwell() {
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let tmp = TempDir::new().unwrap();
+        let (state, rx) = test_state(tmp.path());
+        let addr = serve(state).await;
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/api/v1/control"))
+            .await
+            .unwrap();
+
+        // Sent all at once; the second waits out the first's dwell
+        let sent_at = std::time::Instant::now();
+        for message in [
+            r#"{"id": 1, "command": {"type": "invert", "enabled": true}, "dwell_ms": 200}"#,
+            r#"{"id": "two", "command": {"type": "stop", "clear": true}}"#,
+            r#"{"id": 3, "command": {"type": "nope"}}"#,
+        ] {
+            ws.send(Message::text(message)).await.unwrap();
+        }
+        let mut replies = Vec::new();
+        for _ in 0..3 {
+            let reply = ws.next().await.unwrap().unwrap().into_text().unwrap();
+            let reply: serde_json::Value = serde_json::from_str(&reply).unwrap();
+            replies.push((reply, sent_at.elapsed()));
+        }
+
+        assert_eq!(replies[0].0, serde_json::json!({ "id": 1, "ok": true }));
+        assert_eq!(replies[1].0, serde_json::json!({ "id": "two", "ok": true }));
+        assert!(replies[0].1 < Duration::from_millis(200));
+        assert!(replies[1].1 >= Duration::from_millis(200));
+        assert_eq!(replies[2].0["id"], 3);
+        assert_eq!(replies[2].0["ok"], false);
+        assert!(replies[2].0["error"].is_string());
+
+        assert!(matches!(rx.try_recv(), Ok(RenderCommand::SetInvert(true))));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(RenderCommand::Stop { clear: true })
+        ));
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,224 @@
+//! Settings reloaded from `--settings-file` on SIGHUP, without a restart.
+//!
+//! Restarting the server blanks the panel and drops every stream, so the
+//! settings that are only limits — the brightness cap and the refresh cap —
+//! can also come from a small JSON file that is read at startup and again
+//! whenever the process gets SIGHUP:
+//!
+//! ```json
+//! { "max_brightness": 60, "max_refresh_fps": 30 }
+//! ```
+//!
+//! A key left out of the file falls back to its command-line flag, and a
+//! `max_refresh_fps` of 0 lifts the cap. Any other key is a setting that
+//! only a restart can change; it's reported and ignored. The current values
+//! live in `Limits`, shared with the render threads, which read them on
+//! every use.
+//!
+//! ## Rust concepts
+//! - `AtomicU8`/`AtomicU32` for values one thread writes and others read,
+//!   without a lock
+//! - Parsing into `serde_json::Map` first, to find keys the struct doesn't know
+
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicU8, AtomicU32, Ordering};
+
+/// Keys the settings file may contain.
+pub const RELOADABLE: [&str; 2] = ["max_brightness", "max_refresh_fps"];
+
+/// The settings that can change while the server runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RuntimeSettings {
+    /// Cap on software and hardware brightness, 0-100
+    pub max_brightness: u8,
+    /// Most swaps per second; `None` for no cap
+    pub max_refresh_fps: Option<u32>,
+}
+
+impl RuntimeSettings {
+    /// One line per setting that differs in `new`, e.g.
+    /// `max_brightness: 100 -> 60`.
+    pub fn changes(&self, new: &RuntimeSettings) -> Vec<String> {
+        let fps = |fps: Option<u32>| fps.map_or("unlimited".to_string(), |fps| fps.to_string());
+        let mut changes = Vec::new();
+        if self.max_brightness != new.max_brightness {
+            changes.push(format!(
+                "max_brightness: {} -> {}",
+                self.max_brightness, new.max_brightness
+            ));
+        }
+        if self.max_refresh_fps != new.max_refresh_fps {
+            changes.push(format!(
+                "max_refresh_fps: {} -> {}",
+                fps(self.max_refresh_fps),
+                fps(new.max_refresh_fps)
+            ));
+        }
+        changes
+    }
+}
+
+/// What a settings file sets; `None` leaves the command-line value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct SettingsFile {
+    pub max_brightness: Option<u8>,
+    /// 0 for no cap
+    pub max_refresh_fps: Option<u32>,
+}
+
+impl SettingsFile {
+    /// Parse the file's JSON, also returning the keys that can't be
+    /// reloaded (sorted).
+    pub fn parse(json: &str) -> Result<(Self, Vec<String>), String> {
+        let map: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(json).map_err(|e| format!("Invalid settings JSON: {e}"))?;
+        let restart_only = map
+            .keys()
+            .filter(|key| !RELOADABLE.contains(&key.as_str()))
+            .cloned()
+            .collect();
+        let known = map
+            .into_iter()
+            .filter(|(key, _)| RELOADABLE.contains(&key.as_str()))
+            .collect();
+        let file: Self = serde_json::from_value(serde_json::Value::Object(known))
+            .map_err(|e| format!("Invalid settings: {e}"))?;
+        if let Some(max) = file.max_brightness
+            && max > 100
+        {
+            return Err(format!("max_brightness must be 0-100, got {max}"));
+        }
+        Ok((file, restart_only))
+    }
+
+    /// Read and parse the file at `path`.
+    pub fn load(path: &Path) -> Result<(Self, Vec<String>), String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        Self::parse(&json)
+    }
+
+    /// The settings in effect: the file's values over `flags`.
+    pub fn resolve(&self, flags: RuntimeSettings) -> RuntimeSettings {
+        RuntimeSettings {
+            max_brightness: self.max_brightness.unwrap_or(flags.max_brightness),
+            max_refresh_fps: match self.max_refresh_fps {
+                Some(0) => None,
+                Some(fps) => Some(fps),
+                None => flags.max_refresh_fps,
+            },
+        }
+    }
+}
+
+/// The settings in effect, shared between the reload task and the render
+/// threads.
+#[derive(Debug)]
+pub struct Limits {
+    max_brightness: AtomicU8,
+    /// 0 for no cap
+    max_refresh_fps: AtomicU32,
+}
+
+impl Limits {
+    pub fn new(settings: RuntimeSettings) -> Self {
+        Self {
+            max_brightness: AtomicU8::new(settings.max_brightness),
+            max_refresh_fps: AtomicU32::new(settings.max_refresh_fps.unwrap_or(0)),
+        }
+    }
+
+    pub fn max_brightness(&self) -> u8 {
+        self.max_brightness.load(Ordering::Relaxed)
+    }
+
+    pub fn max_refresh_fps(&self) -> Option<u32> {
+        Some(self.max_refresh_fps.load(Ordering::Relaxed)).filter(|&fps| fps > 0)
+    }
+
+    pub fn get(&self) -> RuntimeSettings {
+        RuntimeSettings {
+            max_brightness: self.max_brightness(),
+            max_refresh_fps: self.max_refresh_fps(),
+        }
+    }
+
+    pub fn set(&self, settings: RuntimeSettings) {
+        self.max_brightness
+            .store(settings.max_brightness, Ordering::Relaxed);
+        self.max_refresh_fps
+            .store(settings.max_refresh_fps.unwrap_or(0), Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const FLAGS: RuntimeSettings = RuntimeSettings {
+        max_brightness: 100,
+        max_refresh_fps: Some(60),
+    };
+
+    #[test]
+    fn file_values_override_the_flags() {
+        let (file, restart_only) = SettingsFile::parse(r#"{ "max_brightness": 40 }"#).unwrap();
+        assert!(restart_only.is_empty());
+        assert_eq!(
+            file.resolve(FLAGS),
+            RuntimeSettings {
+                max_brightness: 40,
+                max_refresh_fps: Some(60),
+            }
+        );
+
+        // Zero lifts the refresh cap
+        let (file, _) = SettingsFile::parse(r#"{ "max_refresh_fps": 0 }"#).unwrap();
+        assert_eq!(file.resolve(FLAGS).max_refresh_fps, None);
+    }
+
+    #[test]
+    fn other_keys_are_reported_as_needing_a_restart() {
+        let (file, restart_only) =
+            SettingsFile::parse(r#"{ "port": 9000, "max_brightness": 50, "cols": 64 }"#).unwrap();
+        assert_eq!(file.max_brightness, Some(50));
+        assert_eq!(restart_only, ["cols", "port"]);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert!(SettingsFile::parse(r#"{ "max_brightness": 101 }"#).is_err());
+        assert!(SettingsFile::parse(r#"{ "max_refresh_fps": "fast" }"#).is_err());
+        assert!(SettingsFile::parse("[]").is_err());
+    }
+
+    #[test]
+    fn lists_what_changed() {
+        let new = RuntimeSettings {
+            max_brightness: 60,
+            max_refresh_fps: None,
+        };
+        assert_eq!(
+            FLAGS.changes(&new),
+            [
+                "max_brightness: 100 -> 60",
+                "max_refresh_fps: 60 -> unlimited"
+            ]
+        );
+        assert!(new.changes(&new).is_empty());
+    }
+
+    #[test]
+    fn limits_round_trip() {
+        let limits = Limits::new(FLAGS);
+        assert_eq!(limits.get(), FLAGS);
+        let new = RuntimeSettings {
+            max_brightness: 20,
+            max_refresh_fps: None,
+        };
+        limits.set(new);
+        assert_eq!(limits.get(), new);
+    }
+}
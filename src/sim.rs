@@ -0,0 +1,282 @@
+//! Simulations that run entirely on the panel: Conway's Game of Life and a
+//! bouncing ball (`POST /api/v1/display/effect`).
+//!
+//! Both are plain state stepped by the render thread and drawn into a
+//! frame, so the rules can be tested without a panel. The Life grid wraps
+//! around at the edges, so gliders fly off one side and back in the other.
+//! A board that dies out or settles into a still life or a blinker would
+//! sit there forever, so the render thread seeds a fresh one instead.
+//!
+//! ## Rust concepts
+//! - `rem_euclid` to wrap negative neighbor coordinates around the grid
+//! - A tiny xorshift generator instead of pulling in a random number crate
+
+use crate::Color;
+use image::{Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Which simulation `POST /api/v1/display/effect` runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectKind {
+    /// Conway's Game of Life from a random board, `speed` generations per
+    /// second
+    GameOfLife,
+    /// A ball bouncing off the edges at `speed` pixels per second
+    Bounce,
+}
+
+impl EffectKind {
+    /// Name shown as the current media in the status.
+    pub fn label(self) -> &'static str {
+        match self {
+            EffectKind::GameOfLife => "game of life",
+            EffectKind::Bounce => "bounce",
+        }
+    }
+}
+
+// ── Game of Life ─────────────────────────────────────────────────────
+
+/// Fraction of cells alive on a random board, out of 256.
+const LIFE_DENSITY: u64 = 80;
+
+/// A Game of Life board, wrapping around at the edges.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Life {
+    width: u32,
+    height: u32,
+    /// Indexed by `y * width + x`
+    cells: Vec<bool>,
+}
+
+impl Life {
+    /// A board with only the cells at `alive` (as `(x, y)`) alive.
+    pub fn from_cells(width: u32, height: u32, alive: &[(u32, u32)]) -> Self {
+        let mut cells = vec![false; (width * height) as usize];
+        for &(x, y) in alive {
+            cells[(y * width + x) as usize] = true;
+        }
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// A board with about a third of its cells alive, the same for the
+    /// same `seed`.
+    pub fn random(width: u32, height: u32, seed: u64) -> Self {
+        // Spread nearby seeds apart; xorshift needs a non-zero state
+        let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+        let cells = (0..width * height)
+            .map(|_| xorshift(&mut state) % 256 < LIFE_DENSITY)
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn alive(&self, x: u32, y: u32) -> bool {
+        self.cells[(y * self.width + x) as usize]
+    }
+
+    /// Number of live cells.
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|&&alive| alive).count()
+    }
+
+    /// The next generation: a live cell with two or three live neighbors
+    /// lives on, and a dead one with exactly three comes alive.
+    pub fn step(&self) -> Self {
+        let cells = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let neighbors = self.live_neighbors(x, y);
+                neighbors == 3 || (neighbors == 2 && self.alive(x, y))
+            })
+            .collect();
+        Self {
+            width: self.width,
+            height: self.height,
+            cells,
+        }
+    }
+
+    fn live_neighbors(&self, x: u32, y: u32) -> usize {
+        let (w, h) = (self.width as i64, self.height as i64);
+        [
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ]
+        .iter()
+        .filter(|&&(dx, dy)| {
+            let nx = (x as i64 + dx).rem_euclid(w) as u32;
+            let ny = (y as i64 + dy).rem_euclid(h) as u32;
+            self.alive(nx, ny)
+        })
+        .count()
+    }
+
+    /// Live cells in `color` on black.
+    pub fn frame(&self, color: Color) -> RgbImage {
+        RgbImage::from_fn(self.width, self.height, |x, y| {
+            if self.alive(x, y) {
+                Rgb::from(color)
+            } else {
+                Rgb([0, 0, 0])
+            }
+        })
+    }
+}
+
+/// Next number from a xorshift64 generator; `state` must not be zero.
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+// ── Bouncing ball ────────────────────────────────────────────────────
+
+/// A ball moving in a straight line and bouncing off the panel edges.
+#[derive(Clone, Debug)]
+pub struct Bounce {
+    width: f32,
+    height: f32,
+    radius: f32,
+    /// Center of the ball, in pixels
+    x: f32,
+    y: f32,
+    /// Velocity in pixels per second
+    vx: f32,
+    vy: f32,
+}
+
+impl Bounce {
+    /// A ball an eighth of the panel's smaller side across, starting in the
+    /// middle and heading down and to the right at `speed` pixels per second.
+    pub fn new(width: u32, height: u32, speed: u32) -> Self {
+        let radius = (width.min(height) as f32 / 16.0).max(1.0);
+        let speed = speed as f32;
+        Self {
+            width: width as f32,
+            height: height as f32,
+            radius,
+            x: width as f32 / 2.0,
+            y: height as f32 / 2.0,
+            // A 3-4-5 triangle, so it doesn't retrace its path right away
+            vx: speed * 4.0 / 5.0,
+            vy: speed * 3.0 / 5.0,
+        }
+    }
+
+    /// Center of the ball, in pixels.
+    pub fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    /// Move on by `elapsed`, reflecting off any edge the ball reaches.
+    pub fn step(&mut self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f32();
+        (self.x, self.vx) = reflect(self.x + self.vx * secs, self.vx, self.radius, self.width);
+        (self.y, self.vy) = reflect(self.y + self.vy * secs, self.vy, self.radius, self.height);
+    }
+
+    /// The ball in `color` on black.
+    pub fn frame(&self, color: Color) -> RgbImage {
+        let r2 = self.radius * self.radius;
+        RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let dx = x as f32 + 0.5 - self.x;
+            let dy = y as f32 + 0.5 - self.y;
+            if dx * dx + dy * dy <= r2 {
+                Rgb::from(color)
+            } else {
+                Rgb([0, 0, 0])
+            }
+        })
+    }
+}
+
+/// Keep a ball of `radius` centered at `pos` within `0..size`, mirroring
+/// any overshoot back inside and turning the velocity around.
+fn reflect(pos: f32, velocity: f32, radius: f32, size: f32) -> (f32, f32) {
+    let (low, high) = (radius, (size - radius).max(radius));
+    if pos < low {
+        ((2.0 * low - pos).min(high), velocity.abs())
+    } else if pos > high {
+        ((2.0 * high - pos).max(low), -velocity.abs())
+    } else {
+        (pos, velocity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn block_is_a_still_life() {
+        let block = Life::from_cells(6, 6, &[(2, 2), (3, 2), (2, 3), (3, 3)]);
+        assert_eq!(block.step(), block);
+    }
+
+    #[test]
+    fn blinker_has_period_two() {
+        let horizontal = Life::from_cells(5, 5, &[(1, 2), (2, 2), (3, 2)]);
+        let vertical = Life::from_cells(5, 5, &[(2, 1), (2, 2), (2, 3)]);
+        assert_eq!(horizontal.step(), vertical);
+        assert_eq!(horizontal.step().step(), horizontal);
+    }
+
+    #[test]
+    fn board_wraps_at_the_edges() {
+        // A blinker across the left/right edge still blinks
+        let split = Life::from_cells(5, 5, &[(4, 2), (0, 2), (1, 2)]);
+        let vertical = Life::from_cells(5, 5, &[(0, 1), (0, 2), (0, 3)]);
+        assert_eq!(split.step(), vertical);
+    }
+
+    #[test]
+    fn random_boards_follow_the_seed() {
+        let board = Life::random(32, 16, 42);
+        assert_eq!(board, Life::random(32, 16, 42));
+        assert_ne!(board, Life::random(32, 16, 43));
+        let population = board.population();
+        assert!((100..250).contains(&population), "{population} alive");
+    }
+
+    #[test]
+    fn ball_bounces_off_the_edges() {
+        // Radius 2 on a 32x32 panel, so the center stays within 2..=30,
+        // moving 8 px/s across and 6 px/s down
+        let mut ball = Bounce::new(32, 32, 10);
+        // 2 past the right edge, so 2 back from it
+        ball.step(Duration::from_secs(2));
+        assert_eq!(ball.position(), (28.0, 28.0));
+        // Now heading left, and off the bottom edge
+        ball.step(Duration::from_secs(1));
+        assert_eq!(ball.position(), (20.0, 26.0));
+        ball.step(Duration::from_secs(1));
+        assert_eq!(ball.position(), (12.0, 20.0));
+    }
+
+    #[test]
+    fn ball_is_drawn_at_its_position() {
+        let ball = Bounce::new(16, 16, 10);
+        let frame = ball.frame(Color::new(255, 0, 0));
+        assert_eq!(frame.get_pixel(8, 8), &Rgb([255, 0, 0]));
+        assert_eq!(frame.get_pixel(0, 0), &Rgb([0, 0, 0]));
+    }
+}
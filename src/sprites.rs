@@ -0,0 +1,134 @@
+//! Built-in 8x8 icons for status displays: check marks, arrows, weather.
+//!
+//! BDF fonts don't cover emoji or symbols, so a few common ones are bundled
+//! here as one-bit bitmaps, one byte per row with the most significant bit
+//! on the left. Sprites have no color of their own; they're drawn in
+//! whatever color the request asks for, at any whole-number scale.
+//!
+//! ## Rust concepts
+//! - `const` arrays of tuples as a small compile-time lookup table
+//! - Bit masks (`0x80 >> col`) to read one pixel out of a byte
+//! - `u32::try_from` to clip drawing at the frame edges
+
+use image::{Rgb, RgbImage};
+
+/// Width and height of every sprite, in pixels.
+pub const SPRITE_SIZE: u32 = 8;
+
+/// A one-bit sprite: each byte is a row, bit 7 is the leftmost pixel.
+pub type Sprite = [u8; 8];
+
+const SPRITES: &[(&str, Sprite)] = &[
+    ("check", [0x00, 0x01, 0x03, 0x86, 0xCC, 0x78, 0x30, 0x00]),
+    ("cross", [0x81, 0xC3, 0x66, 0x3C, 0x3C, 0x66, 0xC3, 0x81]),
+    ("up", [0x18, 0x18, 0x3C, 0x3C, 0x7E, 0x7E, 0xFF, 0xFF]),
+    ("down", [0xFF, 0xFF, 0x7E, 0x7E, 0x3C, 0x3C, 0x18, 0x18]),
+    ("left", [0x03, 0x0F, 0x3F, 0xFF, 0xFF, 0x3F, 0x0F, 0x03]),
+    ("right", [0xC0, 0xF0, 0xFC, 0xFF, 0xFF, 0xFC, 0xF0, 0xC0]),
+    ("heart", [0x66, 0xFF, 0xFF, 0xFF, 0x7E, 0x3C, 0x18, 0x00]),
+    ("dot", [0x00, 0x3C, 0x7E, 0x7E, 0x7E, 0x7E, 0x3C, 0x00]),
+    ("sun", [0x99, 0x42, 0x3C, 0xBD, 0xBD, 0x3C, 0x42, 0x99]),
+    ("cloud", [0x00, 0x0C, 0x1E, 0x7E, 0xFF, 0xFF, 0x7E, 0x00]),
+    ("rain", [0x0C, 0x1E, 0x7E, 0xFF, 0x7E, 0x00, 0x49, 0x92]),
+    ("snow", [0x10, 0x54, 0x38, 0xFE, 0x38, 0x54, 0x10, 0x00]),
+];
+
+/// The built-in sprite called `name`, if there is one.
+pub fn get(name: &str) -> Option<Sprite> {
+    SPRITES
+        .iter()
+        .find(|(sprite_name, _)| *sprite_name == name)
+        .map(|&(_, sprite)| sprite)
+}
+
+/// Names of all built-in sprites, in a stable order.
+pub fn names() -> impl Iterator<Item = &'static str> {
+    SPRITES.iter().map(|&(name, _)| name)
+}
+
+/// Draw `sprite` onto `frame` with its top-left corner at `(x, y)`, each
+/// sprite pixel `scale` pixels wide. Only set bits are drawn, so whatever
+/// is under the sprite shows through; anything off the frame is clipped.
+pub fn blit(frame: &mut RgbImage, sprite: &Sprite, x: i32, y: i32, scale: u32, color: Rgb<u8>) {
+    // i64 so a position near the i32 limits can't overflow
+    let scale = scale.max(1) as i64;
+    for (row, bits) in sprite.iter().enumerate() {
+        for col in 0..SPRITE_SIZE {
+            if bits & (0x80 >> col) == 0 {
+                continue;
+            }
+            let left = x as i64 + col as i64 * scale;
+            let top = y as i64 + row as i64 * scale;
+            for py in top..top + scale {
+                for px in left..left + scale {
+                    if let (Ok(px), Ok(py)) = (u32::try_from(px), u32::try_from(py))
+                        && px < frame.width()
+                        && py < frame.height()
+                    {
+                        frame.put_pixel(px, py, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const ON: Rgb<u8> = Rgb([0, 255, 0]);
+    const UNDER: Rgb<u8> = Rgb([10, 10, 10]);
+
+    #[test]
+    fn looks_up_sprites_by_name() {
+        assert_eq!(get("heart"), Some(SPRITES[6].1));
+        assert_eq!(get("unicorn"), None);
+        assert_eq!(names().count(), SPRITES.len());
+    }
+
+    #[test]
+    fn sprite_names_are_unique() {
+        let mut all: Vec<_> = names().collect();
+        all.sort_unstable();
+        all.dedup();
+        assert_eq!(all.len(), SPRITES.len());
+    }
+
+    #[test]
+    fn blit_draws_set_bits_over_the_frame() {
+        let mut frame = RgbImage::from_pixel(8, 8, UNDER);
+        blit(&mut frame, &get("up").unwrap(), 0, 0, 1, ON);
+
+        // Top row is `...##...`
+        assert_eq!(*frame.get_pixel(2, 0), UNDER);
+        assert_eq!(*frame.get_pixel(3, 0), ON);
+        assert_eq!(*frame.get_pixel(4, 0), ON);
+        assert_eq!(*frame.get_pixel(5, 0), UNDER);
+        // Bottom row is solid
+        assert!((0..8).all(|x| *frame.get_pixel(x, 7) == ON));
+    }
+
+    #[test]
+    fn blit_scales_each_pixel() {
+        let mut frame = RgbImage::from_pixel(16, 16, UNDER);
+        blit(&mut frame, &get("up").unwrap(), 0, 0, 2, ON);
+
+        // Sprite pixel (3, 0) covers frame pixels (6..8, 0..2)
+        assert_eq!(*frame.get_pixel(6, 0), ON);
+        assert_eq!(*frame.get_pixel(7, 1), ON);
+        assert_eq!(*frame.get_pixel(5, 0), UNDER);
+    }
+
+    #[test]
+    fn blit_clips_at_the_edges() {
+        let mut frame = RgbImage::from_pixel(4, 4, UNDER);
+        blit(&mut frame, &get("cross").unwrap(), -4, -4, 1, ON);
+
+        // Only the sprite's bottom-right quarter lands on the frame
+        assert_eq!(*frame.get_pixel(3, 3), ON);
+        assert_eq!(*frame.get_pixel(0, 0), ON);
+        assert_eq!(*frame.get_pixel(3, 0), UNDER);
+    }
+}
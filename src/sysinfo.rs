@@ -0,0 +1,130 @@
+//! System stats for the built-in dashboard: CPU temperature, load, free
+//! memory, and IP address.
+//!
+//! Everything is read from `/proc` and `/sys`, so a headless Pi can show
+//! its own health without a client. Each value is optional: a missing file
+//! (e.g. no thermal zone on a desktop) shows as `--` instead of failing the
+//! whole dashboard.
+//!
+//! ## Rust concepts
+//! - `Option` chaining with `?` inside functions returning `Option`
+//! - `str::lines` and `split_whitespace` to pick fields out of text files
+//! - Connecting a UDP socket to learn the outgoing address (no packet is sent)
+
+use std::net::{IpAddr, UdpSocket};
+
+const THERMAL_PATH: &str = "/sys/class/thermal/thermal_zone0/temp";
+const LOADAVG_PATH: &str = "/proc/loadavg";
+const MEMINFO_PATH: &str = "/proc/meminfo";
+
+/// CPU temperature in °C from a thermal zone's `temp` file (millidegrees).
+pub fn parse_thermal(s: &str) -> Option<f32> {
+    let millidegrees: i32 = s.trim().parse().ok()?;
+    Some(millidegrees as f32 / 1000.0)
+}
+
+/// One-minute load average, the first field of `/proc/loadavg`.
+pub fn parse_loadavg(s: &str) -> Option<f32> {
+    s.split_whitespace().next()?.parse().ok()
+}
+
+/// Memory available to new programs in MiB, from `/proc/meminfo`.
+pub fn parse_mem_available(s: &str) -> Option<u64> {
+    let line = s.lines().find(|line| line.starts_with("MemAvailable:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib / 1024)
+}
+
+/// The address this machine uses to reach the outside, if it has a route.
+fn local_ip() -> Option<IpAddr> {
+    // Connecting a UDP socket only picks a route; nothing goes on the wire
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    Some(socket.local_addr().ok()?.ip())
+}
+
+/// One reading of every stat shown on the dashboard.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SysInfo {
+    pub cpu_temp: Option<f32>,
+    pub load: Option<f32>,
+    pub mem_available_mb: Option<u64>,
+    pub ip: Option<IpAddr>,
+}
+
+impl SysInfo {
+    /// Read the current stats. Values that can't be read are left `None`.
+    pub fn read() -> Self {
+        let read = |path| std::fs::read_to_string(path).ok();
+        Self {
+            cpu_temp: read(THERMAL_PATH).as_deref().and_then(parse_thermal),
+            load: read(LOADAVG_PATH).as_deref().and_then(parse_loadavg),
+            mem_available_mb: read(MEMINFO_PATH).as_deref().and_then(parse_mem_available),
+            ip: local_ip(),
+        }
+    }
+
+    /// Dashboard lines, short enough for a 64-pixel-wide panel in a 6x13
+    /// font (except a full IPv6 address, which is clipped).
+    pub fn lines(&self) -> Vec<String> {
+        fn or_dashes(value: Option<String>) -> String {
+            value.unwrap_or_else(|| "--".to_string())
+        }
+        vec![
+            format!(
+                "CPU {}",
+                or_dashes(self.cpu_temp.map(|t| format!("{t:.1}C")))
+            ),
+            format!("Load {}", or_dashes(self.load.map(|l| format!("{l:.2}")))),
+            format!(
+                "Mem {}",
+                or_dashes(self.mem_available_mb.map(|mb| format!("{mb}M")))
+            ),
+            or_dashes(self.ip.map(|ip| ip.to_string())),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("52312\n", Some(52.312))]
+    #[case("-5000", Some(-5.0))]
+    #[case("", None)]
+    fn parses_thermal_millidegrees(#[case] s: &str, #[case] expected: Option<f32>) {
+        assert_eq!(parse_thermal(s), expected);
+    }
+
+    #[test]
+    fn parses_first_load_average() {
+        assert_eq!(parse_loadavg("0.42 0.30 0.25 1/123 4567\n"), Some(0.42));
+        assert_eq!(parse_loadavg(""), None);
+    }
+
+    #[test]
+    fn parses_mem_available() {
+        let meminfo = "MemTotal:        3884200 kB\n\
+                       MemFree:          120000 kB\n\
+                       MemAvailable:    2048000 kB\n";
+        assert_eq!(parse_mem_available(meminfo), Some(2000));
+        assert_eq!(parse_mem_available("MemTotal: 1 kB\n"), None);
+    }
+
+    #[test]
+    fn lines_show_dashes_for_missing_values() {
+        let info = SysInfo {
+            cpu_temp: Some(48.25),
+            load: None,
+            mem_available_mb: Some(512),
+            ip: Some(IpAddr::from([192, 168, 1, 20])),
+        };
+        assert_eq!(
+            info.lines(),
+            vec!["CPU 48.2C", "Load --", "Mem 512M", "192.168.1.20"]
+        );
+    }
+}
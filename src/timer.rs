@@ -0,0 +1,76 @@
+//! Countdown timers: the `mm:ss` readout and what happens at zero.
+//!
+//! The render thread redraws the readout whenever the whole number of
+//! seconds left changes, counting from when the timer started rather than
+//! adding up sleeps, so a slow frame never makes the timer run late.
+//!
+//! ## Rust concepts
+//! - Externally tagged enums: `"flash"` or `{"text": "Done!"}` in JSON
+//! - `div_ceil` so a timer shows `00:01` until the last second is fully up
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// What a timer does when it reaches zero.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerFinish {
+    /// Flash `00:00` a few times, then leave it up
+    #[default]
+    Flash,
+    /// Blank the panel
+    Clear,
+    /// Replace the readout with this text, wrapped and centered
+    Text(String),
+}
+
+/// `seconds` as minutes and seconds, e.g. `01:05`. Minutes keep counting
+/// past 59 rather than rolling over into hours.
+pub fn format_mm_ss(seconds: u32) -> String {
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Whole seconds left of a `seconds`-long timer after `elapsed`, rounded
+/// up so the readout only reaches zero when the time is up.
+pub fn seconds_left(seconds: u32, elapsed: Duration) -> u32 {
+    let left_ms = (seconds as u128 * 1000).saturating_sub(elapsed.as_millis());
+    left_ms.div_ceil(1000) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, "00:00")]
+    #[case(59, "00:59")]
+    #[case(60, "01:00")]
+    #[case(3661, "61:01")]
+    fn formats_minutes_and_seconds(#[case] seconds: u32, #[case] expected: &str) {
+        assert_eq!(format_mm_ss(seconds), expected);
+    }
+
+    #[rstest]
+    #[case(Duration::ZERO, 90)]
+    #[case(Duration::from_millis(1), 90)]
+    #[case(Duration::from_millis(999), 90)]
+    #[case(Duration::from_secs(1), 89)]
+    #[case(Duration::from_millis(89_500), 1)]
+    #[case(Duration::from_secs(90), 0)]
+    #[case(Duration::from_secs(100), 0)]
+    fn rounds_the_time_left_up(#[case] elapsed: Duration, #[case] expected: u32) {
+        assert_eq!(seconds_left(90, elapsed), expected);
+    }
+
+    #[test]
+    fn finish_actions_in_json() {
+        let parse = |json| serde_json::from_str::<TimerFinish>(json).unwrap();
+        assert_eq!(parse(r#""clear""#), TimerFinish::Clear);
+        assert_eq!(
+            parse(r#"{"text": "Done!"}"#),
+            TimerFinish::Text("Done!".to_string())
+        );
+    }
+}